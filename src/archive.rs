@@ -0,0 +1,99 @@
+use std::error::Error;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use simple_reversi::game::Game;
+
+use crate::messages::msg;
+use crate::saved::SavedGame;
+
+/// 終局した対局を記録しておくディレクトリ（ユーザーのデータディレクトリの```simple-reversi/games```）
+///
+/// データディレクトリは```XDG_DATA_HOME```，```APPDATA```，```HOME```の```.local/share```の順に探す．
+pub fn dir() -> Option<PathBuf> {
+    let env = |key| std::env::var_os(key).filter(|v| !v.is_empty());
+    let base = env("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env("APPDATA").map(PathBuf::from))
+        .or_else(|| env("HOME").map(|h| PathBuf::from(h).join(".local").join("share")))?;
+    Some(base.join("simple-reversi").join("games"))
+}
+
+/// 終局したgameを，終局した日時（UTC）のファイル名でJSONにして記録する
+///
+/// 同じ秒に終局した対局があれば，ファイル名の後ろに番号をつける．
+pub fn store(game: &Game, saved: &SavedGame) -> Result<PathBuf, Box<dyn Error>> {
+    let dir = dir().ok_or("no data directory")?;
+    std::fs::create_dir_all(&dir)?;
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let (y, mo, d, h, mi, s) = civil(secs);
+    let stem = format!("{:04}{:02}{:02}-{:02}{:02}{:02}", y, mo, d, h, mi, s);
+    let mut path = dir.join(format!("{}.json", stem));
+    let mut n = 1;
+    while path.exists() {
+        n += 1;
+        path = dir.join(format!("{}-{}.json", stem, n));
+    }
+    game.save_json(&path, saved)
+        .map_err(|e| msg().game_file_error(&e))?;
+    Ok(path)
+}
+
+/// 記録した対局のファイルの一覧（古い順．ディレクトリがなければ空）
+pub fn entries() -> io::Result<Vec<PathBuf>> {
+    let dir = match dir() {
+        Some(dir) if dir.exists() => dir,
+        _ => return Ok(Vec::new()),
+    };
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|x| x == "json"))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// ファイル名から読んだ終局の日時（```2026-10-16 15:30```の形式．読めなければファイル名のまま）
+pub fn date(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let digits: String = stem.chars().filter(char::is_ascii_digit).take(12).collect();
+    if digits.len() < 12 {
+        return stem;
+    }
+    format!(
+        "{}-{}-{} {}:{}",
+        &digits[0..4],
+        &digits[4..6],
+        &digits[6..8],
+        &digits[8..10],
+        &digits[10..12]
+    )
+}
+
+/// UNIX時間secsのUTCでの年，月，日，時，分，秒
+fn civil(secs: u64) -> (i64, u32, u32, u32, u32, u32) {
+    let days = (secs / 86400) as i64;
+    let rem = secs % 86400;
+    // 3月1日を年の始まりとして，400年ごとの周期で数える
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (
+        year,
+        month,
+        day,
+        (rem / 3600) as u32,
+        (rem % 3600 / 60) as u32,
+        (rem % 60) as u32,
+    )
+}
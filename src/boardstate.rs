@@ -1,41 +1,304 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ReversiError;
+
 /// どちらのターンかを判定する列挙型
 ///
 /// 駒などを判別するのにも使う。
 /// 値を代入するときにムーヴだと面倒なのでCopyトレイトを実装。
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-enum Turn {
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Player {
     White,
     Black,
 }
 
+/// マス目の状態を表す列挙型
+///
+/// ```Empty```は駒が置かれていないマス、```Blocked```は塞がれていて置けないマス、
+/// ```Occupied```はどちらかの駒が置かれているマス。
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Cell {
+    Empty,
+    Blocked,
+    Occupied(Player),
+}
+
+impl Player {
+    /// 相手
+    pub fn opponent(self) -> Player {
+        match self {
+            Player::White => Player::Black,
+            Player::Black => Player::White,
+        }
+    }
+
+    /// 駒の文字
+    pub fn piece(self) -> char {
+        match self {
+            Player::White => WHITE,
+            Player::Black => BLACK,
+        }
+    }
+
+    /// 駒の文字から変換する（駒の文字でなければNone）
+    pub fn from_piece(c: char) -> Option<Player> {
+        match c {
+            WHITE => Some(Player::White),
+            BLACK => Some(Player::Black),
+            _ => None,
+        }
+    }
+}
+
+impl Cell {
+    /// マス目の状態を表す文字
+    pub fn to_char(self) -> char {
+        match self {
+            Cell::Empty => NO_PIECE,
+            Cell::Blocked => BLOCKED,
+            Cell::Occupied(p) => p.piece(),
+        }
+    }
+}
+
+/// 盤面上のマス目の座標
+///
+/// rowが行番号、colが列番号で、どちらも0始まり。
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Coord {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Coord {
+    /// 新しい座標を作成する
+    pub fn new(row: usize, col: usize) -> Coord {
+        Coord { row, col }
+    }
+
+    /// 列を表す英字（0列目が```a```、25列目が```z```で、その右は```aa```、```ab```、……）
+    pub fn column_name(col: usize) -> String {
+        let mut name = Vec::new();
+        let mut n = col + 1;
+        while n > 0 {
+            n -= 1;
+            name.push((b'a' + (n % 26) as u8) as char);
+            n /= 26;
+        }
+        name.iter().rev().collect()
+    }
+}
+
+/// 列の英字と1始まりの行番号を続けた形式（```d3```のような形式）で表示する
+impl fmt::Display for Coord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", Coord::column_name(self.col), self.row + 1)
+    }
+}
+
+/// 列の英字と1始まりの行番号を続けた形式（```d3```や```D3```）から読み込む
+impl FromStr for Coord {
+    type Err = ReversiError;
+
+    fn from_str(s: &str) -> Result<Coord, ReversiError> {
+        let s = s.trim();
+        let err = || ReversiError::UnknownCoord(s.to_string());
+        let digits = s.find(|c: char| !c.is_ascii_alphabetic()).ok_or_else(err)?;
+        if digits == 0 || digits > 3 {
+            return Err(err());
+        }
+        let col = s[..digits].bytes().fold(0, |n, b| {
+            n * 26 + (b.to_ascii_lowercase() - b'a') as usize + 1
+        });
+        match s[digits..].parse::<usize>() {
+            Ok(row) if row >= 1 => Ok(Coord::new(row - 1, col - 1)),
+            _ => Err(err()),
+        }
+    }
+}
+
+/// 盤面の回転・鏡映の8通りの対称変換
+///
+/// 下位のビットから順に、転置するか、行を上下反転するか、列を左右反転するかを表す
+/// （転置してから反転する）。```Symmetry(0)```は恒等変換。
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Symmetry(u8);
+
+impl Symmetry {
+    /// 恒等変換
+    pub const IDENTITY: Symmetry = Symmetry(0);
+
+    /// 8通りの対称変換を列挙する
+    pub fn all() -> impl Iterator<Item = Symmetry> {
+        (0..8).map(Symmetry)
+    }
+
+    /// 各辺sizeマスの盤面の座標を変換する
+    pub fn apply(self, size: usize, coord: Coord) -> Coord {
+        let (mut r, mut c) = (coord.row, coord.col);
+        if self.0 & 1 != 0 {
+            std::mem::swap(&mut r, &mut c);
+        }
+        if self.0 & 2 != 0 {
+            r = size - 1 - r;
+        }
+        if self.0 & 4 != 0 {
+            c = size - 1 - c;
+        }
+        Coord::new(r, c)
+    }
+
+    /// 逆変換
+    pub fn inverse(self) -> Symmetry {
+        if self.0 & 1 != 0 {
+            // 転置を挟むと行と列の反転が入れ替わる
+            Symmetry(1 | ((self.0 & 2) << 1) | ((self.0 & 4) >> 1))
+        } else {
+            self
+        }
+    }
+}
+
+/// 駒の個数の集計
+///
+/// black、whiteはそれぞれの駒の個数、emptyは駒が置かれていないマスの個数
+/// （塞がれたマスは含まない）。
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Score {
+    pub black: usize,
+    pub white: usize,
+    pub empty: usize,
+}
+
 /// 盤面の情報を持つ構造体
 ///
 /// sizeは盤面のサイズ（高さ=幅）。
-/// stateは2次元配列で、各要素は```Option<Turn>```型
-/// （```None```が駒が置かれていない状態、```Some(Turn::White）```が白い駒が置かれている状態、
-/// ```Some(Turn::Black)```が黒い駒が置かれている状態）。
+/// stateは2次元配列で、各要素は```Option<Player>```型
+/// （```None```が駒が置かれていない状態、```Some(Player::White）```が白い駒が置かれている状態、
+/// ```Some(Player::Black)```が黒い駒が置かれている状態）。
+/// blockedは駒を置けないマス（ハンデなどのために塞いだマス）かどうかの2次元配列。
 /// turnは今どっちのターンなのかの情報を持つ。
-#[derive(Debug)]
+/// hashは局面のZobristハッシュで、駒を置いたり裏返したり手番が変わったりするたびに差分で更新する。
+///
+/// 探索で盤面を複製したり、局面をHashMapのキーにしたりできるように
+/// Clone、Eq、Hashを実装している。
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct BoardState {
     size: usize,
-    state: Vec<Vec<Option<Turn>>>,
-    turn: Turn,
+    state: Vec<Vec<Option<Player>>>,
+    blocked: Vec<Vec<bool>>,
+    turn: Player,
+    hash: u64,
 }
 
 impl BoardState {
     /// 新しい盤面を作成する
     pub fn new(n: usize, white_turn: bool) -> BoardState {
         assert!(n != 0);
-        let mut s: Vec<Vec<Option<Turn>>> = vec![vec![None; 2 * n]; 2 * n];
-        s[n - 1][n - 1] = Some(Turn::White);
-        s[n - 1][n] = Some(Turn::Black);
-        s[n][n - 1] = Some(Turn::Black);
-        s[n][n] = Some(Turn::White);
+        let mut s: Vec<Vec<Option<Player>>> = vec![vec![None; 2 * n]; 2 * n];
+        s[n - 1][n - 1] = Some(Player::White);
+        s[n - 1][n] = Some(Player::Black);
+        s[n][n - 1] = Some(Player::Black);
+        s[n][n] = Some(Player::White);
+        BoardState::from_parts(
+            s,
+            vec![vec![false; 2 * n]; 2 * n],
+            if white_turn {
+                Player::White
+            } else {
+                Player::Black
+            },
+        )
+    }
+
+    /// 各マスの状態と手番から作成し、Zobristハッシュを計算する
+    fn from_parts(
+        state: Vec<Vec<Option<Player>>>,
+        blocked: Vec<Vec<bool>>,
+        turn: Player,
+    ) -> BoardState {
+        let mut hash: u64 = side_key(turn);
+        for (i, row) in state.iter().enumerate() {
+            for (j, c) in row.iter().enumerate() {
+                if let Some(p) = c {
+                    hash ^= square_key(i, j, *p);
+                }
+            }
+        }
         BoardState {
-            size: 2 * n,
-            state: s,
-            turn: if white_turn { Turn::White } else { Turn::Black },
+            size: state.len(),
+            state,
+            blocked,
+            turn,
+            hash,
+        }
+    }
+
+    /// 文字で表された盤面から作成する
+    ///
+    /// ```show_board()```と同じ文字（```.```、```o```、```#```、```x```）を並べたものを1行ずつ書く。
+    /// 行の中の空白や、空の行は無視する。
+    /// 正方形で各辺が偶数マスであること、中央の4マスに駒が置かれていることを確認する。
+    pub fn from_ascii(s: &str, white_turn: bool) -> Result<BoardState, ReversiError> {
+        let rows: Vec<Vec<char>> = s
+            .lines()
+            .map(|l| {
+                l.chars()
+                    .filter(|c| !c.is_whitespace())
+                    .collect::<Vec<char>>()
+            })
+            .filter(|r| !r.is_empty())
+            .collect();
+        let n = rows.len();
+        if n == 0 || !n.is_multiple_of(2) {
+            return Err(ReversiError::InvalidSize(n));
+        }
+
+        let mut state: Vec<Vec<Option<Player>>> = vec![vec![None; n]; n];
+        let mut blocked: Vec<Vec<bool>> = vec![vec![false; n]; n];
+        for (i, row) in rows.iter().enumerate() {
+            if row.len() != n {
+                return Err(ReversiError::RowLength {
+                    row: i,
+                    len: row.len(),
+                    size: n,
+                });
+            }
+            for (j, &ch) in row.iter().enumerate() {
+                state[i][j] = match ch {
+                    WHITE => Some(Player::White),
+                    BLACK => Some(Player::Black),
+                    NO_PIECE => None,
+                    BLOCKED => {
+                        blocked[i][j] = true;
+                        None
+                    }
+                    _ => return Err(ReversiError::UnknownChar { row: i, col: j, ch }),
+                };
+            }
         }
+
+        // 中央の4マスは最初から埋まっているので、空いていることはない
+        let h = n / 2;
+        for &(i, j) in &[(h - 1, h - 1), (h - 1, h), (h, h - 1), (h, h)] {
+            if state[i][j].is_none() {
+                return Err(ReversiError::EmptyCenter(Coord::new(i, j)));
+            }
+        }
+
+        Ok(BoardState::from_parts(
+            state,
+            blocked,
+            if white_turn {
+                Player::White
+            } else {
+                Player::Black
+            },
+        ))
     }
 
     /// 盤面の大きさを取得する
@@ -43,23 +306,55 @@ impl BoardState {
         self.size
     }
 
-    /// 盤面の状態をchar型の二次元配列で出力する
-    pub fn show_board(&self) -> Vec<Vec<char>> {
+    /// 全てのマス目の座標と状態を左上から順に列挙するイテレータ
+    pub fn cells(&self) -> impl Iterator<Item = (Coord, Cell)> + '_ {
+        self.state.iter().enumerate().flat_map(move |(i, row)| {
+            row.iter().enumerate().map(move |(j, &c)| {
+                let cell = match c {
+                    Some(p) => Cell::Occupied(p),
+                    None if self.blocked[i][j] => Cell::Blocked,
+                    None => Cell::Empty,
+                };
+                (Coord::new(i, j), cell)
+            })
+        })
+    }
+
+    /// そのマスに置かれている駒（置かれていない場合や盤面の外の場合はNone）
+    pub fn get(&self, coord: Coord) -> Option<Player> {
+        self.state
+            .get(coord.row)
+            .and_then(|row| row.get(coord.col))
+            .copied()
+            .flatten()
+    }
+
+    /// そのマスが空いているかどうか（塞がれたマスや盤面の外はfalse）
+    pub fn is_empty(&self, coord: Coord) -> bool {
+        coord.row < self.size
+            && coord.col < self.size
+            && self.state[coord.row][coord.col].is_none()
+            && !self.blocked[coord.row][coord.col]
+    }
+
+    /// 盤面の状態を```Cell```の二次元配列で出力する
+    pub fn grid(&self) -> Vec<Vec<Cell>> {
         let n = self.size;
-        let mut v: Vec<Vec<char>> = vec![vec![NO_PIECE; n]; n];
-        for i in 0..n {
-            for j in 0..n {
-                if let Some(t) = &self.state[i][j] {
-                    v[i][j] = match t {
-                        Turn::Black => BLACK,
-                        Turn::White => WHITE,
-                    }
-                }
-            }
+        let mut v: Vec<Vec<Cell>> = vec![vec![Cell::Empty; n]; n];
+        for (c, cell) in self.cells() {
+            v[c.row][c.col] = cell;
         }
         v
     }
 
+    /// 盤面の状態をchar型の二次元配列で出力する
+    pub fn show_board(&self) -> Vec<Vec<char>> {
+        self.grid()
+            .iter()
+            .map(|row| row.iter().map(|c| c.to_char()).collect())
+            .collect()
+    }
+
     /// 白い駒（char型）
     pub fn white_piece() -> char {
         WHITE
@@ -70,213 +365,736 @@ impl BoardState {
         BLACK
     }
 
+    /// 駒が置かれていないマス（char型）
+    pub fn no_piece() -> char {
+        NO_PIECE
+    }
+
+    /// 塞がれていて駒を置けないマス（char型）
+    pub fn blocked_square() -> char {
+        BLOCKED
+    }
+
+    /// 局面のZobristハッシュ
+    ///
+    /// 駒の配置と手番が同じ局面は同じ値になる。
+    pub fn zobrist_hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// 対称変換した盤面
+    pub fn transformed(&self, sym: Symmetry) -> BoardState {
+        let n = self.size;
+        let mut state: Vec<Vec<Option<Player>>> = vec![vec![None; n]; n];
+        let mut blocked: Vec<Vec<bool>> = vec![vec![false; n]; n];
+        for i in 0..n {
+            for j in 0..n {
+                let c = sym.apply(n, Coord::new(i, j));
+                state[c.row][c.col] = self.state[i][j];
+                blocked[c.row][c.col] = self.blocked[i][j];
+            }
+        }
+        BoardState::from_parts(state, blocked, self.turn)
+    }
+
+    /// 回転・鏡映した8通りの盤面（```Symmetry::all()```の順）
+    pub fn symmetries(&self) -> Vec<BoardState> {
+        Symmetry::all().map(|sym| self.transformed(sym)).collect()
+    }
+
+    /// 8通りの対称変換のうち、盤面が辞書式順序で最小になる変換
+    ///
+    /// 左上から順にマスを見て、空き・塞がれたマス・黒・白の順に小さいとみなす。
+    pub fn canonical_symmetry(&self) -> Symmetry {
+        Symmetry::all()
+            .min_by_key(|&sym| {
+                let t = self.transformed(sym);
+                t.cells()
+                    .map(|(_, cell)| match cell {
+                        Cell::Empty => 0u8,
+                        Cell::Blocked => 1,
+                        Cell::Occupied(Player::Black) => 2,
+                        Cell::Occupied(Player::White) => 3,
+                    })
+                    .collect::<Vec<u8>>()
+            })
+            .unwrap()
+    }
+
+    /// 回転・鏡映で移り合う盤面の代表（辞書式順序で最小のもの）
+    pub fn canonical_form(&self) -> BoardState {
+        self.transformed(self.canonical_symmetry())
+    }
+
+    /// 局面を1行の文字列で表す（チェスのFENに似た形式）
+    ///
+    /// 上の行から順に```/```で区切って並べ、続けて空白で区切って手番（```b```か```w```）と盤面の大きさを書く。
+    /// 各行は```show_board()```と同じ文字で、続いた空きマスはその個数の数字で表す。
+    /// 例えば8×8の初期配置は```8/8/8/3o#3/3#o3/8/8/8 b 8```。
+    pub fn to_position_string(&self) -> String {
+        let rows: Vec<String> = self
+            .grid()
+            .iter()
+            .map(|cells| {
+                let mut row = String::new();
+                let mut empties = 0;
+                for cell in cells {
+                    if *cell == Cell::Empty {
+                        empties += 1;
+                        continue;
+                    }
+                    if empties > 0 {
+                        row.push_str(&empties.to_string());
+                        empties = 0;
+                    }
+                    row.push(cell.to_char());
+                }
+                if empties > 0 {
+                    row.push_str(&empties.to_string());
+                }
+                row
+            })
+            .collect();
+        let side = match self.turn {
+            Player::Black => 'b',
+            Player::White => 'w',
+        };
+        format!("{} {} {}", rows.join("/"), side, self.size)
+    }
+
+    /// ```to_position_string```の形式の文字列から作成する
+    ///
+    /// 行の数と各行のマス目の数が盤面の大きさと合っていることを確認する。
+    /// 中央の4マスが空いていてもよい（```BoardBuilder```と同じ）。
+    pub fn from_position_string(s: &str) -> Result<BoardState, ReversiError> {
+        let invalid = || ReversiError::InvalidPosition(s.trim().to_string());
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        let (grid, side, size) = match fields[..] {
+            [grid, side, size] => (grid, side, size),
+            _ => return Err(invalid()),
+        };
+        let size: usize = size.parse().map_err(|_| invalid())?;
+        let turn = match side {
+            "b" | "B" => Player::Black,
+            "w" | "W" => Player::White,
+            _ => return Err(invalid()),
+        };
+        let rows: Vec<&str> = grid.split('/').collect();
+        if rows.len() != size {
+            return Err(invalid());
+        }
+
+        let mut builder = BoardBuilder::new(size).without_initial_discs().turn(turn);
+        for (row, line) in rows.iter().enumerate() {
+            let mut col = 0;
+            let mut empties = 0;
+            for ch in line.chars() {
+                if let Some(d) = ch.to_digit(10) {
+                    empties = empties * 10 + d as usize;
+                    continue;
+                }
+                col += empties;
+                empties = 0;
+                if col < size {
+                    let c = Coord::new(row, col);
+                    builder = match ch {
+                        NO_PIECE => builder,
+                        BLOCKED => builder.blocked(c),
+                        _ => match Player::from_piece(ch) {
+                            Some(p) => builder.disc(c, p),
+                            None => return Err(ReversiError::UnknownChar { row, col, ch }),
+                        },
+                    };
+                }
+                col += 1;
+            }
+            col += empties;
+            if col != size {
+                return Err(ReversiError::RowLength {
+                    row,
+                    len: col,
+                    size,
+                });
+            }
+        }
+        builder.build()
+    }
+
+    /// どちらのターンか
+    pub fn turn(&self) -> Player {
+        self.turn
+    }
+
     /// どちらのターンかを駒の文字で出力
     pub fn which_turn(&self) -> char {
-        match self.turn {
-            Turn::Black => BLACK,
-            Turn::White => WHITE,
-        }
+        self.turn.piece()
     }
 
     /// 白の番かどうか
     pub fn is_it_white_turn(&self) -> bool {
-        self.turn == Turn::White
+        self.turn == Player::White
     }
 
-    /// 駒の個数を出力
-    pub fn count_pieces(&self) -> ((char, usize), (char, usize)) {
-        let mut white_count: usize = 0;
-        let mut black_count: usize = 0;
+    /// playerの駒の個数
+    pub fn count(&self, player: Player) -> usize {
+        self.state
+            .iter()
+            .flatten()
+            .filter(|&&c| c == Some(player))
+            .count()
+    }
+
+    /// 駒の個数を集計する
+    pub fn score(&self) -> Score {
+        let mut score = Score {
+            black: 0,
+            white: 0,
+            empty: 0,
+        };
+        for (_, cell) in self.cells() {
+            match cell {
+                Cell::Occupied(Player::Black) => score.black += 1,
+                Cell::Occupied(Player::White) => score.white += 1,
+                Cell::Empty => score.empty += 1,
+                Cell::Blocked => {}
+            }
+        }
+        score
+    }
+
+    /// playerから見た駒の個数の差（自分の駒の個数 - 相手の駒の個数）
+    pub fn disc_difference(&self, player: Player) -> i32 {
+        self.count(player) as i32 - self.count(player.opponent()) as i32
+    }
+
+    /// 今後どう打たれても裏返されることのないplayerの駒（確定石）の座標の一覧
+    ///
+    /// 縦・横・斜めの4本の線それぞれについて、
+    /// 隣が盤面の端か塞がれたマスか自分の確定石であるか、線上に空きマスがない場合に
+    /// その方向では裏返されないとみなし、4本とも裏返されない駒を確定石とする。
+    /// 確定石が増えなくなるまで繰り返すので、隅から伸びる確定石も見つかる。
+    /// （見つけられるのは確定石の一部である場合もある。）
+    pub fn stable_discs(&self, player: Player) -> Vec<Coord> {
         let n = self.size;
-        for i in 0..n {
-            for j in 0..n {
-                if let Some(t) = &self.state[i][j] {
-                    match t {
-                        Turn::White => white_count += 1,
-                        Turn::Black => black_count += 1,
+        let mut stable: Vec<Vec<bool>> = vec![vec![false; n]; n];
+        loop {
+            let mut changed = false;
+            for i in 0..n {
+                for j in 0..n {
+                    if stable[i][j] || self.state[i][j] != Some(player) {
+                        continue;
                     }
+                    if AXES
+                        .iter()
+                        .all(|&(di, dj)| self.is_stable_along(&stable, i, j, di, dj))
+                    {
+                        stable[i][j] = true;
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        self.cells()
+            .map(|(c, _)| c)
+            .filter(|c| stable[c.row][c.col])
+            .collect()
+    }
+
+    /// (i, j)の駒が(di, dj)の方向の線に沿って裏返されないかどうか
+    fn is_stable_along(&self, stable: &[Vec<bool>], i: usize, j: usize, di: i32, dj: i32) -> bool {
+        let n = self.size;
+
+        // どちらかの隣が端・塞がれたマス・確定石なら裏返されない
+        for &(si, sj) in &[(di, dj), (-di, -dj)] {
+            let x = i as i32 + si;
+            let y = j as i32 + sj;
+            if !BoardState::in_range(x, n) || !BoardState::in_range(y, n) {
+                return true;
+            }
+            let (x, y) = (x as usize, y as usize);
+            if self.blocked[x][y] || stable[x][y] {
+                return true;
+            }
+        }
+
+        // 線上（端か塞がれたマスまで）に空きマスがなければ裏返されない
+        for &(si, sj) in &[(di, dj), (-di, -dj)] {
+            let mut x = i as i32 + si;
+            let mut y = j as i32 + sj;
+            while BoardState::in_range(x, n) && BoardState::in_range(y, n) {
+                let (ux, uy) = (x as usize, y as usize);
+                if self.blocked[ux][uy] {
+                    break;
+                }
+                if self.state[ux][uy].is_none() {
+                    return false;
                 }
+                x += si;
+                y += sj;
             }
         }
-        ((WHITE, white_count), (BLACK, black_count))
+        true
+    }
+
+    /// 駒の個数を出力
+    pub fn count_pieces(&self) -> ((char, usize), (char, usize)) {
+        (
+            (WHITE, self.count(Player::White)),
+            (BLACK, self.count(Player::Black)),
+        )
     }
 
     /// そこに置いたときに裏返せる駒の個数
     pub fn cnt_reversable(&self) -> Vec<Vec<usize>> {
         let n = self.size;
         let mut vec: Vec<Vec<usize>> = vec![vec![0; n]; n];
+        for (i, row) in vec.iter_mut().enumerate() {
+            for (j, c) in row.iter_mut().enumerate() {
+                *c = self.cnt_reversable_at(self.turn, i, j);
+            }
+        }
+        vec
+    }
+
+    /// playerが(i, j)に置いたときに裏返せる駒の個数
+    fn cnt_reversable_at(&self, player: Player, i: usize, j: usize) -> usize {
+        if self.state[i][j].is_some() || self.blocked[i][j] {
+            // もう置いてあるマスや塞がれたマスはスルー
+            return 0;
+        }
+        (0..8)
+            .map(|k| self.cnt_reversable_toward(player, i, j, k))
+            .sum()
+    }
+
+    /// playerが(i, j)に置いたときにk番目の方向に裏返せる駒の個数
+    ///
+    /// (i, j)が空いているかどうかは見ないので、呼び出し側で確認すること。
+    fn cnt_reversable_toward(&self, player: Player, i: usize, j: usize, k: usize) -> usize {
+        let n = self.size;
         let s = &self.state;
-        for i in 0..n {
-            for j in 0..n {
-                if let Some(_) = s[i][j] {
-                    // もう置いてあるマスはスルー
-                    continue;
+
+        // まず1マス隣
+        let new_x: i32 = i as i32 + dx(k);
+        let new_y: i32 = j as i32 + dy(k);
+
+        // 盤面から出ていた場合
+        if !BoardState::in_range(new_x, n) || !BoardState::in_range(new_y, n) {
+            return 0;
+        }
+        let new_x: usize = new_x as usize;
+        let new_y: usize = new_y as usize;
+
+        // 隣のマスが空ならもう処理はいらない
+        if let Some(t) = s[new_x][new_y] {
+            // 隣のマスが自分と同じ色ならもう処理はいらない
+            if t == player {
+                return 0;
+            }
+            // 隣のマスが自分と違う色のときだけ進んで行く
+            for l in 1..n {
+                let new_x: i32 = new_x as i32 + l as i32 * dx(k);
+                let new_y: i32 = new_y as i32 + l as i32 * dy(k);
+
+                // 盤面から出たら終了
+                if !BoardState::in_range(new_x, n) || !BoardState::in_range(new_y, n) {
+                    break;
                 }
-                for k in 0..8 {
-                    // 進む方向ごとに判定
+                let new_x: usize = new_x as usize;
+                let new_y: usize = new_y as usize;
 
-                    // まず1マス隣
-                    let new_x: i32 = i as i32 + dx(k);
-                    let new_y: i32 = j as i32 + dy(k);
+                // 空のマスに着いたら終了
+                if s[new_x][new_y].is_none() {
+                    break;
+                }
 
-                    // 盤面から出ていた場合
-                    if !BoardState::in_range(new_x, n) || !BoardState::in_range(new_y, n) {
-                        continue;
-                    }
-                    let new_x: usize = new_x as usize;
-                    let new_y: usize = new_y as usize;
-
-                    // 隣のマスが空ならもう処理はいらない
-                    if let Some(t) = s[new_x][new_y] {
-                        // 隣のマスが自分と同じ色ならもう処理はいらない
-                        if t == self.turn {
-                            continue;
-                        }
-                        // 隣のマスが自分と違う色のときだけ進んで行く
-                        for l in 1..n {
-                            let new_x: i32 = new_x as i32 + l as i32 * dx(k);
-                            let new_y: i32 = new_y as i32 + l as i32 * dy(k);
-
-                            // 盤面から出たら終了
-                            if !BoardState::in_range(new_x, n) || !BoardState::in_range(new_y, n) {
-                                break;
-                            }
-                            let new_x: usize = new_x as usize;
-                            let new_y: usize = new_y as usize;
-
-                            // 空のマスに着いたら終了
-                            if let None = s[new_x][new_y] {
-                                break;
-                            }
-
-                            // 自分と同じ色が再び現れたらこのときだけ裏返せるので
-                            // 裏返せる枚数をカウントアップ
-                            if let Some(t) = s[new_x][new_y] {
-                                if t == self.turn {
-                                    vec[i][j] += l;
-                                    break;
-                                }
-                            }
-                        }
+                // 自分と同じ色が再び現れたらこのときだけ裏返せるので
+                // 裏返せる枚数を返す
+                if let Some(t) = s[new_x][new_y] {
+                    if t == player {
+                        return l;
                     }
                 }
             }
         }
-        vec
+        0
+    }
+
+    /// そのマスに置いたときに裏返る駒の座標の一覧
+    ///
+    /// 置けないマスの場合は空のVecを返す。
+    pub fn flips_for(&self, coord: Coord) -> Vec<Coord> {
+        let (i, j) = (coord.row, coord.col);
+        let mut v: Vec<Coord> = Vec::new();
+        if i >= self.size || j >= self.size || self.cnt_reversable_at(self.turn, i, j) == 0 {
+            return v;
+        }
+        for k in 0..8 {
+            // 方向ごとに、間に挟まれている駒を順に追加していく
+            for m in 1..=self.cnt_reversable_toward(self.turn, i, j, k) {
+                v.push(Coord::new(
+                    (i as i32 + m as i32 * dx(k)) as usize,
+                    (j as i32 + m as i32 * dy(k)) as usize,
+                ));
+            }
+        }
+        v
+    }
+
+    /// そのマスに置けるかどうか
+    pub fn is_legal(&self, coord: Coord) -> bool {
+        coord.row < self.size
+            && coord.col < self.size
+            && self.cnt_reversable_at(self.turn, coord.row, coord.col) > 0
     }
 
-    /// 置けるかどうかを判定
-    fn puttable(&self) -> bool {
+    /// 置けるマス目を左上から順に列挙するイテレータ
+    pub fn legal_moves(&self) -> impl Iterator<Item = Coord> + '_ {
         let n = self.size;
-        let vec = self.cnt_reversable();
-        let mut flag: bool = false;
-        for i in 0..n {
-            for j in 0..n {
-                if vec[i][j] > 0 {
-                    flag = true;
+        (0..n)
+            .flat_map(move |i| (0..n).map(move |j| Coord::new(i, j)))
+            .filter(move |&c| self.is_legal(c))
+    }
+
+    /// playerに置けるマスがあるかどうかを判定
+    pub fn has_legal_move(&self, player: Player) -> bool {
+        let n = self.size;
+        (0..n).any(|i| (0..n).any(|j| self.cnt_reversable_at(player, i, j) > 0))
+    }
+
+    /// playerが置けるマスの個数（着手可能数）
+    pub fn mobility(&self, player: Player) -> usize {
+        let n = self.size;
+        (0..n)
+            .map(|i| {
+                (0..n)
+                    .filter(|&j| self.cnt_reversable_at(player, i, j) > 0)
+                    .count()
+            })
+            .sum()
+    }
+
+    /// 相手の駒に隣接している空きマスの個数（潜在的な着手可能数）
+    ///
+    /// 今は置けなくても、今後置けるようになる可能性があるマスの多さの目安。
+    pub fn potential_mobility(&self, player: Player) -> usize {
+        let n = self.size;
+        let opponent = player.opponent();
+        self.cells()
+            .filter(|&(c, cell)| {
+                cell == Cell::Empty
+                    && (0..8).any(|k| {
+                        let x = c.row as i32 + dx(k);
+                        let y = c.col as i32 + dy(k);
+                        BoardState::in_range(x, n)
+                            && BoardState::in_range(y, n)
+                            && self.state[x as usize][y as usize] == Some(opponent)
+                    })
+            })
+            .count()
+    }
+
+    /// 空きマスに隣接しているplayerの駒（開放的な駒）の個数
+    ///
+    /// 空きマスごとに周りの駒に印をつけていくので、盤面を1回走査するだけで求まる。
+    pub fn frontier_discs(&self, player: Player) -> usize {
+        let n = self.size;
+        let mut frontier: Vec<Vec<bool>> = vec![vec![false; n]; n];
+        for (c, cell) in self.cells() {
+            if cell != Cell::Empty {
+                continue;
+            }
+            for k in 0..8 {
+                let x = c.row as i32 + dx(k);
+                let y = c.col as i32 + dy(k);
+                if BoardState::in_range(x, n) && BoardState::in_range(y, n) {
+                    frontier[x as usize][y as usize] = true;
                 }
             }
         }
-        flag
+        self.cells()
+            .filter(|&(c, cell)| cell == Cell::Occupied(player) && frontier[c.row][c.col])
+            .count()
+    }
+
+    /// 4つの隅の座標
+    pub fn corners(&self) -> Vec<Coord> {
+        let m = self.size - 1;
+        vec![
+            Coord::new(0, 0),
+            Coord::new(0, m),
+            Coord::new(m, 0),
+            Coord::new(m, m),
+        ]
+    }
+
+    /// 隅を除いた辺のマスの座標
+    pub fn edges(&self) -> Vec<Coord> {
+        let m = self.size - 1;
+        self.cells()
+            .map(|(c, _)| c)
+            .filter(|c| {
+                let on_edge = c.row == 0 || c.row == m || c.col == 0 || c.col == m;
+                let on_corner = (c.row == 0 || c.row == m) && (c.col == 0 || c.col == m);
+                on_edge && !on_corner
+            })
+            .collect()
+    }
+
+    /// 隅の斜め隣のマス（X打ち）の座標
+    ///
+    /// 各辺4マス未満の盤面では隅と重なるので空のVecを返す。
+    pub fn x_squares(&self) -> Vec<Coord> {
+        if self.size < 4 {
+            return Vec::new();
+        }
+        let m = self.size - 2;
+        vec![
+            Coord::new(1, 1),
+            Coord::new(1, m),
+            Coord::new(m, 1),
+            Coord::new(m, m),
+        ]
+    }
+
+    /// 辺上で隅の隣のマス（C打ち）の座標
+    ///
+    /// 各辺4マス未満の盤面では隅と重なるので空のVecを返す。
+    pub fn c_squares(&self) -> Vec<Coord> {
+        if self.size < 4 {
+            return Vec::new();
+        }
+        let m = self.size - 1;
+        vec![
+            Coord::new(0, 1),
+            Coord::new(1, 0),
+            Coord::new(0, m - 1),
+            Coord::new(1, m),
+            Coord::new(m - 1, 0),
+            Coord::new(m, 1),
+            Coord::new(m, m - 1),
+            Coord::new(m - 1, m),
+        ]
+    }
+
+    /// 隅にあるplayerの駒の個数
+    pub fn corner_count(&self, player: Player) -> usize {
+        self.corners()
+            .into_iter()
+            .filter(|&c| self.get(c) == Some(player))
+            .count()
+    }
+
+    /// 手番の側に置けるマスがなく、パスしなければならないかどうか
+    ///
+    /// 両者とも置けるマスがない（終局している）場合はfalse。
+    pub fn must_pass(&self) -> bool {
+        !self.has_legal_move(self.turn) && self.has_legal_move(self.turn.opponent())
+    }
+
+    /// 両者とも置けるマスがなく、終局しているかどうか
+    pub fn is_game_over(&self) -> bool {
+        !self.has_legal_move(self.turn) && !self.has_legal_move(self.turn.opponent())
+    }
+
+    /// パスして手番を相手に渡す
+    ///
+    /// 手番の側に置けるマスがある場合はパスできない。
+    pub fn pass(&mut self) -> Result<(), ReversiError> {
+        if self.has_legal_move(self.turn) {
+            return Err(ReversiError::IllegalPass);
+        }
+        self.change_turn();
+        Ok(())
     }
 
     /// マス目に駒を置く操作
     ///
     /// 返り値は、ゲームを続けられる場合true、両者ともに置けるマスがない場合にfalse。
+    /// 次の手番の側に置けるマスがない場合は、自動で```pass```して手番を戻す。
     pub fn put(&mut self, i: usize, j: usize) -> bool {
         let n = self.size;
         assert!(i < n && j < n);
-        let vec = &self.cnt_reversable();
-        assert!(vec[i][j] > 0);
-        let s = &mut self.state;
-        s[i][j] = Some(self.turn);
-        for k in 0..8 {
-            // 進む方向ごとに判定
+        let flips = self.flips_for(Coord::new(i, j));
+        assert!(!flips.is_empty());
+        let me = self.turn;
+        self.state[i][j] = Some(me);
+        self.hash ^= square_key(i, j, me);
+        // 間の駒を裏返していく処理
+        for c in flips {
+            self.state[c.row][c.col] = Some(me);
+            self.hash ^= square_key(c.row, c.col, me) ^ square_key(c.row, c.col, me.opponent());
+        }
 
-            // まず1マス隣
-            let new_x: i32 = i as i32 + dx(k);
-            let new_y: i32 = j as i32 + dy(k);
+        // ターンを交代
+        self.change_turn();
 
-            // 盤面から出ていた場合
-            if !BoardState::in_range(new_x, n) || !BoardState::in_range(new_y, n) {
-                continue;
-            }
-            let new_x: usize = new_x as usize;
-            let new_y: usize = new_y as usize;
+        // 置けないならパスしてもう一度ターンを交代
+        if self.must_pass() {
+            self.change_turn();
+        }
 
-            // 隣のマスが空ならもう処理はいらない
-            if let Some(t) = s[new_x][new_y] {
-                // 隣のマスが自分と同じ色ならもう処理はいらない
-                if t == self.turn {
-                    continue;
-                }
-                // 隣のマスが自分と違う色のときだけ進んで行く
-                for l in 1..n {
-                    let new_x: i32 = new_x as i32 + l as i32 * dx(k);
-                    let new_y: i32 = new_y as i32 + l as i32 * dy(k);
-
-                    // 盤面から出たら終了
-                    if !BoardState::in_range(new_x, n) || !BoardState::in_range(new_y, n) {
-                        break;
-                    }
-                    let new_x: usize = new_x as usize;
-                    let new_y: usize = new_y as usize;
+        // 続けられるならtrueを返し、両者とも置けないならfalseを返す
+        !self.is_game_over()
+    }
 
-                    // 空のマスに着いたら終了
-                    if let None = s[new_x][new_y] {
-                        break;
-                    }
+    /// ターンを交代する
+    fn change_turn(&mut self) {
+        self.hash ^= side_key(self.turn) ^ side_key(self.turn.opponent());
+        self.turn = self.turn.opponent();
+    }
 
-                    // 自分と同じ色が再び現れたらこのときだけ裏返せるので
-                    // 実際に裏返していく
-                    if let Some(t) = s[new_x][new_y] {
-                        if t == self.turn {
-                            // 間の駒を裏返していく処理
-                            for m in 1..=l {
-                                s[(i as i32 + m as i32 * dx(k)) as usize]
-                                    [(j as i32 + m as i32 * dy(k)) as usize] = Some(self.turn);
-                            }
-                            break;
-                        }
-                    }
-                }
+    /// マスの範囲内（0..n）かどうかを判定
+    fn in_range(z: i32, n: usize) -> bool {
+        z >= 0 && z < n as i32
+    }
+}
+
+/// ```from_ascii```と同じ形式の盤面を読み込む
+///
+/// 最後の行に駒の文字を1つだけ書くと、その色の番とする（省略した場合は黒の番）。
+impl FromStr for BoardState {
+    type Err = ReversiError;
+
+    fn from_str(s: &str) -> Result<BoardState, ReversiError> {
+        let last = s.lines().rev().map(|l| l.trim()).find(|l| !l.is_empty());
+        match last {
+            Some(l) if l == WHITE.to_string() || l == BLACK.to_string() => {
+                let grid = &s[..s.rfind(l).unwrap()];
+                BoardState::from_ascii(grid, l == WHITE.to_string())
             }
+            _ => BoardState::from_ascii(s, false),
         }
+    }
+}
 
-        // ターンを交代
-        self.turn = if self.turn == Turn::White {
-            Turn::Black
-        } else {
-            Turn::White
-        };
+/// 盤面を自由に設定して作成するためのビルダー
+///
+/// ```BoardState::new```では決められない、手番・置き石・塞いだマスなどを指定できる。
+/// 何も指定しなければ```BoardState::new```と同じ初期配置になる。
+#[derive(Clone, Debug)]
+pub struct BoardBuilder {
+    size: usize,
+    turn: Player,
+    initial_discs: bool,
+    discs: Vec<(Coord, Player)>,
+    blocked: Vec<Coord>,
+}
 
-        // 置けるならtrueを返して終了
-        if BoardState::puttable(&self) {
-            return true;
+impl BoardBuilder {
+    /// 各辺sizeマスの盤面のビルダーを作成する
+    pub fn new(size: usize) -> BoardBuilder {
+        BoardBuilder {
+            size,
+            turn: Player::Black,
+            initial_discs: true,
+            discs: Vec::new(),
+            blocked: Vec::new(),
         }
+    }
 
-        // 置けないならもう一度ターンを交代
-        self.turn = if self.turn == Turn::White {
-            Turn::Black
-        } else {
-            Turn::White
-        };
+    /// どちらの番から始めるか
+    pub fn turn(mut self, player: Player) -> BoardBuilder {
+        self.turn = player;
+        self
+    }
 
-        // 今度は置けるならtrueを返す
-        if BoardState::puttable(&self) {
-            true
-        } else {
-            // 置けないならfalseを返す
-            false
+    /// 中央の4マスの初期配置を置かずに、空の盤面から始める
+    pub fn without_initial_discs(mut self) -> BoardBuilder {
+        self.initial_discs = false;
+        self
+    }
+
+    /// playerの駒を置く
+    ///
+    /// 同じマスに何度も置いた場合は最後のものが有効。
+    pub fn disc(mut self, coord: Coord, player: Player) -> BoardBuilder {
+        self.discs.push((coord, player));
+        self
+    }
+
+    /// マスを塞いで駒を置けないようにする
+    pub fn blocked(mut self, coord: Coord) -> BoardBuilder {
+        self.blocked.push(coord);
+        self
+    }
+
+    /// 盤面を作成する
+    pub fn build(self) -> Result<BoardState, ReversiError> {
+        let n = self.size;
+        if n == 0 || !n.is_multiple_of(2) {
+            return Err(ReversiError::InvalidSize(n));
+        }
+
+        let mut state: Vec<Vec<Option<Player>>> = vec![vec![None; n]; n];
+        let mut blocked: Vec<Vec<bool>> = vec![vec![false; n]; n];
+
+        if self.initial_discs {
+            let h = n / 2;
+            state[h - 1][h - 1] = Some(Player::White);
+            state[h - 1][h] = Some(Player::Black);
+            state[h][h - 1] = Some(Player::Black);
+            state[h][h] = Some(Player::White);
+        }
+
+        for &c in &self.blocked {
+            if c.row >= n || c.col >= n {
+                return Err(ReversiError::OutOfRange(c));
+            }
+            blocked[c.row][c.col] = true;
+            // 初期配置の駒があるマスは塞げない
+            if state[c.row][c.col].is_some() {
+                return Err(ReversiError::BlockedSquare(c));
+            }
+        }
+
+        for &(c, player) in &self.discs {
+            if c.row >= n || c.col >= n {
+                return Err(ReversiError::OutOfRange(c));
+            }
+            if blocked[c.row][c.col] {
+                return Err(ReversiError::BlockedSquare(c));
+            }
+            state[c.row][c.col] = Some(player);
         }
+
+        Ok(BoardState::from_parts(state, blocked, self.turn))
     }
+}
 
-    /// マスの範囲内（0..n）かどうかを判定
-    fn in_range(z: i32, n: usize) -> bool {
-        z >= 0 && z < n as i32
+/// Zobristハッシュ用の擬似乱数（splitmix64）
+///
+/// 盤面のサイズが自由なので表を持たず、番号から毎回計算する。
+const fn zobrist_key(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// (i, j)にplayerの駒があることに対応するZobristハッシュの値
+fn square_key(i: usize, j: usize, player: Player) -> u64 {
+    let color: u64 = match player {
+        Player::White => 1,
+        Player::Black => 2,
+    };
+    zobrist_key(((i as u64) << 34) | ((j as u64) << 2) | color)
+}
+
+/// playerの番であることに対応するZobristハッシュの値
+fn side_key(player: Player) -> u64 {
+    match player {
+        Player::White => zobrist_key(0),
+        Player::Black => 0,
     }
 }
 
+/// 縦・横・斜めの4本の線の向き
+const AXES: [(i32, i32); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
 // これはダメっぽい
 // const dx: Vec<isize> = vec![1,1,1,0,-1,-1,-1,0];
 // const dy: Vec<isize> = vec![-1,0,1,1,1,0,-1,-1];
@@ -284,9 +1102,9 @@ impl BoardState {
 /// x方向への微小変化を見る用の配列の代わり
 const fn dx(n: usize) -> i32 {
     match n {
-        0 | 1 | 2 => 1,
+        0..=2 => 1,
         3 | 7 => 0,
-        4 | 5 | 6 => -1,
+        4..=6 => -1,
         _ => 0,
     }
 }
@@ -294,7 +1112,7 @@ const fn dx(n: usize) -> i32 {
 /// y方向への微小変化を見る用の配列の代わり
 const fn dy(n: usize) -> i32 {
     match n {
-        2 | 3 | 4 => 1,
+        2..=4 => 1,
         1 | 5 => 0,
         0 | 6 | 7 => -1,
         _ => 0,
@@ -304,3 +1122,32 @@ const fn dy(n: usize) -> i32 {
 const WHITE: char = 'o';
 const BLACK: char = '#';
 const NO_PIECE: char = '.';
+const BLOCKED: char = 'x';
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    /// 差分で更新したハッシュが、同じ局面から計算し直したものと一致しているか
+    fn assert_hash_matches(bs: &BoardState) {
+        let fresh = BoardState::from_parts(bs.state.clone(), bs.blocked.clone(), bs.turn);
+        assert_eq!(bs.zobrist_hash(), fresh.zobrist_hash());
+    }
+
+    #[test]
+    fn incremental_hash_matches_recomputed_hash_through_random_games() {
+        for seed in 0..20 {
+            let mut rng = SmallRng::seed_from_u64(seed);
+            let mut bs = BoardState::new(4, seed % 2 == 1);
+            assert_hash_matches(&bs);
+            while !bs.is_game_over() {
+                let moves: Vec<Coord> = bs.legal_moves().collect();
+                let c = moves[rng.gen_range(0, moves.len())];
+                bs.put(c.row, c.col);
+                assert_hash_matches(&bs);
+            }
+        }
+    }
+}
@@ -0,0 +1,532 @@
+use std::fmt;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+use simple_reversi::boardstate::BoardState;
+use unicode_width::UnicodeWidthChar;
+
+use crate::config::Config;
+use crate::messages::{msg, Lang};
+
+/// 簡易的なリバーシ（いわゆるオセロ）
+///
+/// 対局の設定（盤面のサイズ，対局の形式，色，強さなど）を省略した場合は，設定ファイルの値を使うか，
+/// 対局を始める前に入力を求める．
+/// オプションはサブコマンドの前後どちらにも書ける．
+#[derive(Parser, Debug)]
+#[command(version)]
+#[command(group(ArgGroup::new("mode").args(["cpu", "watch", "pvp"])))]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// 盤面のサイズ（4以上の偶数）
+    #[arg(long, global = true, value_parser = parse_size)]
+    pub size: Option<usize>,
+
+    /// CPUと対局する
+    #[arg(long)]
+    pub cpu: bool,
+
+    /// CPU同士の対局を見る
+    #[arg(long)]
+    pub watch: bool,
+
+    /// 自分で両方を操作する
+    #[arg(long)]
+    pub pvp: bool,
+
+    /// CPUと対局するときの自分の色
+    #[arg(long, value_enum)]
+    pub color: Option<Color>,
+
+    /// 黒の対局者の名前（2人で対局するときに指定しなければ入力を求める）
+    #[arg(long)]
+    pub black_name: Option<String>,
+
+    /// 白の対局者の名前（2人で対局するときに指定しなければ入力を求める）
+    #[arg(long)]
+    pub white_name: Option<String>,
+
+    /// CPUの強さの番号（1: よくばり，2: かんたん，3: そこそこ，4: ふつう，5: むずかしい，6: 達人）
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=6))]
+    pub level: Option<u8>,
+
+    /// CPUの先読みの方法
+    #[arg(long, value_enum)]
+    pub search: Option<SearchMethod>,
+
+    /// 対局ごとにCPUの強さを自動で調整する
+    #[arg(long, conflicts_with = "no_adaptive")]
+    pub adaptive: bool,
+
+    /// CPUの強さを自動で調整しない
+    #[arg(long)]
+    pub no_adaptive: bool,
+
+    /// ```save```で保存した対局を，保存したときの設定で続きから再開する
+    #[arg(long, value_name = "FILE")]
+    pub resume: Option<String>,
+
+    /// ```F5D6C3```のような1行の棋譜の手順を打った局面から，最初の対局を始める
+    #[arg(long, value_name = "MOVES", conflicts_with_all = ["resume", "edit"])]
+    pub from_transcript: Option<String>,
+
+    /// 盤面を表示したときと同じ形式（手番は```turn: #```の行）で局面を書いたファイルから対局を始める
+    ///
+    /// ```--from-transcript```と一緒に指定すると，この局面から手順を打つ．
+    /// ```--position FILE script```のようにサブコマンドの前に指定すると，```script```でもこの局面から始める．
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["resume", "edit"])]
+    pub position: Option<String>,
+
+    /// 対局を始める前に，駒を置いたり取り除いたりして始める局面を編集する
+    #[arg(long)]
+    pub edit: bool,
+
+    /// 盤面の代わりに，直前の手と裏返った駒，置けるマス目を文章で表示する（読み上げソフト向け）
+    #[arg(long)]
+    pub describe: bool,
+
+    /// 駒が置ける場所のヒント（+印）を常に表示する
+    #[arg(long)]
+    pub show_hints: bool,
+
+    /// 毎手，それぞれの置けるマス目の数を表示する
+    #[arg(long)]
+    pub show_mobility: bool,
+
+    /// 駒を●と○で表示する
+    #[arg(long)]
+    pub unicode: bool,
+
+    /// 盤面に表示する黒の駒，白の駒，空きマスの文字（カンマ区切り．例: "X,O,·"）
+    #[arg(long, value_parser = parse_pieces, value_name = "BLACK,WHITE,EMPTY")]
+    pub pieces: Option<Pieces>,
+
+    /// 幅が曖昧な文字（●など）を全角として盤面の列を揃える
+    #[arg(long)]
+    pub wide: bool,
+
+    /// 盤面のマス目の並べ方（既定では端末の大きさに合わせる）
+    #[arg(long, value_enum, default_value_t = Layout::Auto)]
+    pub layout: Layout,
+
+    /// 盤面や入力を求める表示を出さずに，結果を1行だけ表示する（```script```サブコマンドか```--watch```でのみ使える）
+    #[arg(long, global = true)]
+    pub quiet: bool,
+
+    /// 局面が変わるたびに，盤面・手番・置けるマス目・駒の個数・直前の手をJSONで1行に出力する
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// CPU同士の対局（```--watch --quiet```か```tournament```サブコマンド）の結果を1局ごとに追記するCSVのファイル
+    #[arg(long, global = true, value_name = "FILE")]
+    pub csv: Option<String>,
+
+    /// 対局を全画面表示で行う
+    #[cfg(feature = "tui")]
+    #[arg(long)]
+    pub tui: bool,
+
+    /// 盤面の配色（指定しなければclassic）
+    #[arg(long, value_enum)]
+    pub theme: Option<Theme>,
+
+    /// 盤面を色付きで表示しない（環境変数NO_COLORを設定しても同じ）
+    #[arg(long)]
+    pub no_color: bool,
+
+    /// CPUが使う定石集のファイル
+    #[arg(long, value_name = "FILE")]
+    pub book: Option<String>,
+
+    /// 表示に使う言語（指定しなければ環境変数LANGから決める）
+    #[arg(long, global = true, value_enum)]
+    pub lang: Option<Lang>,
+
+    /// CPUが先読みに使うスレッドの数（既定ではCPUのコア数）
+    #[arg(long, global = true, value_parser = clap::value_parser!(u32).range(1..))]
+    pub threads: Option<u32>,
+
+    /// CPUが使う乱数の種
+    #[arg(long, global = true)]
+    pub seed: Option<u64>,
+
+    /// 設定ファイル（TOML形式．指定しなければユーザーの設定ディレクトリのsimple-reversi/config.toml）
+    #[arg(long, global = true, value_name = "FILE")]
+    pub config: Option<String>,
+
+    /// 設定ファイルを読み込めなかった場合のエラー
+    #[arg(skip)]
+    pub config_error: Option<String>,
+
+    /// 対局中の棋譜を1手ごとに自動で保存するかどうか（設定ファイルの```autosave```）
+    #[arg(skip = true)]
+    pub autosave: bool,
+
+    /// 自動で保存するファイル（設定ファイルの```autosave_path```）
+    #[arg(skip)]
+    pub autosave_path: Option<String>,
+
+    /// CPUの評価関数の重みのファイル（TOML形式）
+    #[arg(long, global = true, value_name = "FILE")]
+    pub weights: Option<String>,
+
+    /// 1人あたりの持ち時間（分，切れたら負け）
+    #[arg(long, value_name = "MINUTES", value_parser = parse_minutes)]
+    pub clock: Option<Duration>,
+
+    /// 1手の制限時間（秒）
+    #[arg(long, value_name = "SECONDS", value_parser = parse_seconds)]
+    pub move_time: Option<Duration>,
+
+    /// 1手の制限時間を過ぎたときの扱い
+    #[arg(long, value_enum, default_value_t = TimeoutAction::Forfeit)]
+    pub on_move_timeout: TimeoutAction,
+
+    /// 直前に置かれた駒に印をつけない
+    #[arg(long, conflicts_with = "highlight_flips")]
+    pub no_highlight: bool,
+
+    /// 直前の手で裏返った駒にも印をつける
+    #[arg(long)]
+    pub highlight_flips: bool,
+
+    /// CPUが打って自分の番になったら端末のベルを鳴らす
+    #[arg(long)]
+    pub bell: bool,
+
+    /// CPUが打って自分の番になったらデスクトップに通知する（```notify-send```か```osascript```が使える場合）
+    #[arg(long)]
+    pub notify: bool,
+
+    /// 駒が裏返るところを少しずつ表示しない（すぐに次の盤面を表示する）
+    #[arg(long)]
+    pub no_animation: bool,
+
+    /// 駒を置く前に裏返る駒を表示して確かめる
+    #[arg(long)]
+    pub confirm_moves: bool,
+
+    /// 1局で待ったできる回数の上限（指定しなければ何回でもできる）
+    #[arg(long)]
+    pub max_undos: Option<usize>,
+
+    /// 何番勝負にするか（色を入れ替えながら，過半数を勝つか全ての対局が終わるまで続ける）
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub best_of: Option<u32>,
+
+    /// 評価の表示や対局の振り返りで読む深さ
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u32).range(1..))]
+    pub analysis_depth: u32,
+
+    /// 対局の振り返りで悪手とみなす評価値の下がり幅
+    #[arg(long, default_value_t = 50, value_parser = clap::value_parser!(i32).range(1..))]
+    pub blunder_threshold: i32,
+
+    /// 対局の振り返りをしたら，各手の評価と記号をつけた棋譜をこのファイルに書き込む
+    /// （```.sgf```で終わればSGFのコメント，それ以外は文章）
+    #[arg(long, value_name = "FILE")]
+    pub annotate: Option<String>,
+
+    /// CPUが投了する評価値の下がり幅（指定しなければ投了しない）
+    #[arg(long, value_parser = clap::value_parser!(i32).range(1..))]
+    pub resign: Option<i32>,
+
+    /// CPUがわざと最善でない手を打つ確率（0から1）
+    ///
+    /// 全ての手を読んでから選ぶので，```--stats```，```--win-rate```とは一緒に指定できない．
+    #[arg(long, value_parser = parse_probability, conflicts_with_all = ["stats", "win_rate"])]
+    pub blunder_rate: Option<f64>,
+
+    /// CPUがわざと打つ最善でない手の，評価値の下がり幅の上限
+    #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(i32).range(0..))]
+    pub blunder_margin: i32,
+
+    /// CPUがProbCutを使わずに正確に読む
+    #[arg(long)]
+    pub no_probcut: bool,
+
+    /// CPUが考えている途中経過を表示する
+    #[arg(long)]
+    pub stats: bool,
+
+    /// CPUが自分の勝つ確率の見積もりを表示する
+    #[arg(long)]
+    pub win_rate: bool,
+
+    /// 評価値を勝つ確率にするときの尺度
+    #[arg(long)]
+    pub win_scale: Option<f64>,
+}
+
+/// サブコマンド
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// CPU同士の総当たり戦
+    Tournament {
+        /// 1つの組み合わせの対局数
+        #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u32).range(1..))]
+        games: u32,
+        /// 参加させる強さの番号（カンマ区切り，既定は全て）
+        #[arg(long, value_delimiter = ',', value_parser = clap::value_parser!(u8).range(1..=6))]
+        levels: Vec<u8>,
+        /// モンテカルロ木探索のCPUも参加させる
+        #[arg(long)]
+        mcts: bool,
+    },
+    /// 読む手の順番の工夫の効果を測る
+    Bench {
+        /// 読む深さ
+        #[arg(long, default_value_t = 6, value_parser = clap::value_parser!(u32).range(1..))]
+        depth: u32,
+    },
+    /// 自己対局で評価関数の重みを調整する
+    Tune {
+        /// 試行の回数
+        #[arg(long, default_value_t = 20, value_parser = clap::value_parser!(u32).range(1..))]
+        iterations: u32,
+        /// 1回の試行の対局数
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+        games: u32,
+        /// 対局させるCPUの読む深さ
+        #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u32).range(1..))]
+        depth: u32,
+        /// 重みをずらす幅
+        #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(i32).range(1..))]
+        step: i32,
+        /// 調整した重みを書き込むファイル
+        #[arg(long, value_name = "FILE", default_value = "weights.toml")]
+        output: String,
+    },
+    /// 保存した棋譜を1手ずつ進めたり戻したりして見る
+    Replay {
+        /// 棋譜のファイル（対局中に```save```で保存したものか，WTHOR形式の```.wtb```）
+        file: String,
+        /// WTHOR形式の場合に見る対局の番号（1から）
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+        game: u32,
+    },
+    /// 保存した棋譜の局面の盤面を画像（SVGかPNG）に書き出す
+    ExportImage {
+        /// 棋譜のファイル（```replay```と同じもの）
+        file: String,
+        /// 書き出す画像のファイル（```.png```で終わればPNG，それ以外はSVG）
+        output: String,
+        /// 何手目の後の局面を書き出すか（指定しなければ最後の局面）
+        #[arg(long = "move", value_name = "N")]
+        at: Option<u32>,
+        /// 各駒の上に何手目に置かれたかを表示する
+        #[arg(long)]
+        numbers: bool,
+        /// WTHOR形式の場合に書き出す対局の番号（1から）
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+        game: u32,
+    },
+    /// 終局した対局の記録を一覧表示したり，1手ずつ見たりする
+    History {
+        #[command(subcommand)]
+        action: HistoryAction,
+    },
+    /// WTHOR形式の対局のデータベース（```.wtb```）の対局を一覧表示する
+    ///
+    /// 対局者と大会の名前は，同じディレクトリの```WTHOR.JOU```と```WTHOR.TRN```から読む．
+    Wthor {
+        /// データベースのファイル
+        file: String,
+        /// 各対局の序盤を定石集（```--book```で使える形式）としてこのファイルに書き込む
+        #[arg(long, value_name = "FILE")]
+        book: Option<String>,
+        /// 定石集に書き込む手数
+        #[arg(long, default_value_t = 20, value_parser = clap::value_parser!(u32).range(1..=60))]
+        depth: u32,
+    },
+    /// 手の並びを読んで入力を求めずに打ち，最後の盤面と結果を表示する
+    Script {
+        /// 空白で区切った手の並びのファイル（指定しないか```-```なら標準入力から読む）
+        file: Option<String>,
+    },
+    /// 今の設定（オプションで指定したものを含む）を設定ファイルに書き込む
+    Config,
+    /// 自己対局の結果から，評価値を勝つ確率にするときの尺度を求める
+    Calibrate {
+        /// 対局数
+        #[arg(long, default_value_t = 50, value_parser = clap::value_parser!(u32).range(1..))]
+        games: u32,
+        /// 対局させるCPUの読む深さ
+        #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u32).range(1..))]
+        depth: u32,
+    },
+}
+
+/// ```history```サブコマンドですること
+#[derive(Subcommand, Debug)]
+pub enum HistoryAction {
+    /// 記録した対局の日時，相手，盤面の大きさ，結果を一覧表示する
+    List,
+    /// 記録した対局を```replay```と同じように1手ずつ見る
+    Show {
+        /// ```list```で表示した番号
+        #[arg(value_parser = clap::value_parser!(u32).range(1..))]
+        id: u32,
+    },
+}
+
+/// CPUと対局するときの自分の色
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Color {
+    Black,
+    White,
+}
+
+/// 盤面のマス目の並べ方
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub enum Layout {
+    /// 端末の大きさに合わせる
+    Auto,
+    /// 間を空けずに並べる
+    Compact,
+    /// 1文字分ずつ空けて並べる
+    Normal,
+    /// 罫線で囲んで並べる
+    Large,
+}
+
+/// 盤面の配色
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    /// 緑の盤面
+    Classic,
+    /// 色覚の違いに関わらず見分けやすい，青の盤面と黄色の印
+    HighContrast,
+    /// 色を使わずに文字と印だけで表す
+    Mono,
+}
+
+/// 1手の制限時間を過ぎたときの扱い
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub enum TimeoutAction {
+    /// 負けにする
+    Forfeit,
+    /// ランダムな手を打つ
+    Random,
+}
+
+/// CPUの先読みの方法
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub enum SearchMethod {
+    /// αβ法
+    AlphaBeta,
+    /// モンテカルロ木探索
+    Mcts,
+}
+
+/// 解析済みのコマンドライン引数
+static CLI: OnceLock<Cli> = OnceLock::new();
+
+/// 解析済みのコマンドライン引数（最初に呼んだときに解析し，指定しなかった項目を設定ファイルで埋める）
+pub fn cli() -> &'static Cli {
+    CLI.get_or_init(|| {
+        let mut cli = Cli::parse();
+        if let Some(path) = Config::path(&cli) {
+            match Config::load(&path) {
+                Ok(config) => config.apply(&mut cli),
+                Err(e) => cli.config_error = Some(format!("{}: {}", path.display(), e)),
+            }
+        }
+        cli
+    })
+}
+
+/// 解析済みのコマンドライン引数（まだ解析している途中ならNone）
+pub fn parsed_cli() -> Option<&'static Cli> {
+    CLI.get()
+}
+
+/// 盤面のサイズを読む（4以上の偶数）
+fn parse_size(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(n) if n >= 4 && n.is_multiple_of(2) => Ok(n),
+        _ => Err(msg().invalid_size_arg().to_string()),
+    }
+}
+
+/// 盤面に表示する黒の駒，白の駒，空きマスの文字（```--pieces```）
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Pieces {
+    pub black: char,
+    pub white: char,
+    pub empty: char,
+}
+
+/// ```--pieces```と同じ形式（カンマ区切り）で書く
+impl fmt::Display for Pieces {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{}", self.black, self.white, self.empty)
+    }
+}
+
+/// 駒の文字を読む（カンマ区切りの3文字）
+///
+/// 3つの文字と塞いだマスの文字が全て異なり，表示したときの幅が同じであることを確かめる．
+pub fn parse_pieces(s: &str) -> Result<Pieces, String> {
+    let chars: Vec<char> = s
+        .split(',')
+        .map(|part| {
+            let mut it = part.trim().chars();
+            match (it.next(), it.next()) {
+                (Some(c), None) => Ok(c),
+                _ => Err(msg().pieces_one_char().to_string()),
+            }
+        })
+        .collect::<Result<_, _>>()?;
+    let (black, white, empty) = match chars.as_slice() {
+        &[b, w, e] => (b, w, e),
+        _ => return Err(msg().pieces_three().to_string()),
+    };
+    let all = [black, white, empty, BoardState::blocked_square()];
+    if (1..all.len()).any(|i| all[..i].contains(&all[i])) {
+        return Err(msg().pieces_distinct(BoardState::blocked_square()));
+    }
+    let width = black.width();
+    if width.is_none_or(|w| w == 0) || white.width() != width || empty.width() != width {
+        return Err(msg().pieces_width().to_string());
+    }
+    Ok(Pieces {
+        black,
+        white,
+        empty,
+    })
+}
+
+/// 確率を読む（0以上1以下）
+fn parse_probability(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(p) if (0.0..=1.0).contains(&p) => Ok(p),
+        _ => Err(msg().invalid_probability().to_string()),
+    }
+}
+
+/// 持ち時間（分）を読む（正の数で，```Duration```で表せるもの）
+fn parse_minutes(s: &str) -> Result<Duration, String> {
+    let minutes = parse_time(s)?;
+    Duration::try_from_secs_f64(minutes * 60.0).map_err(|_| msg().time_too_long().to_string())
+}
+
+/// 1手の制限時間（秒）を読む（正の数で，```Duration```で表せるもの）
+fn parse_seconds(s: &str) -> Result<Duration, String> {
+    let secs = parse_time(s)?;
+    Duration::try_from_secs_f64(secs).map_err(|_| msg().time_too_long().to_string())
+}
+
+/// 時間（分や秒）を読む（正の数）
+fn parse_time(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(m) if m > 0.0 && m.is_finite() => Ok(m),
+        _ => Err(msg().not_positive().to_string()),
+    }
+}
@@ -0,0 +1,67 @@
+use std::time::{Duration, Instant};
+
+use crate::boardstate::Player;
+
+/// 対局時計（黒と白それぞれの持ち時間）
+///
+/// remainingは止まっている時点での黒、白の残り時間で、
+/// runningは時計が動いている側と、動き始めた時刻。
+#[derive(Clone, Debug)]
+pub struct Clock {
+    remaining: [Duration; 2],
+    running: Option<(Player, Instant)>,
+}
+
+impl Clock {
+    /// 両者にbudgetずつの持ち時間を与えた時計（どちらの時計も止まっている）
+    pub fn new(budget: Duration) -> Clock {
+        Clock {
+            remaining: [budget; 2],
+            running: None,
+        }
+    }
+
+    /// 黒にblack、白にwhiteの持ち時間を与えた時計（途中から再開する場合など）
+    pub fn with_remaining(black: Duration, white: Duration) -> Clock {
+        Clock {
+            remaining: [black, white],
+            running: None,
+        }
+    }
+
+    /// playerの時計を動かす（動いていた側の時計は止める）
+    pub fn start(&mut self, player: Player) {
+        self.stop();
+        self.running = Some((player, Instant::now()));
+    }
+
+    /// 動いている時計を止めて、その側を返す（どちらも止まっていればNone）
+    pub fn stop(&mut self) -> Option<Player> {
+        let (player, since) = self.running.take()?;
+        let r = &mut self.remaining[Clock::index(player)];
+        *r = r.saturating_sub(since.elapsed());
+        Some(player)
+    }
+
+    /// playerの残り時間（時計が動いていれば、今の時点での残り時間）
+    pub fn remaining(&self, player: Player) -> Duration {
+        let r = self.remaining[Clock::index(player)];
+        match self.running {
+            Some((p, since)) if p == player => r.saturating_sub(since.elapsed()),
+            _ => r,
+        }
+    }
+
+    /// playerの持ち時間が切れているかどうか
+    pub fn is_out_of_time(&self, player: Player) -> bool {
+        self.remaining(player) == Duration::from_secs(0)
+    }
+
+    /// ```remaining```の中での位置
+    fn index(player: Player) -> usize {
+        match player {
+            Player::Black => 0,
+            Player::White => 1,
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use simple_reversi::boardstate::{Cell, Coord, Player};
+use simple_reversi::game::Move;
+
+/// 既定で棋譜を保存するファイル
+pub const DEFAULT_SAVE_FILE: &str = "game.txt";
+
+/// 既定で盤面の画像を書き出すファイル
+pub const DEFAULT_IMAGE_FILE: &str = "board.svg";
+
+/// 自分の番に入力できるコマンド
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GameCommand {
+    /// そのマス目に駒を置く
+    Place(Coord),
+    /// コマンドの一覧を表示する
+    Help,
+    /// ルールとマス目の指定のしかた，コマンドの一覧を表示する
+    Rules,
+    /// CPUのおすすめの手を表示する
+    Hint,
+    /// 置けるマス目に印をつけて盤面を表示し直す
+    Moves,
+    /// 置ける全てのマス目の評価を表示する（boardがtrueなら盤面にも順位を表示する）
+    Analyze { board: bool },
+    /// これまでの棋譜を表示する
+    History,
+    /// 棋譜を```F5D6C3```のような1行の形式で表示する
+    Transcript,
+    /// 今の局面を```8/8/8/3o#3/3#o3/8/8/8 b 8```のような1行の形式で表示する
+    Position,
+    /// 今の駒の個数を表示する
+    Score,
+    /// 盤面を文章で表示するかどうかを切り替える
+    Describe,
+    /// 盤面が端末に収まらないときに，そのマス目の周りを表示する
+    View(Coord),
+    /// 棋譜をファイルに保存する
+    Save(String),
+    /// 今の盤面を画像（SVGかPNG）に書き出す（numbersがtrueなら駒に手数を重ねる）
+    Image { path: String, numbers: bool },
+    /// 待ったする
+    Undo,
+    /// 投了する
+    Quit,
+    /// 1手の制限時間が切れた（入力されるものではなく，時間切れを表す）
+    Timeout,
+}
+
+impl GameCommand {
+    /// 入力された1行を読む（読めなければNone）
+    ///
+    /// マス目は```d3```のような列の英字と行番号か，```3 4```や```3-4```や```3,4```のような行番号と列番号で指定する．
+    /// コマンドは英単語のほかに1文字の略記（```h```，```a```，```u```など）も使える．
+    pub fn parse(line: &str) -> Option<GameCommand> {
+        let mut words = line
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|w| !w.is_empty());
+        let first = words.next()?.to_ascii_lowercase();
+        let rest: Vec<&str> = words.collect();
+        let command = match (first.as_str(), rest.as_slice()) {
+            ("help" | "?", []) => GameCommand::Help,
+            ("rules", []) => GameCommand::Rules,
+            ("hint" | "h", []) => GameCommand::Hint,
+            ("moves" | "m", []) => GameCommand::Moves,
+            ("analyze" | "a", []) => GameCommand::Analyze { board: false },
+            ("analyze" | "a", ["board"]) | ("ab", []) => GameCommand::Analyze { board: true },
+            ("history", []) => GameCommand::History,
+            ("transcript", []) => GameCommand::Transcript,
+            ("position", []) => GameCommand::Position,
+            ("score", []) => GameCommand::Score,
+            ("describe", []) => GameCommand::Describe,
+            ("view", [square]) => GameCommand::View(square.parse().ok()?),
+            ("save", []) => GameCommand::Save(DEFAULT_SAVE_FILE.to_string()),
+            ("save", [path]) => GameCommand::Save(path.to_string()),
+            ("image", []) => GameCommand::Image {
+                path: DEFAULT_IMAGE_FILE.to_string(),
+                numbers: false,
+            },
+            ("image", [path]) => GameCommand::Image {
+                path: path.to_string(),
+                numbers: false,
+            },
+            ("image", [path, "numbers"]) => GameCommand::Image {
+                path: path.to_string(),
+                numbers: true,
+            },
+            ("undo" | "u", []) => GameCommand::Undo,
+            ("quit" | "resign" | "q", []) => GameCommand::Quit,
+            (_, []) => match (first.parse::<Coord>(), first.parse::<Move>()) {
+                (Ok(c), _) | (_, Ok(Move::Place(c))) => GameCommand::Place(c),
+                _ => return None,
+            },
+            (row, [col]) => {
+                let row = row.parse::<usize>().ok().filter(|&n| n >= 1)?;
+                let col = col.parse::<usize>().ok().filter(|&n| n >= 1)?;
+                GameCommand::Place(Coord::new(row - 1, col - 1))
+            }
+            _ => return None,
+        };
+        Some(command)
+    }
+}
+
+/// 局面の編集（```--edit```）で入力できるコマンド
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EditCommand {
+    /// そのマス目を駒のある・空の・塞いだマス目にする
+    Set(Coord, Cell),
+    /// その側の番にする
+    Turn(Player),
+    /// 全てのマス目を空にする
+    Clear,
+    /// 最初の配置に戻す
+    Reset,
+    /// ファイルから盤面を読み込む
+    Load(String),
+    /// コマンドの一覧を表示する
+    Help,
+    /// 編集を終えて対局を始める
+    Done,
+}
+
+impl EditCommand {
+    /// 入力された1行を読む（読めなければNone）
+    ///
+    /// マス目は```d3```のような列の英字と行番号か，```3-4```のような行番号と列番号で指定する．
+    pub fn parse(line: &str) -> Option<EditCommand> {
+        let mut words = line.split_whitespace();
+        let first = words.next()?.to_ascii_lowercase();
+        let rest: Vec<&str> = words.collect();
+        let square = |s: &str| match (s.parse::<Coord>(), s.parse::<Move>()) {
+            (Ok(c), _) | (_, Ok(Move::Place(c))) => Some(c),
+            _ => None,
+        };
+        let player = |s: &str| match s.to_ascii_lowercase().as_str() {
+            "b" | "black" => Some(Player::Black),
+            "w" | "white" => Some(Player::White),
+            _ => None,
+        };
+        let command = match (first.as_str(), rest.as_slice()) {
+            ("b" | "black", [s]) => EditCommand::Set(square(s)?, Cell::Occupied(Player::Black)),
+            ("w" | "white", [s]) => EditCommand::Set(square(s)?, Cell::Occupied(Player::White)),
+            ("e" | "empty", [s]) => EditCommand::Set(square(s)?, Cell::Empty),
+            ("x" | "block", [s]) => EditCommand::Set(square(s)?, Cell::Blocked),
+            ("turn", [p]) => EditCommand::Turn(player(p)?),
+            ("clear", []) => EditCommand::Clear,
+            ("reset", []) => EditCommand::Reset,
+            ("load", [path]) => EditCommand::Load(path.to_string()),
+            ("help" | "?", []) => EditCommand::Help,
+            ("done" | "start", []) => EditCommand::Done,
+            _ => return None,
+        };
+        Some(command)
+    }
+}
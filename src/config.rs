@@ -0,0 +1,145 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cli::{parse_pieces, Cli, Color, Theme};
+use crate::messages::{msg, Lang};
+
+/// 設定ファイルに書いておける既定の設定（TOML形式）
+///
+/// 書かなかった項目やコマンドライン引数で指定した項目は，コマンドライン引数の値を使う．
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// 盤面のサイズ（4以上の偶数）
+    pub size: Option<usize>,
+    /// 表示に使う言語
+    pub lang: Option<Lang>,
+    /// CPUと対局するときの自分の色
+    pub color: Option<Color>,
+    /// CPUの強さの番号（1から6）
+    pub level: Option<u8>,
+    /// 駒が置ける場所のヒントを常に表示するかどうか
+    pub show_hints: Option<bool>,
+    /// 駒を●と○で表示するかどうか
+    pub unicode: Option<bool>,
+    /// 黒の駒，白の駒，空きマスの文字（```--pieces```と同じくカンマ区切り）
+    pub pieces: Option<String>,
+    /// 盤面の配色
+    pub theme: Option<Theme>,
+    /// 対局中の棋譜を1手ごとに自動で保存し，異常終了したら次に起動したときに再開できるようにするかどうか（既定はする）
+    pub autosave: Option<bool>,
+    /// 自動で保存するファイル（既定は一時ディレクトリの```simple-reversi-autosave.txt```）
+    pub autosave_path: Option<String>,
+}
+
+/// 設定ファイルの読み書きに失敗したことを表すエラー
+#[derive(Debug)]
+pub enum ConfigError {
+    /// ファイルを読み書きできなかった
+    Io(io::Error),
+    /// TOMLとして読めなかった
+    Parse(toml::de::Error),
+    /// TOMLとして書き出せなかった
+    Serialize(toml::ser::Error),
+    /// 項目の値が正しくなかった
+    Invalid(&'static str),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "{}", e),
+            ConfigError::Parse(e) => write!(f, "{}", e),
+            ConfigError::Serialize(e) => write!(f, "{}", e),
+            ConfigError::Invalid(key) => write!(f, "{}", msg().invalid_value(key)),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+impl Config {
+    /// 設定ファイルの場所（```--config```で指定しなければユーザーの設定ディレクトリの```simple-reversi/config.toml```）
+    ///
+    /// 設定ディレクトリは```XDG_CONFIG_HOME```，```APPDATA```，```HOME```の```.config```の順に探す．
+    pub fn path(cli: &Cli) -> Option<PathBuf> {
+        if let Some(path) = &cli.config {
+            return Some(PathBuf::from(path));
+        }
+        let env = |key| std::env::var_os(key).filter(|v| !v.is_empty());
+        let base = env("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env("APPDATA").map(PathBuf::from))
+            .or_else(|| env("HOME").map(|h| PathBuf::from(h).join(".config")))?;
+        Some(base.join("simple-reversi").join("config.toml"))
+    }
+
+    /// pathのファイルから読み込む（ファイルがなければ何も設定しない）
+    pub fn load(path: &PathBuf) -> Result<Config, ConfigError> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => return Err(ConfigError::Io(e)),
+        };
+        let config: Config = toml::from_str(&text).map_err(ConfigError::Parse)?;
+        if config.size.is_some_and(|n| n < 4 || n % 2 != 0) {
+            return Err(ConfigError::Invalid("size"));
+        }
+        if config.level.is_some_and(|n| !(1..=6).contains(&n)) {
+            return Err(ConfigError::Invalid("level"));
+        }
+        if config
+            .pieces
+            .as_deref()
+            .is_some_and(|s| parse_pieces(s).is_err())
+        {
+            return Err(ConfigError::Invalid("pieces"));
+        }
+        Ok(config)
+    }
+
+    /// pathのファイルに書き込む（ディレクトリがなければ作る）
+    pub fn save(&self, path: &PathBuf) -> Result<(), ConfigError> {
+        let text = toml::to_string(self).map_err(ConfigError::Serialize)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(ConfigError::Io)?;
+        }
+        std::fs::write(path, text).map_err(ConfigError::Io)
+    }
+
+    /// 今の設定（コマンドライン引数と設定ファイルを合わせたもの）
+    pub fn from_cli(cli: &Cli) -> Config {
+        Config {
+            size: cli.size,
+            lang: cli.lang,
+            color: cli.color,
+            level: cli.level,
+            show_hints: Some(cli.show_hints),
+            unicode: Some(cli.unicode),
+            pieces: cli.pieces.map(|p| p.to_string()),
+            theme: cli.theme,
+            autosave: Some(cli.autosave),
+            autosave_path: cli.autosave_path.clone(),
+        }
+    }
+
+    /// コマンドライン引数で指定しなかった項目を，この設定で埋める
+    pub fn apply(&self, cli: &mut Cli) {
+        cli.size = cli.size.or(self.size);
+        cli.lang = cli.lang.or(self.lang);
+        cli.color = cli.color.or(self.color);
+        cli.level = cli.level.or(self.level);
+        cli.show_hints |= self.show_hints.unwrap_or(false);
+        cli.unicode |= self.unicode.unwrap_or(false);
+        cli.pieces = cli
+            .pieces
+            .or_else(|| parse_pieces(self.pieces.as_deref()?).ok());
+        cli.theme = cli.theme.or(self.theme);
+        cli.autosave = self.autosave.unwrap_or(cli.autosave);
+        cli.autosave_path = self.autosave_path.clone();
+    }
+}
@@ -0,0 +1,74 @@
+//! 自己対局や総当たり戦の結果のCSV形式での書き出し（表計算ソフトなどで集計するため）
+//!
+//! 1局ごとに1行を追記する。ファイルが空なら、先に見出しの行を書く。
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::boardstate::Player;
+use crate::game::Game;
+
+/// 見出しの行
+pub const HEADER: &str = "seed,black,white,size,result,disc_diff,moves,duration";
+
+/// CSVの1行に書く1局の結果
+///
+/// seedは乱数の種（指定しなかった場合はNone）、black、whiteは黒・白のCPUの名前、
+/// durationは対局にかかった時間。
+pub struct GameRow<'a> {
+    pub seed: Option<u64>,
+    pub black: &'a str,
+    pub white: &'a str,
+    pub game: &'a Game,
+    pub duration: Duration,
+}
+
+impl GameRow<'_> {
+    /// CSVの1行（改行は含まない）
+    ///
+    /// resultは勝った側（```black```か```white```、引き分けなら```draw```、終局していなければ```unfinished```）、
+    /// disc_diffは黒の駒の個数から白の駒の個数を引いたもの、movesは```F5D6```の形式の手順、
+    /// durationは秒数。
+    pub fn to_line(&self) -> String {
+        let score = self.game.board().score();
+        let result = match self.game.outcome() {
+            None => "unfinished",
+            Some(o) => match o.winner() {
+                Some(Player::Black) => "black",
+                Some(Player::White) => "white",
+                None => "draw",
+            },
+        };
+        [
+            self.seed.map_or(String::new(), |s| s.to_string()),
+            quote(self.black),
+            quote(self.white),
+            self.game.board().get_size().to_string(),
+            result.to_string(),
+            (score.black as i64 - score.white as i64).to_string(),
+            self.game.compact_transcript(),
+            format!("{:.3}", self.duration.as_secs_f64()),
+        ]
+        .join(",")
+    }
+}
+
+/// カンマ、ダブルクォート、改行を含む値をダブルクォートで囲む
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// pathのファイルにrowの行を追記する（ファイルがないか空なら、先に見出しの行を書く）
+pub fn append<P: AsRef<Path>>(path: P, row: &GameRow) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    if file.metadata()?.len() == 0 {
+        writeln!(file, "{}", HEADER)?;
+    }
+    writeln!(file, "{}", row.to_line())
+}
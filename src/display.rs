@@ -0,0 +1,467 @@
+use std::fmt;
+use std::io::IsTerminal;
+use std::ops::Range;
+
+use crossterm::style::{Color, Stylize};
+use unicode_width::UnicodeWidthChar;
+
+use crate::boardstate::{BoardState, Cell, Coord, Player};
+use crate::error::ReversiError;
+
+/// 盤面を文字で表示するときの見た目の設定
+///
+/// black、white、emptyはそれぞれ黒い駒、白い駒、駒が置かれていないマスの文字で、
+/// blockedは塞がれていて駒を置けないマスの文字。
+/// hintが```Some```のときは、置けるマス目をその文字で表示する。
+/// colorがtrueのときは、ANSIエスケープシーケンスで```theme```の配色の盤面に黒と白の駒を色付きで表示し、
+/// 置けるマス目の印や重ねて表示する文字を目立つ色にする。
+/// wideがtrueのときは、```●```のような幅が曖昧な文字を全角（2文字分）として列を揃える。
+/// lettersがtrueのときは、列番号の代わりに列の英字（```a```、```b```、……）を表示する。
+/// layoutはマス目の並べ方、themeは色付きで表示するときの配色。
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct BoardStyle {
+    pub black: char,
+    pub white: char,
+    pub empty: char,
+    pub blocked: char,
+    pub hint: Option<char>,
+    pub color: bool,
+    pub wide: bool,
+    pub letters: bool,
+    pub layout: CellLayout,
+    pub theme: ColorTheme,
+}
+
+/// 色付きで表示するときの配色
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ColorTheme {
+    /// 緑の盤面に黒と白の駒
+    Classic,
+    /// 色覚の違いに関わらず見分けやすい、青の盤面と黄色の印の組み合わせ
+    HighContrast,
+    /// 色を使わずに文字と印だけで表す（```color```がtrueでも色付きにしない）
+    Monochrome,
+}
+
+/// 配色ごとの色（盤面、目立たせるマスの背景と、駒・空きマス・塞いだマス・印の文字の色）
+struct Palette {
+    board: Color,
+    highlight: Color,
+    black: Color,
+    white: Color,
+    empty: Color,
+    blocked: Color,
+    accent: Color,
+}
+
+impl ColorTheme {
+    /// 配色の色（```Monochrome```ならNone）
+    fn palette(self) -> Option<Palette> {
+        match self {
+            ColorTheme::Classic => Some(Palette {
+                board: Color::DarkGreen,
+                highlight: Color::DarkYellow,
+                black: Color::Black,
+                white: Color::White,
+                empty: Color::Green,
+                blocked: Color::DarkGrey,
+                accent: Color::Yellow,
+            }),
+            ColorTheme::HighContrast => Some(Palette {
+                board: Color::DarkBlue,
+                highlight: Color::DarkYellow,
+                black: Color::Black,
+                white: Color::White,
+                empty: Color::Grey,
+                blocked: Color::DarkGrey,
+                accent: Color::Yellow,
+            }),
+            ColorTheme::Monochrome => None,
+        }
+    }
+}
+
+/// マス目の並べ方
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CellLayout {
+    /// 間を空けずに並べる（列の見出しはマス目の幅に収まる末尾だけを表示し、左隣の印は色だけで表す）
+    Compact,
+    /// 左隣に1文字分の空白（印を表示する場所）を空けて並べる
+    Normal,
+    /// 罫線で囲んで並べる
+    Large,
+}
+
+impl Default for BoardStyle {
+    fn default() -> BoardStyle {
+        BoardStyle {
+            black: BoardState::black_piece(),
+            white: BoardState::white_piece(),
+            empty: BoardState::no_piece(),
+            blocked: BoardState::blocked_square(),
+            hint: None,
+            color: false,
+            wide: false,
+            letters: false,
+            layout: CellLayout::Normal,
+            theme: ColorTheme::Classic,
+        }
+    }
+}
+
+impl BoardStyle {
+    /// 置けるマス目に印をつけるようにしたものを返す
+    pub fn with_hint(self, hint: char) -> BoardStyle {
+        BoardStyle {
+            hint: Some(hint),
+            ..self
+        }
+    }
+
+    /// 色付きで表示するかどうかを指定したものを返す
+    pub fn with_color(self, color: bool) -> BoardStyle {
+        BoardStyle { color, ..self }
+    }
+
+    /// 駒を```●```と```○```で、空いているマスを```·```で表示する見た目
+    pub fn unicode() -> BoardStyle {
+        BoardStyle {
+            black: '●',
+            white: '○',
+            empty: '·',
+            blocked: '■',
+            ..BoardStyle::default()
+        }
+    }
+
+    /// 黒い駒、白い駒、駒が置かれていないマスの文字を指定したものを返す
+    pub fn with_pieces(self, black: char, white: char, empty: char) -> BoardStyle {
+        BoardStyle {
+            black,
+            white,
+            empty,
+            ..self
+        }
+    }
+
+    /// 幅が曖昧な文字を全角として列を揃えるかどうかを指定したものを返す
+    pub fn with_wide(self, wide: bool) -> BoardStyle {
+        BoardStyle { wide, ..self }
+    }
+
+    /// 列の英字を表示するかどうかを指定したものを返す
+    pub fn with_letters(self, letters: bool) -> BoardStyle {
+        BoardStyle { letters, ..self }
+    }
+
+    /// 色付きで表示するときの配色を指定したものを返す
+    pub fn with_theme(self, theme: ColorTheme) -> BoardStyle {
+        BoardStyle { theme, ..self }
+    }
+
+    /// マス目の並べ方を指定したものを返す
+    pub fn with_layout(self, layout: CellLayout) -> BoardStyle {
+        BoardStyle { layout, ..self }
+    }
+
+    /// この見た目で表示した盤面の文字列（```turn:```の行で手番を指定できる）から作成する
+    ///
+    /// 駒とマス目の文字（この見た目のものか```from_ascii```と同じもの）だけが並んだ行を盤面の行として、
+    /// 先頭の行番号、空白、罫線、直前の手などの印（```>```、```*```）やANSIエスケープシーケンスを無視する。
+    /// 列の見出しなど、それ以外の文字を含む行は読み飛ばす。
+    /// 手番は```turn: #```のように駒の文字か、```black```、```white```（```b```、```w```、```黒```、```白```でもよい）で書く
+    /// （書かなければ黒の番。```turn```や```black```などの言葉は大文字でもよいが、駒の文字は大文字と小文字を区別する）。
+    pub fn parse_board(&self, text: &str) -> Result<BoardState, ReversiError> {
+        let mut grid = String::new();
+        let mut white_turn = false;
+        for line in text.lines() {
+            let line = strip_escapes(line);
+            let line = line.trim();
+            // 大文字と小文字を区別しないのは見出しと```black```などの言葉だけで、駒の文字はそのまま比べる
+            if let Some((_, value)) = line
+                .split_once([':', '：'])
+                .filter(|(key, _)| key.trim().eq_ignore_ascii_case("turn"))
+            {
+                let value = value.trim();
+                white_turn = match value.chars().collect::<Vec<char>>()[..] {
+                    [c] if c == self.black || c == BoardState::black_piece() => false,
+                    [c] if c == self.white || c == BoardState::white_piece() => true,
+                    _ => match value.to_lowercase().as_str() {
+                        "black" | "b" | "黒" => false,
+                        "white" | "w" | "白" => true,
+                        _ => return Err(ReversiError::InvalidPosition(line.to_string())),
+                    },
+                };
+                continue;
+            }
+            if let Some(row) = self.board_row(line.trim_start_matches(|c: char| c.is_ascii_digit()))
+            {
+                grid.push_str(&row);
+                grid.push('\n');
+            }
+        }
+        BoardState::from_ascii(&grid, white_turn)
+    }
+
+    /// 盤面の1行（行番号は除いたもの）を```from_ascii```の文字にしたもの（盤面の行でなければNone）
+    fn board_row(&self, line: &str) -> Option<String> {
+        let mut row = String::new();
+        for c in line.chars() {
+            let ch = match c {
+                _ if c.is_whitespace() || c == '|' => continue,
+                _ if c == self.black => BoardState::black_piece(),
+                _ if c == self.white => BoardState::white_piece(),
+                _ if c == self.empty || Some(c) == self.hint => BoardState::no_piece(),
+                _ if c == self.blocked => BoardState::blocked_square(),
+                '>' | '*' => continue,
+                _ if Player::from_piece(c).is_some()
+                    || c == BoardState::no_piece()
+                    || c == BoardState::blocked_square() =>
+                {
+                    c
+                }
+                _ => return None,
+            };
+            row.push(ch);
+        }
+        Some(row).filter(|r| !r.is_empty())
+    }
+
+    /// 文字を端末に表示したときの幅（制御文字などは1とする）
+    fn char_width(&self, c: char) -> usize {
+        let w = if self.wide { c.width_cjk() } else { c.width() };
+        w.unwrap_or(1).max(1)
+    }
+}
+
+/// ANSIエスケープシーケンス（```ESC [ ... 文字```）を取り除いた文字列
+fn strip_escapes(line: &str) -> String {
+    let mut out = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            out.push(c);
+            continue;
+        }
+        if chars.next() == Some('[') {
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+    out
+}
+
+/// 標準出力に色付きで表示できそうかどうか
+///
+/// 環境変数```NO_COLOR```が空でない値で設定されている場合、標準出力が端末でない場合、
+/// 環境変数```TERM```が```dumb```の場合はfalse。
+pub fn supports_color() -> bool {
+    if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        return false;
+    }
+    if std::env::var_os("TERM").is_some_and(|v| v == "dumb") {
+        return false;
+    }
+    std::io::stdout().is_terminal()
+}
+
+/// 標準出力の端末の大きさ（列数、行数。端末でない場合や取得できない場合はNone）
+pub fn terminal_size() -> Option<(usize, usize)> {
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+    let (cols, rows) = crossterm::terminal::size().ok()?;
+    Some((cols as usize, rows as usize))
+}
+
+/// 見た目の設定と組にした盤面
+///
+/// ```BoardState::styled```で作成し、```fmt::Display```で表示する。
+/// marksはマス目に重ねて表示する文字（駒や```hint```より優先する）。
+/// highlightsはマス目の左隣に表示する印（直前の手などを目立たせるためのもので、駒はそのまま表示する）。
+/// rowsとcolsは表示する行と列の範囲（盤面の一部だけを表示するためのもの）。
+pub struct StyledBoard<'a> {
+    board: &'a BoardState,
+    style: BoardStyle,
+    marks: Vec<(Coord, char)>,
+    highlights: Vec<(Coord, char)>,
+    rows: Range<usize>,
+    cols: Range<usize>,
+}
+
+impl BoardState {
+    /// 見た目の設定を指定して表示するためのものを作成する
+    pub fn styled(&self, style: BoardStyle) -> StyledBoard<'_> {
+        StyledBoard {
+            board: self,
+            style,
+            marks: Vec::new(),
+            highlights: Vec::new(),
+            rows: 0..self.get_size(),
+            cols: 0..self.get_size(),
+        }
+    }
+}
+
+impl StyledBoard<'_> {
+    /// マス目に重ねて表示する文字を指定する
+    pub fn with_marks(self, marks: &[(Coord, char)]) -> Self {
+        StyledBoard {
+            marks: marks.to_vec(),
+            ..self
+        }
+    }
+
+    /// マス目の左隣に表示する印を指定する
+    pub fn with_highlights(self, highlights: &[(Coord, char)]) -> Self {
+        StyledBoard {
+            highlights: highlights.to_vec(),
+            ..self
+        }
+    }
+
+    /// rows行目とcols列目の範囲だけを表示する（盤面の外の部分は除く）
+    pub fn with_window(self, rows: Range<usize>, cols: Range<usize>) -> Self {
+        let n = self.board.get_size();
+        StyledBoard {
+            rows: rows.start.min(n)..rows.end.min(n),
+            cols: cols.start.min(n)..cols.end.min(n),
+            ..self
+        }
+    }
+
+    /// 表示したときの幅と高さ（文字数と行数）
+    pub fn dimensions(&self) -> (usize, usize) {
+        let (label, width) = (self.label_width(), self.cell_width());
+        let (cols, rows) = (self.cols.len(), self.rows.len());
+        match self.style.layout {
+            CellLayout::Compact => (label + cols * width, rows + 1),
+            CellLayout::Normal => (label + cols * (width + 1), rows + 1),
+            CellLayout::Large => (label + 1 + cols * (width + 3), 2 * rows + 2),
+        }
+    }
+
+    /// 列の見出し（英字か1始まりの番号）
+    fn header(&self, col: usize) -> String {
+        if self.style.letters {
+            Coord::column_name(col)
+        } else {
+            (col + 1).to_string()
+        }
+    }
+
+    /// 行番号の幅（2桁以上）
+    fn label_width(&self) -> usize {
+        self.board.get_size().to_string().len().max(2)
+    }
+
+    /// マス目の幅（使う文字と列の見出しのうち一番幅の広いものに揃える。```Compact```では見出しは除く）
+    fn cell_width(&self) -> usize {
+        let style = &self.style;
+        let headers = self
+            .cols
+            .clone()
+            .filter(|_| style.layout != CellLayout::Compact)
+            .map(|col| self.header(col).len());
+        [style.black, style.white, style.empty, style.blocked]
+            .iter()
+            .chain(style.hint.iter())
+            .chain(self.marks.iter().map(|(_, m)| m))
+            .map(|&c| style.char_width(c))
+            .chain(headers)
+            .max()
+            .unwrap_or(1)
+    }
+}
+
+impl fmt::Display for StyledBoard<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bs = self.board;
+        let style = &self.style;
+        let width = self.cell_width();
+        let label = self.label_width();
+        let large = style.layout == CellLayout::Large;
+        let palette = style.theme.palette().filter(|_| style.color);
+        let grid = bs.grid();
+        // 罫線の行
+        let rule = format!(
+            "{:w$}+{}",
+            "",
+            format!("{}+", "-".repeat(width + 2)).repeat(self.cols.len()),
+            w = label
+        );
+
+        // 列の見出しの行
+        write!(f, "{:w$}", "", w = label)?;
+        for col in self.cols.clone() {
+            let h = self.header(col);
+            match style.layout {
+                CellLayout::Compact => {
+                    let tail = &h[h.len().saturating_sub(width)..];
+                    write!(f, "{:w$}", tail, w = width)?
+                }
+                CellLayout::Normal => write!(f, " {:w$}", h, w = width)?,
+                CellLayout::Large => write!(f, "  {:w$} ", h, w = width)?,
+            }
+        }
+        writeln!(f)?;
+        if large {
+            writeln!(f, "{}", rule)?;
+        }
+
+        // 行番号と各マス目
+        for row in self.rows.clone() {
+            write!(f, "{:>w$}", row + 1, w = label)?;
+            if large {
+                write!(f, "|")?;
+            }
+            for col in self.cols.clone() {
+                let coord = Coord::new(row, col);
+                let cell = grid[row][col];
+                let mark = self.marks.iter().find(|(c, _)| *c == coord);
+                let (c, fg): (char, fn(&Palette) -> Color) = match (cell, mark) {
+                    (_, Some(&(_, m))) => (m, |p| p.accent),
+                    (Cell::Occupied(Player::Black), None) => (style.black, |p| p.black),
+                    (Cell::Occupied(Player::White), None) => (style.white, |p| p.white),
+                    (Cell::Blocked, None) => (style.blocked, |p| p.blocked),
+                    (Cell::Empty, None) => match style.hint {
+                        Some(h) if bs.is_legal(coord) => (h, |p| p.accent),
+                        _ => (style.empty, |p| p.empty),
+                    },
+                };
+                let highlight = self.highlights.iter().find(|(c, _)| *c == coord);
+                let left = match (highlight, style.layout) {
+                    (_, CellLayout::Compact) => String::new(),
+                    (Some(&(_, h)), _) => h.to_string(),
+                    (None, _) => " ".to_string(),
+                };
+                let pad = " ".repeat(width - style.char_width(c) + usize::from(large));
+                let cell = format!("{}{}{}", left, c, pad);
+                match &palette {
+                    Some(p) if highlight.is_some() => {
+                        write!(f, "{}", cell.with(fg(p)).on(p.highlight))?
+                    }
+                    Some(p) => write!(f, "{}", cell.with(fg(p)).on(p.board))?,
+                    None => write!(f, "{}", cell)?,
+                }
+                if large {
+                    write!(f, "|")?;
+                }
+            }
+            writeln!(f)?;
+            if large {
+                writeln!(f, "{}", rule)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for BoardState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.styled(BoardStyle::default()).fmt(f)
+    }
+}
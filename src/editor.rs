@@ -0,0 +1,90 @@
+use simple_reversi::boardstate::{BoardBuilder, BoardState, Cell, Coord};
+use simple_reversi::error::ReversiError;
+
+use crate::command::EditCommand;
+use crate::messages::msg;
+use crate::{board_style, fit_layout, input, piece};
+
+/// 対局を始める局面を編集する（```--edit```）
+///
+/// startの局面から始めて，```done```が入力されたときの局面を返す．
+/// 入力が終わった場合はその時点の局面を返す．
+pub fn edit_position(start: BoardState) -> BoardState {
+    let size = start.get_size();
+    let mut bs = start.clone();
+    println!("{}", msg().editor_help());
+    loop {
+        print!("{}", bs.styled(fit_layout(&bs, board_style())));
+        println!("{}", msg().turn(&piece(bs.turn()).to_string()));
+
+        let line = input::read_line();
+        if line.is_empty() {
+            return bs;
+        }
+        let edited = match EditCommand::parse(&line) {
+            Some(EditCommand::Set(c, cell)) => with_cell(&bs, c, cell),
+            Some(EditCommand::Turn(player)) => builder_from(&bs, None).turn(player).build(),
+            Some(EditCommand::Clear) => BoardBuilder::new(size)
+                .without_initial_discs()
+                .turn(bs.turn())
+                .build(),
+            Some(EditCommand::Reset) => Ok(start.clone()),
+            Some(EditCommand::Load(path)) => match std::fs::read_to_string(&path) {
+                // 大きさの違う盤面は読み込まない
+                Ok(text) => text.parse::<BoardState>().and_then(|b| match b.get_size() {
+                    n if n == size => Ok(b),
+                    n => Err(ReversiError::InvalidSize(n)),
+                }),
+                Err(e) => {
+                    println!("{}", msg().replay_load_failed(&path, &e));
+                    continue;
+                }
+            },
+            Some(EditCommand::Help) => {
+                println!("{}", msg().editor_help());
+                continue;
+            }
+            Some(EditCommand::Done) if bs.is_game_over() => {
+                println!("{}", msg().editor_game_over());
+                continue;
+            }
+            Some(EditCommand::Done) => return bs,
+            None => {
+                println!("{}", msg().unknown_command());
+                continue;
+            }
+        };
+        match edited {
+            Ok(new) => bs = new,
+            Err(e) => println!("{}", msg().editor_error(&msg().reversi_error(&e))),
+        }
+    }
+}
+
+/// bsと同じ手番で，skip以外のマス目の駒と塞いだマス目を置いたビルダー
+fn builder_from(bs: &BoardState, skip: Option<Coord>) -> BoardBuilder {
+    bs.cells().filter(|&(c, _)| Some(c) != skip).fold(
+        BoardBuilder::new(bs.get_size())
+            .without_initial_discs()
+            .turn(bs.turn()),
+        |b, (c, cell)| match cell {
+            Cell::Occupied(player) => b.disc(c, player),
+            Cell::Blocked => b.blocked(c),
+            Cell::Empty => b,
+        },
+    )
+}
+
+/// bsのcのマス目だけをcellにした盤面
+fn with_cell(bs: &BoardState, c: Coord, cell: Cell) -> Result<BoardState, ReversiError> {
+    let builder = builder_from(bs, Some(c));
+    match cell {
+        Cell::Occupied(player) => builder.disc(c, player),
+        Cell::Blocked => builder.blocked(c),
+        Cell::Empty if c.row >= bs.get_size() || c.col >= bs.get_size() => {
+            return Err(ReversiError::OutOfRange(c))
+        }
+        Cell::Empty => builder,
+    }
+    .build()
+}
@@ -0,0 +1,971 @@
+use std::cmp::Reverse;
+use std::sync::atomic::{AtomicI32, AtomicU64, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::boardstate::{BoardState, Coord, Player};
+use crate::game::Move;
+
+pub mod book;
+pub mod eval;
+pub mod handicap;
+pub mod mcts;
+pub mod pattern;
+pub mod probability;
+pub mod review;
+pub mod strategy;
+mod tt;
+use eval::{EvalWeights, Evaluator, PositionalEvaluator};
+use mcts::Mcts;
+use strategy::{GreedyStrategy, HeuristicStrategy, RandomStrategy, Strategy};
+use tt::{Bound, Entry, TranspositionTable};
+
+/// 終局したときの評価値の倍率
+///
+/// 駒の個数の差にこれを掛けるので、終局した局面はどんな途中の局面よりも優先される
+/// （途中の局面の評価値は、評価関数の値を絶対値がこれより小さくなるように切り詰めたもの）。
+const WIN_SCORE: i32 = 10000;
+
+/// 評価値の上限（αβ探索の初期窓に使う）
+const INF: i32 = i32::MAX / 2;
+
+/// 置換表の大きさ（2の何乗個の項目を持つか）
+const TT_BITS: u32 = 16;
+
+/// 終局まで読み切るようにする空きマスの数の既定値
+const ENDGAME_EMPTIES: usize = 10;
+
+/// CPUの強さ
+///
+/// ```Greedy```は先読みせず、一番多く裏返せるマス目に置く。
+/// ```Easy```は先読みせず、裏返せる枚数で重み付けしてランダムに選ぶ。
+/// ```Moderate```は先読みせず、隅を取り、空いている隅の隣を避ける決まりに従って選ぶ。
+/// それ以外は```Engine```で先読みし、強いほど深く読む（```Expert```は時間いっぱいまで読む）。
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Greedy,
+    Easy,
+    Moderate,
+    Normal,
+    Hard,
+    Expert,
+}
+
+impl Level {
+    /// 全ての強さ（弱い順）
+    pub const ALL: [Level; 6] = [
+        Level::Greedy,
+        Level::Easy,
+        Level::Moderate,
+        Level::Normal,
+        Level::Hard,
+        Level::Expert,
+    ];
+
+    /// 先読みする深さ（先読みしない場合や、時間で区切る場合はNone）
+    pub fn depth(self) -> Option<u32> {
+        match self {
+            Level::Greedy | Level::Easy | Level::Moderate => None,
+            Level::Normal => Some(2),
+            Level::Hard => Some(4),
+            Level::Expert => None,
+        }
+    }
+
+    /// 先読みに使う時間（時間で区切らない場合はNone）
+    ///
+    /// 盤面が大きいと同じ深さでも時間が大きく変わるので、一番強いCPUは時間で区切る。
+    pub fn time_limit(self) -> Option<Duration> {
+        match self {
+            Level::Expert => Some(Duration::from_secs(2)),
+            _ => None,
+        }
+    }
+
+    /// モンテカルロ木探索で手を選ぶ場合のエンジン（先読みしない強さではNone）
+    ///
+    /// 盤面が大きくても1回のプレイアウトにかかる時間はあまり変わらないので、回数で区切る。
+    /// 一番強いCPUは```time_limit```と同じ時間で区切る。
+    pub fn mcts(self) -> Option<Mcts> {
+        match self {
+            Level::Greedy | Level::Easy | Level::Moderate => None,
+            Level::Normal => Some(Mcts::with_iterations(300)),
+            Level::Hard => Some(Mcts::with_iterations(1500)),
+            Level::Expert => Some(Mcts::with_time_limit(Duration::from_secs(2))),
+        }
+    }
+
+    /// 先読みする強さでのαβ法のエンジン（先読みしない強さではNone）
+    ///
+    /// threadsは先読みに使うスレッドの数、weightsは評価関数（```PositionalEvaluator```）の重み。
+    /// ProbCutは既定の設定で使う。
+    pub fn engine(self, threads: usize, weights: &EvalWeights) -> Option<Engine> {
+        let evaluator = PositionalEvaluator::new(weights.positional);
+        let engine = match (self.depth(), self.time_limit()) {
+            (Some(d), _) => Engine::with_evaluator(d, evaluator),
+            (None, Some(t)) => Engine::with_evaluator(1, evaluator).time_limit(t),
+            (None, None) => return None,
+        };
+        Some(engine.threads(threads).probcut(Some(ProbCut::default())))
+    }
+
+    /// この強さで手を選ぶCPU（先読みする強さではαβ法を使う）
+    ///
+    /// threadsは先読みに使うスレッドの数。
+    /// seedは乱数の種で、Noneなら実行するたびに異なる乱数になる。
+    /// weightsは先読みするときの評価関数（```PositionalEvaluator```）の重み。
+    pub fn strategy(
+        self,
+        threads: usize,
+        seed: Option<u64>,
+        weights: &EvalWeights,
+    ) -> Box<dyn Strategy> {
+        if let Some(engine) = self.engine(threads, weights) {
+            return Box::new(engine);
+        }
+        match self {
+            Level::Greedy => Box::new(GreedyStrategy::with_seed(seed)),
+            Level::Moderate => Box::new(HeuristicStrategy::with_seed(seed)),
+            _ => Box::new(RandomStrategy::with_seed(seed)),
+        }
+    }
+
+    /// 1段階強い強さ（一番強ければNone）
+    pub fn stronger(self) -> Option<Level> {
+        Level::ALL.get(self.index() + 1).copied()
+    }
+
+    /// 1段階弱い強さ（一番弱ければNone）
+    pub fn weaker(self) -> Option<Level> {
+        self.index().checked_sub(1).map(|i| Level::ALL[i])
+    }
+
+    /// ```ALL```の中での位置
+    fn index(self) -> usize {
+        Level::ALL.iter().position(|&l| l == self).unwrap()
+    }
+}
+
+/// 人間の成績に合わせて、対局ごとにCPUの強さを上げ下げする仕組み
+///
+/// 人間が勝ったら1段階強くし、2局続けて負けたら1段階弱くする。
+/// lossesは人間が続けて負けた回数（引き分けや強さの変更で0に戻す）。
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct AdaptiveLevel {
+    level: Level,
+    losses: u32,
+}
+
+impl AdaptiveLevel {
+    /// levelから始める
+    pub fn new(level: Level) -> AdaptiveLevel {
+        AdaptiveLevel { level, losses: 0 }
+    }
+
+    /// 現在の強さ
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// 1局の結果（勝った方、引き分けならNone）を記録する
+    ///
+    /// humanは人間が持っていた側。強さが変わった場合は新しい強さを返す。
+    pub fn record(&mut self, winner: Option<Player>, human: Player) -> Option<Level> {
+        let changed = match winner {
+            Some(w) if w == human => self.level.stronger(),
+            Some(_) => {
+                self.losses += 1;
+                if self.losses >= 2 {
+                    self.level.weaker()
+                } else {
+                    None
+                }
+            }
+            None => {
+                self.losses = 0;
+                None
+            }
+        };
+        if let Some(level) = changed {
+            self.level = level;
+            self.losses = 0;
+        }
+        changed
+    }
+}
+
+/// αβ法で先読みして手を選ぶ思考エンジン
+///
+/// depthは何手先まで読むか（パスは手数に数えない）。
+/// evaluatorは先読みの末端の局面の評価関数。
+/// time_limitが```Some```のときは、```Strategy```として手を選ぶときに
+/// depthを使わずにその時間いっぱいまで読む。
+/// 空きマスの数がendgame_empties以下になったら、深さや時間に関係なく終局まで読み切る。
+/// threadsは並列に読むスレッドの数（最初の手を分担して読む）。
+/// move_orderingが```true```のときは、枝刈りが多く起きるように、よさそうな手から順に読む。
+/// resign_thresholdが```Some```のときは、```Strategy```として手を選ぶときに
+/// 最善手の評価値がその値の符号を反転させた値以下なら投了する。
+/// probcutが```Some```のときは、ProbCutで見込みのない局面を浅い探索で打ち切る。
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Engine<E = PositionalEvaluator> {
+    depth: u32,
+    evaluator: E,
+    time_limit: Option<Duration>,
+    endgame_empties: usize,
+    threads: usize,
+    move_ordering: bool,
+    resign_threshold: Option<i32>,
+    probcut: Option<ProbCut>,
+}
+
+/// ProbCut（浅い探索の結果から深い探索の結果を予想して、読む前に打ち切る枝刈り）の設定
+///
+/// 残りの深さがmin_depth以上の局面では、まず半分の深さで読み、
+/// その評価値がβ+margin以上（α-margin以下）になったら、深く読んでもβ以上（α以下）になると予想して打ち切る。
+/// marginが小さいほど多く打ち切るが、読み間違いも増える。
+/// 読み切れる局面（残りの深さが空きマスの数以上）では使わないので、終盤の読み切りの結果は正確なまま。
+/// 浅く読む深さが1手以上になるように、min_depthは3以上にする
+/// （それより小さいと、浅い探索は評価関数の値だけで予想することになる）。
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ProbCut {
+    pub margin: i32,
+    pub min_depth: u32,
+}
+
+impl Default for ProbCut {
+    fn default() -> ProbCut {
+        ProbCut {
+            margin: 30,
+            min_depth: 3,
+        }
+    }
+}
+
+impl Engine {
+    /// depth手先まで読むエンジンを作成する（評価関数は```PositionalEvaluator```）
+    pub fn new(depth: u32) -> Engine {
+        Engine::with_evaluator(depth, PositionalEvaluator::default())
+    }
+}
+
+impl<E: Evaluator> Engine<E> {
+    /// 評価関数を指定して、depth手先まで読むエンジンを作成する
+    pub fn with_evaluator(depth: u32, evaluator: E) -> Engine<E> {
+        assert!(depth != 0);
+        Engine {
+            depth,
+            evaluator,
+            time_limit: None,
+            endgame_empties: ENDGAME_EMPTIES,
+            threads: 1,
+            move_ordering: true,
+            resign_threshold: None,
+            probcut: None,
+        }
+    }
+
+    /// ```Strategy```として手を選ぶときの制限時間を指定する
+    pub fn time_limit(self, limit: Duration) -> Engine<E> {
+        Engine {
+            time_limit: Some(limit),
+            ..self
+        }
+    }
+
+    /// 空きマスの数が何個以下になったら終局まで読み切るかを指定する（0なら読み切らない）
+    pub fn endgame_empties(self, empties: usize) -> Engine<E> {
+        Engine {
+            endgame_empties: empties,
+            ..self
+        }
+    }
+
+    /// 並列に読むスレッドの数を指定する
+    ///
+    /// 2つ以上にすると、スレッドの間で共有する評価値の範囲によって置換表やProbCutの結果が変わるので、
+    /// 同じ局面でも毎回同じ手を選ぶとは限らない。
+    pub fn threads(self, threads: usize) -> Engine<E> {
+        assert!(threads != 0);
+        Engine { threads, ..self }
+    }
+
+    /// 読む手の順番を工夫するかどうかを指定する（既定では工夫する）
+    ///
+    /// 置換表の最善手、隅、キラー手（同じ深さで枝刈りを起こした手）、
+    /// ヒストリー（これまでに枝刈りを起こした回数）の順に優先して読む。
+    /// 結果の評価値は変わらないが、調べる局面の数（```SearchResult::nodes```）が大きく減る。
+    pub fn move_ordering(self, move_ordering: bool) -> Engine<E> {
+        Engine {
+            move_ordering,
+            ..self
+        }
+    }
+
+    /// ProbCutを使うかどうかとその設定を指定する（既定では使わない）
+    ///
+    /// 中盤で深く読めるようになる代わりに、評価値が正確でなくなることがある。
+    pub fn probcut(self, probcut: Option<ProbCut>) -> Engine<E> {
+        Engine { probcut, ..self }
+    }
+
+    /// 最善手の評価値が-threshold以下になったら投了するようにする
+    ///
+    /// 終局まで読み切った場合の評価値は駒1個の差につき10000なので、
+    /// thresholdが10000以下なら、読み切って負けが決まった時点で投了する。
+    pub fn resign_threshold(self, threshold: i32) -> Engine<E> {
+        Engine {
+            resign_threshold: Some(threshold),
+            ..self
+        }
+    }
+
+    /// 探索の結果から、投了すべきかどうかを判断する
+    pub fn resigns_after(&self, result: &SearchResult) -> bool {
+        self.resign_threshold.is_some_and(|t| result.score <= -t)
+    }
+
+    /// 評価関数
+    pub fn evaluator(&self) -> &E {
+        &self.evaluator
+    }
+
+    /// 読む深さ
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// 手番の側にとって最善と思われる手を選ぶ（置けるマスがなければNone）
+    pub fn best_move(&self, bs: &BoardState) -> Option<Coord> {
+        self.search(bs).map(|r| r.best_move)
+    }
+
+    /// 最善と思われる手とその評価値（手番の側から見た値）を求める
+    pub fn search(&self, bs: &BoardState) -> Option<SearchResult> {
+        self.root().search(bs, self.depth_for(bs))
+    }
+
+    /// 置ける全てのマス目について、その手を打った場合の探索の結果を評価値の高い順に並べる
+    ///
+    /// 最善手以外の手も正確な評価値を求めるので、```search```より時間がかかる。
+    /// 評価値が同じ手は```legal_moves```の順に並べる。
+    /// time_limitが```Some```なら、```search_within```と同じように深さを1手ずつ増やし、
+    /// 時間内に読み終えた一番深い結果を返す。
+    pub fn analyze(&self, bs: &BoardState) -> Vec<SearchResult> {
+        let limit = match self.time_limit {
+            Some(t) if bs.score().empty > self.endgame_empties => t,
+            _ => {
+                return self
+                    .root()
+                    .analyze(bs, self.depth_for(bs))
+                    .unwrap_or_default()
+            }
+        };
+        let start = Instant::now();
+        let mut root = self.root();
+        let mut results = root.analyze(bs, 1).unwrap_or_default();
+        root.deadline = Some(start + limit);
+        let max_depth = bs.score().empty as u32;
+        for depth in 2..=max_depth {
+            match root.analyze(bs, depth) {
+                Some(r) => results = r,
+                None => break,
+            }
+        }
+        results
+    }
+
+    /// このエンジンの設定で最初の手を読む部分を作成する
+    fn root(&self) -> RootSearch<'_> {
+        RootSearch::new(self.threads, &self.evaluator)
+            .move_ordering(self.move_ordering)
+            .probcut(self.probcut)
+    }
+
+    /// 局面に応じた読む深さ（終局まで読み切る空きマスの数なら、空きマスの数まで読む）
+    fn depth_for(&self, bs: &BoardState) -> u32 {
+        let empties = bs.score().empty;
+        if empties <= self.endgame_empties {
+            self.depth.max(empties as u32)
+        } else {
+            self.depth
+        }
+    }
+
+    /// 制限時間内で読める深さまで読んで、最善と思われる手を選ぶ（置けるマスがなければNone）
+    pub fn best_move_within(&self, bs: &BoardState, limit: Duration) -> Option<Coord> {
+        self.search_within(bs, limit).map(|r| r.best_move)
+    }
+
+    /// 1手ずつ深くしながら、固定の深さ（終局まで読み切る場合は空きマスの数）まで読む
+    ///
+    /// 結果は```search```と同じだが、深さごとに読み終えるたびに途中経過をon_infoに渡す。
+    /// 結果の```nodes```は、全ての深さで調べた局面の数の合計。
+    pub fn search_with<F: FnMut(&SearchInfo)>(
+        &self,
+        bs: &BoardState,
+        mut on_info: F,
+    ) -> Option<SearchResult> {
+        let start = Instant::now();
+        let root = self.root();
+        let mut result: Option<SearchResult> = None;
+        for depth in 1..=self.depth_for(bs) {
+            let r = root.search(bs, depth)?;
+            let nodes = result.as_ref().map_or(0, |p| p.nodes) + r.nodes;
+            let r = SearchResult { nodes, ..r };
+            on_info(&SearchInfo::new(&r, start.elapsed()));
+            result = Some(r);
+        }
+        result
+    }
+
+    /// 制限時間内で、1手ずつ深くしながら読めるところまで読む（反復深化）
+    ///
+    /// 固定の深さ（```depth()```）は使わない。
+    /// 時間切れになった深さの結果は捨てて、最後に読み切れた深さの結果を返す。
+    /// ただし1手先までは時間に関係なく必ず読む。
+    /// 空きマスの数より深く読む必要はないので、そこまで読めたら時間が残っていても終了する。
+    /// 終局まで読み切る空きマスの数になっていれば、時間に関係なく読み切る。
+    /// 結果の```nodes```は、読み切れた深さまでに調べた局面の数の合計。
+    pub fn search_within(&self, bs: &BoardState, limit: Duration) -> Option<SearchResult> {
+        self.search_within_with(bs, limit, |_| {})
+    }
+
+    /// ```search_within```と同じだが、深さごとに読み終えるたびに途中経過をon_infoに渡す
+    pub fn search_within_with<F: FnMut(&SearchInfo)>(
+        &self,
+        bs: &BoardState,
+        limit: Duration,
+        mut on_info: F,
+    ) -> Option<SearchResult> {
+        let start = Instant::now();
+        if bs.score().empty <= self.endgame_empties {
+            let result = self.search(bs)?;
+            on_info(&SearchInfo::new(&result, start.elapsed()));
+            return Some(result);
+        }
+
+        // 置換表は深さを変えても使い回す
+        let mut root = self.root();
+        let mut result = root.search(bs, 1)?;
+        on_info(&SearchInfo::new(&result, start.elapsed()));
+        root.deadline = Some(start + limit);
+        let max_depth = bs.score().empty as u32;
+        for depth in 2..=max_depth {
+            match root.search(bs, depth) {
+                Some(r) => {
+                    result = SearchResult {
+                        nodes: result.nodes + r.nodes,
+                        ..r
+                    };
+                    on_info(&SearchInfo::new(&result, start.elapsed()));
+                }
+                None => break,
+            }
+        }
+        Some(result)
+    }
+
+    /// ```Strategy```として手を選ぶときと同じ読み方（制限時間があれば```search_within_with```、
+    /// なければ```search_with```）で読み、途中経過をon_infoに渡す
+    pub fn think_with<F: FnMut(&SearchInfo)>(
+        &self,
+        bs: &BoardState,
+        on_info: F,
+    ) -> Option<SearchResult> {
+        match self.time_limit {
+            Some(t) => self.search_within_with(bs, t, on_info),
+            None => self.search_with(bs, on_info),
+        }
+    }
+}
+
+impl<E: Evaluator> Strategy for Engine<E> {
+    fn choose_move(&mut self, bs: &BoardState) -> Move {
+        match self.time_limit {
+            Some(t) => self.best_move_within(bs, t),
+            None => self.best_move(bs),
+        }
+        .map_or(Move::Pass, Move::Place)
+    }
+
+    fn choose_move_or_resign(&mut self, bs: &BoardState) -> Option<Move> {
+        let result = match self.time_limit {
+            Some(t) => self.search_within(bs, t),
+            None => self.search(bs),
+        };
+        match result {
+            Some(r) if self.resigns_after(&r) => None,
+            Some(r) => Some(Move::Place(r.best_move)),
+            None => Some(Move::Pass),
+        }
+    }
+}
+
+/// 終局まで読み切って、駒の個数の差が最も大きくなる手を求める（置けるマスがなければNone）
+///
+/// お互いに最善を尽くした場合の終局時の駒の個数の差は、返り値の```final_disc_difference```で得られる。
+/// 空きマスの数が多いと非常に時間がかかるので、終盤の局面で使う。
+pub fn solve_endgame(bs: &BoardState) -> Option<SearchResult> {
+    let depth = (bs.score().empty as u32).max(1);
+    RootSearch::new(1, &PositionalEvaluator::default()).search(bs, depth)
+}
+
+/// 探索の結果
+///
+/// best_moveは最善と思われる手、scoreはその評価値（手番の側から見た値）、
+/// depthは何手先まで読んだか。
+/// pvは最善手から始まる、お互いに最善を尽くした場合の手順（読み筋）。
+/// exactは終局まで読み切れたかどうか。
+/// nodesは探索で調べた局面の数（```Engine::analyze```ではその手を読むのに調べた数）。
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SearchResult {
+    pub best_move: Coord,
+    pub score: i32,
+    pub depth: u32,
+    pub pv: Vec<Coord>,
+    pub exact: bool,
+    pub nodes: u64,
+}
+
+impl SearchResult {
+    /// 終局まで読み切れた場合の、お互いに最善を尽くしたときの終局時の駒の個数の差
+    /// （手番の側から見た値、読み切れていなければNone）
+    pub fn final_disc_difference(&self) -> Option<i32> {
+        if self.exact {
+            Some(self.score / WIN_SCORE)
+        } else {
+            None
+        }
+    }
+}
+
+/// 反復深化で1つの深さを読み終えたときの途中経過
+///
+/// depthは読み終えた深さ、scoreとpvはその深さでの最善手の評価値と読み筋、
+/// nodesは読み始めてから調べた局面の数の合計、elapsedは読み始めてからの時間。
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SearchInfo {
+    pub depth: u32,
+    pub score: i32,
+    pub nodes: u64,
+    pub elapsed: Duration,
+    pub pv: Vec<Coord>,
+}
+
+impl SearchInfo {
+    fn new(result: &SearchResult, elapsed: Duration) -> SearchInfo {
+        SearchInfo {
+            depth: result.depth,
+            score: result.score,
+            nodes: result.nodes,
+            elapsed,
+            pv: result.pv.clone(),
+        }
+    }
+
+    /// 1秒あたりに調べた局面の数
+    pub fn nodes_per_second(&self) -> u64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            (self.nodes as f64 / secs) as u64
+        } else {
+            self.nodes
+        }
+    }
+}
+
+/// 最初の手を（並列に）読む部分の状態
+///
+/// deadlineを過ぎたら探索を打ち切る。
+/// threadsは並列に読むスレッドの数で、ttは全てのスレッドで共有する置換表、evaluatorは評価関数。
+/// orderingは読む手の順番を工夫するかどうか、probcutはProbCutの設定。
+struct RootSearch<'a> {
+    deadline: Option<Instant>,
+    threads: usize,
+    tt: TranspositionTable,
+    evaluator: &'a dyn Evaluator,
+    ordering: bool,
+    probcut: Option<ProbCut>,
+}
+
+impl<'a> RootSearch<'a> {
+    fn new(threads: usize, evaluator: &'a dyn Evaluator) -> RootSearch<'a> {
+        RootSearch {
+            deadline: None,
+            threads,
+            tt: TranspositionTable::new(TT_BITS),
+            evaluator,
+            ordering: false,
+            probcut: None,
+        }
+    }
+
+    fn move_ordering(self, ordering: bool) -> RootSearch<'a> {
+        RootSearch { ordering, ..self }
+    }
+
+    fn probcut(self, probcut: Option<ProbCut>) -> RootSearch<'a> {
+        RootSearch { probcut, ..self }
+    }
+
+    /// 最初の手を読む順番に並べる
+    ///
+    /// 工夫する場合は、置換表の最善手（浅く読んだときの最善手）と隅を先に読む。
+    fn root_moves(&self, bs: &BoardState) -> Vec<Coord> {
+        let mut moves: Vec<Coord> = bs.legal_moves().collect();
+        if self.ordering {
+            let tt_move = self.tt.probe(bs.zobrist_hash()).and_then(|e| e.best_move);
+            moves.sort_by_key(|&c| Reverse((Some(c) == tt_move, is_corner(bs.get_size(), c))));
+        }
+        moves
+    }
+
+    /// depth手先まで読んで最善手を求める（置けるマスがないか、時間切れならNone）
+    ///
+    /// 各スレッドは、まだ誰も読んでいない最初の手を1つずつ取って読む。
+    /// それまでに見つかった一番よい評価値（α）はスレッドの間で共有する。
+    /// αと同じ評価値の手も正確な値を求めるので、評価値が同じ手の中では、読み終わった順によらず先に読む順番の手を選ぶ。
+    fn search(&self, bs: &BoardState, depth: u32) -> Option<SearchResult> {
+        let moves = self.root_moves(bs);
+        let next = AtomicUsize::new(0);
+        let shared_alpha = AtomicI32::new(-INF);
+        let nodes = AtomicU64::new(0);
+        let threads = self.threads.min(moves.len()).max(1);
+
+        // 各スレッドは、αを更新した手を（何番目の手か, 評価値）の組で集める
+        let results: Vec<Option<Vec<(usize, i32)>>> = thread::scope(|s| {
+            let handles: Vec<_> = (0..threads)
+                .map(|_| {
+                    s.spawn(|| {
+                        let mut searcher = self.searcher(bs);
+                        let mut improved = Vec::new();
+                        loop {
+                            let i = next.fetch_add(1, Ordering::Relaxed);
+                            if i >= moves.len() {
+                                break;
+                            }
+                            // αと同じ評価値の手もαより大きいと分かるように、範囲を1だけ広げる
+                            let alpha = shared_alpha.load(Ordering::Relaxed);
+                            let lower = alpha.saturating_sub(1);
+                            let score = searcher.score_after(bs, moves[i], depth - 1, lower, INF);
+                            if searcher.aborted {
+                                return None;
+                            }
+                            if score > lower {
+                                shared_alpha.fetch_max(score, Ordering::Relaxed);
+                                improved.push((i, score));
+                            }
+                        }
+                        nodes.fetch_add(searcher.nodes, Ordering::Relaxed);
+                        Some(improved)
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        // 評価値が最大の手を選ぶ（同じなら先に読む順番の手）
+        let mut best: Option<(usize, i32)> = None;
+        for (i, score) in results
+            .into_iter()
+            .collect::<Option<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+        {
+            match best {
+                Some((j, b)) if b > score || (b == score && j < i) => {}
+                _ => best = Some((i, score)),
+            }
+        }
+        let (i, score) = best?;
+        let best_move = moves[i];
+        // 次に深く読むときに```root_moves```で最善手を先に読めるように、最初の局面も置換表に記録する
+        self.tt.store(Entry {
+            hash: bs.zobrist_hash(),
+            depth,
+            score,
+            bound: Bound::Exact,
+            best_move: Some(best_move),
+        });
+        Some(SearchResult {
+            best_move,
+            score,
+            depth,
+            pv: self.principal_variation(bs, best_move, depth),
+            // 空きマスの数まで読めば、末端は全て終局した局面になる
+            exact: depth as usize >= bs.score().empty,
+            nodes: nodes.into_inner(),
+        })
+    }
+
+    /// 置ける全てのマス目をそれぞれdepth手先まで読んで、評価値の高い順に並べる（時間切れならNone）
+    fn analyze(&self, bs: &BoardState, depth: u32) -> Option<Vec<SearchResult>> {
+        let mut searcher = self.searcher(bs);
+        let mut scores: Vec<(Coord, i32, u64)> = Vec::new();
+        for c in bs.legal_moves() {
+            let before = searcher.nodes;
+            let score = searcher.score_after(bs, c, depth - 1, -INF, INF);
+            if searcher.aborted {
+                return None;
+            }
+            scores.push((c, score, searcher.nodes - before));
+        }
+        let mut results: Vec<SearchResult> = scores
+            .into_iter()
+            .map(|(c, score, nodes)| SearchResult {
+                best_move: c,
+                score,
+                depth,
+                pv: self.principal_variation(bs, c, depth),
+                exact: depth as usize >= bs.score().empty,
+                nodes,
+            })
+            .collect();
+        results.sort_by_key(|r| Reverse(r.score));
+        Some(results)
+    }
+
+    /// 1つのスレッドで読むための状態を作成する
+    fn searcher(&self, bs: &BoardState) -> Searcher<'_> {
+        Searcher::new(self.deadline, &self.tt, self.evaluator)
+            .move_ordering(self.ordering, bs.get_size())
+            .probcut(self.probcut)
+    }
+
+    /// 置換表の最善手をたどって、firstから始まる読み筋を最大depth手まで復元する
+    fn principal_variation(&self, bs: &BoardState, first: Coord, depth: u32) -> Vec<Coord> {
+        let mut pv = vec![first];
+        let mut bs = bs.clone();
+        let mut c = first;
+        while (pv.len() as u32) < depth && bs.put(c.row, c.col) {
+            match self.tt.probe(bs.zobrist_hash()).and_then(|e| e.best_move) {
+                Some(next) if bs.is_legal(next) => {
+                    pv.push(next);
+                    c = next;
+                }
+                _ => break,
+            }
+        }
+        pv
+    }
+}
+
+/// 1つのスレッドでの探索の状態
+///
+/// deadlineを過ぎたらabortedを立てて探索を打ち切る。
+/// nodesは調べた局面の数で、時刻の確認を間引くのにも使う。
+/// ttは置換表、evaluatorは評価関数。
+/// orderingが```true```のときは、killersに残りの深さごとのキラー手（枝刈りを起こした手）を2つまで、
+/// historyにマス目ごとの枝刈りを起こした度合い（残りの深さの2乗の合計）を覚えて、読む順番に使う。
+/// probcutはProbCutの設定。
+struct Searcher<'a> {
+    deadline: Option<Instant>,
+    nodes: u64,
+    aborted: bool,
+    tt: &'a TranspositionTable,
+    evaluator: &'a dyn Evaluator,
+    ordering: bool,
+    size: usize,
+    killers: Vec<[Option<Coord>; 2]>,
+    history: Vec<u32>,
+    probcut: Option<ProbCut>,
+}
+
+impl<'a> Searcher<'a> {
+    fn new(
+        deadline: Option<Instant>,
+        tt: &'a TranspositionTable,
+        evaluator: &'a dyn Evaluator,
+    ) -> Searcher<'a> {
+        Searcher {
+            deadline,
+            nodes: 0,
+            aborted: false,
+            tt,
+            evaluator,
+            ordering: false,
+            size: 0,
+            killers: Vec::new(),
+            history: Vec::new(),
+            probcut: None,
+        }
+    }
+
+    /// 一辺の長さがsizeの盤面で、読む手の順番を工夫するかどうかを指定する
+    fn move_ordering(self, ordering: bool, size: usize) -> Searcher<'a> {
+        Searcher {
+            ordering,
+            size,
+            history: vec![0; size * size],
+            ..self
+        }
+    }
+
+    fn probcut(self, probcut: Option<ProbCut>) -> Searcher<'a> {
+        Searcher { probcut, ..self }
+    }
+
+    /// 局面の置けるマス目を読む順番に並べる（tt_moveは置換表の最善手）
+    fn ordered_moves(&self, bs: &BoardState, depth: u32, tt_move: Option<Coord>) -> Vec<Coord> {
+        let mut moves: Vec<Coord> = bs.legal_moves().collect();
+        if !self.ordering {
+            return moves;
+        }
+        let killers = self
+            .killers
+            .get(depth as usize)
+            .copied()
+            .unwrap_or_default();
+        moves.sort_by_key(|&c| {
+            Reverse((
+                Some(c) == tt_move,
+                is_corner(self.size, c),
+                killers.contains(&Some(c)),
+                self.history[c.row * self.size + c.col],
+            ))
+        });
+        moves
+    }
+
+    /// ProbCutで打ち切れる場合は、その評価値（βかα）を返す
+    fn probcut_score(&mut self, bs: &BoardState, depth: u32, alpha: i32, beta: i32) -> Option<i32> {
+        let pc = self.probcut?;
+        // 読み切れる局面では使わない
+        if depth < pc.min_depth || depth as usize >= bs.score().empty {
+            return None;
+        }
+        // 勝ち負けが決まるような評価値の側では予想しない
+        // 評価値は手番によって偏るので、浅く読む深さは元の深さと偶奇をそろえる
+        // （min_depthが3より小さいと0手になりうるので、負にならないようにする）
+        let shallow = (depth / 2).saturating_sub((depth - depth / 2) % 2);
+        if beta.abs() < WIN_SCORE {
+            let upper = beta + pc.margin;
+            if self.negamax(bs, shallow, upper - 1, upper) >= upper {
+                return Some(beta);
+            }
+        }
+        if alpha.abs() < WIN_SCORE {
+            let lower = alpha - pc.margin;
+            if self.negamax(bs, shallow, lower, lower + 1) <= lower {
+                return Some(alpha);
+            }
+        }
+        None
+    }
+
+    /// 残りの深さがdepthのときにcで枝刈りが起きたことを記録する
+    fn record_cutoff(&mut self, c: Coord, depth: u32) {
+        if !self.ordering {
+            return;
+        }
+        let d = depth as usize;
+        if self.killers.len() <= d {
+            self.killers.resize(d + 1, [None, None]);
+        }
+        let k = &mut self.killers[d];
+        if k[0] != Some(c) {
+            k[1] = k[0];
+            k[0] = Some(c);
+        }
+        self.history[c.row * self.size + c.col] += depth * depth;
+    }
+
+    /// 手番の側がcに置いた後の局面を、置いた側から見て評価する
+    fn score_after(&mut self, bs: &BoardState, c: Coord, depth: u32, alpha: i32, beta: i32) -> i32 {
+        let me = bs.turn();
+        let mut next = bs.clone();
+        let can_continue = next.put(c.row, c.col);
+        if !can_continue {
+            return next.disc_difference(me) * WIN_SCORE;
+        }
+        if next.turn() == me {
+            // 相手がパスしたので続けて自分の番
+            self.negamax(&next, depth, alpha, beta)
+        } else {
+            -self.negamax(&next, depth, -beta, -alpha)
+        }
+    }
+
+    /// 手番の側から見た局面の評価値をネガマックス法＋αβ法で求める
+    fn negamax(&mut self, bs: &BoardState, depth: u32, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(1024) {
+            if let Some(d) = self.deadline {
+                if Instant::now() >= d {
+                    self.aborted = true;
+                }
+            }
+        }
+        if self.aborted {
+            return 0;
+        }
+        if depth == 0 {
+            // 勝った（負けた）終局の局面より高く（低く）評価しないように切り詰める
+            return self
+                .evaluator
+                .evaluate(bs)
+                .clamp(-(WIN_SCORE - 1), WIN_SCORE - 1);
+        }
+
+        // 置換表に十分深く読んだ結果があればそれを使う
+        let hash = bs.zobrist_hash();
+        let entry = self.tt.probe(hash);
+        if let Some(e) = entry {
+            if e.depth >= depth {
+                match e.bound {
+                    Bound::Exact => return e.score.max(alpha).min(beta),
+                    Bound::Lower if e.score >= beta => return beta,
+                    Bound::Upper if e.score <= alpha => return alpha,
+                    _ => {}
+                }
+            }
+        }
+
+        if let Some(score) = self.probcut_score(bs, depth, alpha, beta) {
+            return score;
+        }
+
+        let alpha_orig = alpha;
+        let mut best_move: Option<Coord> = None;
+        let tt_move = entry.and_then(|e| e.best_move);
+        for c in self.ordered_moves(bs, depth, tt_move) {
+            let score = self.score_after(bs, c, depth - 1, alpha, beta);
+            if score > alpha {
+                alpha = score;
+                best_move = Some(c);
+                if alpha >= beta {
+                    // これ以上読んでも選ばれないので打ち切る
+                    self.record_cutoff(c, depth);
+                    break;
+                }
+            }
+        }
+
+        // 時間切れで途中までしか読んでいない結果は記録しない
+        if !self.aborted {
+            let bound = if alpha <= alpha_orig {
+                Bound::Upper
+            } else if alpha >= beta {
+                Bound::Lower
+            } else {
+                Bound::Exact
+            };
+            self.tt.store(Entry {
+                hash,
+                depth,
+                score: alpha,
+                bound,
+                best_move,
+            });
+        }
+        alpha
+    }
+}
+
+/// 一辺の長さがsizeの盤面で、cが隅かどうか
+fn is_corner(size: usize, c: Coord) -> bool {
+    (c.row == 0 || c.row == size - 1) && (c.col == 0 || c.col == size - 1)
+}
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::{thread_rng, Rng};
+
+use crate::boardstate::{BoardState, Coord};
+use crate::engine::strategy::{make_rng, Strategy};
+use crate::error::{BookError, ReplayError, ReversiError};
+use crate::game::Move;
+
+/// 定石集
+///
+/// 手順を1行に1つずつ書いたもの（手順の木）から作成し、局面ごとに定石の手を引けるようにする。
+/// 対称な局面は同じものとみなすため、局面を```canonical_form```に変換してから
+/// ```zobrist_hash```を鍵にして、変換後の座標で手を持つ。
+/// sizeは盤面の一辺の長さ（違う大きさの盤面では引かない）。
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Book {
+    size: usize,
+    moves: HashMap<u64, Vec<Coord>>,
+}
+
+impl Book {
+    /// 文字列から定石集を作成する
+    ///
+    /// 1行が初期局面startから始まる1つの手順で、手は棋譜用の表示と同じ形式
+    /// （「行-列」の1始まりの番号）を空白で区切って並べる（例: ```5-6 6-6 6-5```）。
+    /// ```pass```は読み飛ばし、空行と```;```で始まる行は無視する。
+    pub fn parse(text: &str, start: &BoardState) -> Result<Book, BookError> {
+        let mut book = Book {
+            size: start.get_size(),
+            moves: HashMap::new(),
+        };
+        for (line, s) in text.lines().enumerate() {
+            let s = s.trim();
+            if s.is_empty() || s.starts_with(';') {
+                continue;
+            }
+            book.add_line(s, start)
+                .map_err(|error| BookError::Line { line, error })?;
+        }
+        Ok(book)
+    }
+
+    /// ファイルから定石集を読み込む（形式は```parse```と同じ）
+    pub fn load<P: AsRef<Path>>(path: P, start: &BoardState) -> Result<Book, BookError> {
+        let text = fs::read_to_string(path)?;
+        Book::parse(&text, start)
+    }
+
+    /// 局面に登録された定石の手の1つをランダムに選ぶ（定石から外れていればNone）
+    pub fn lookup(&self, bs: &BoardState) -> Option<Coord> {
+        self.lookup_with(bs, &mut thread_rng())
+    }
+
+    /// ```lookup```と同じだが、指定した乱数生成器で選ぶ
+    pub fn lookup_with<R: Rng + ?Sized>(&self, bs: &BoardState, rng: &mut R) -> Option<Coord> {
+        self.moves_for(bs).choose(rng).copied()
+    }
+
+    /// 局面に登録された定石の手の一覧
+    pub fn moves_for(&self, bs: &BoardState) -> Vec<Coord> {
+        if bs.get_size() != self.size {
+            return Vec::new();
+        }
+        let sym = bs.canonical_symmetry();
+        let key = bs.transformed(sym).zobrist_hash();
+        let inverse = sym.inverse();
+        self.moves
+            .get(&key)
+            .map(|v| v.iter().map(|&c| inverse.apply(self.size, c)).collect())
+            .unwrap_or_default()
+    }
+
+    /// 登録されている局面の数
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    /// 1つも局面が登録されていないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// 1行分の手順を登録する
+    fn add_line(&mut self, line: &str, start: &BoardState) -> Result<(), ReplayError> {
+        let mut bs = start.clone();
+        for (index, token) in line.split_whitespace().enumerate() {
+            let c = match token.parse::<Move>() {
+                Ok(Move::Place(c)) => c,
+                Ok(Move::Pass) => continue,
+                Err(error) => return Err(ReplayError { index, error }),
+            };
+            if bs.is_game_over() {
+                return Err(ReplayError {
+                    index,
+                    error: ReversiError::GameOver,
+                });
+            }
+            if !bs.is_legal(c) {
+                return Err(ReplayError {
+                    index,
+                    error: ReversiError::IllegalMove(c),
+                });
+            }
+            let sym = bs.canonical_symmetry();
+            let key = bs.transformed(sym).zobrist_hash();
+            let mv = sym.apply(self.size, c);
+            let moves = self.moves.entry(key).or_default();
+            if !moves.contains(&mv) {
+                moves.push(mv);
+            }
+            bs.put(c.row, c.col);
+        }
+        Ok(())
+    }
+}
+
+/// 定石の間は定石集の手を打ち、定石から外れたらinnerで手を選ぶCPU
+///
+/// rngは定石の手が複数あるときに選ぶための乱数生成器。
+pub struct BookStrategy<S> {
+    book: Book,
+    inner: S,
+    rng: SmallRng,
+}
+
+impl<S: Strategy> BookStrategy<S> {
+    /// 定石集と、定石から外れた後に使うCPUを組み合わせる
+    pub fn new(book: Book, inner: S) -> BookStrategy<S> {
+        BookStrategy {
+            book,
+            inner,
+            rng: make_rng(None),
+        }
+    }
+
+    /// 定石の手を選ぶときの乱数の種を指定する
+    pub fn seed(self, seed: u64) -> BookStrategy<S> {
+        BookStrategy {
+            rng: make_rng(Some(seed)),
+            ..self
+        }
+    }
+}
+
+impl<S: Strategy> Strategy for BookStrategy<S> {
+    fn choose_move(&mut self, bs: &BoardState) -> Move {
+        match self.book.lookup_with(bs, &mut self.rng) {
+            Some(c) => Move::Place(c),
+            None => self.inner.choose_move(bs),
+        }
+    }
+
+    fn choose_move_or_resign(&mut self, bs: &BoardState) -> Option<Move> {
+        match self.book.lookup_with(bs, &mut self.rng) {
+            Some(c) => Some(Move::Place(c)),
+            None => self.inner.choose_move_or_resign(bs),
+        }
+    }
+}
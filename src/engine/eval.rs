@@ -0,0 +1,263 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::boardstate::{BoardState, Cell, Coord, Player};
+use crate::error::WeightsError;
+
+/// マス目の重みの既定値（8×8の盤面の左上の4×4の部分）
+///
+/// 隅が最も高く、隅の斜め隣（X打ち）が最も低い。
+/// 他の大きさの盤面では、一番近い辺からの距離で引く（4以上離れたマスは3と同じ重み）。
+const SQUARE_WEIGHTS: [[i32; 4]; 4] = [
+    [100, -20, 10, 5],
+    [-20, -50, -2, -2],
+    [10, -2, -1, -1],
+    [5, -2, -1, -1],
+];
+
+/// 局面の評価関数を表すトレイト
+///
+/// ```Engine::with_evaluator```に渡すと、先読みの末端の局面をこれで評価する。
+/// 終局した局面は評価関数を使わずに駒の個数の差で評価するので、途中の局面だけ考えればよい。
+/// 返した値は、勝った終局の局面（駒1個の差につき10000）の評価値を超えないように```Engine```が切り詰める。
+/// 並列に読むときは複数のスレッドから同時に呼ばれるので、```Sync```である必要がある。
+pub trait Evaluator: Sync {
+    /// 手番の側から見た局面の評価値（大きいほど手番の側が有利）
+    fn evaluate(&self, bs: &BoardState) -> i32;
+}
+
+/// 評価関数の重みの上限（絶対値）
+///
+/// 重みを掛けて足し合わせた評価値が桁あふれしないように制限する。
+/// 全てのマス目や項目の合計は10000を超えることもあるので、終局した局面の評価値を超えないようにするのは
+/// この上限ではなく```Engine```（評価関数の値を切り詰める）。
+pub const MAX_WEIGHT: i32 = 1000;
+
+/// 評価関数の重みの設定
+///
+/// TOMLファイルから読み込める（書かれていない項目は既定値になる）。
+/// 例えば次のように書く（値は既定値）。
+///
+/// ```toml
+/// [positional]
+/// squares = [[100, -20, 10, 5], [-20, -50, -2, -2], [10, -2, -1, -1], [5, -2, -1, -1]]
+/// mobility = 5
+///
+/// [composite]
+/// mobility = [10, 4]
+/// stability = [20, 40]
+/// frontier = [5, 2]
+/// parity = [0, 20]
+/// ```
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EvalWeights {
+    pub positional: PositionalWeights,
+    pub composite: CompositeWeights,
+}
+
+/// ```PositionalEvaluator```の重み
+///
+/// squaresはマス目の重み（8×8の盤面の左上の4×4の部分、```SQUARE_WEIGHTS```を参照）、
+/// mobilityは置けるマスの個数の差の重み。
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PositionalWeights {
+    pub squares: [[i32; 4]; 4],
+    pub mobility: i32,
+}
+
+impl Default for PositionalWeights {
+    fn default() -> PositionalWeights {
+        PositionalWeights {
+            squares: SQUARE_WEIGHTS,
+            mobility: 5,
+        }
+    }
+}
+
+/// ```CompositeEvaluator```の重み
+///
+/// それぞれ\[序盤の重み, 終盤の重み\]で、途中は埋まったマスの割合に応じて間を取る。
+/// mobilityは置けるマスの個数の差、stabilityは確定石の個数の差、
+/// frontierは外側に面した駒の個数の差（多いほど不利とみなして引く）、parityは偶数理論の重み。
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CompositeWeights {
+    pub mobility: [i32; 2],
+    pub stability: [i32; 2],
+    pub frontier: [i32; 2],
+    pub parity: [i32; 2],
+}
+
+impl Default for CompositeWeights {
+    fn default() -> CompositeWeights {
+        CompositeWeights {
+            mobility: [10, 4],
+            stability: [20, 40],
+            frontier: [5, 2],
+            parity: [0, 20],
+        }
+    }
+}
+
+impl EvalWeights {
+    /// TOML形式の文字列から読み込む
+    pub fn from_toml(text: &str) -> Result<EvalWeights, WeightsError> {
+        let weights: EvalWeights = toml::from_str(text).map_err(WeightsError::Parse)?;
+        weights.validate()?;
+        Ok(weights)
+    }
+
+    /// TOMLファイルから読み込む
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<EvalWeights, WeightsError> {
+        let text = fs::read_to_string(path)?;
+        EvalWeights::from_toml(&text)
+    }
+
+    /// TOML形式の文字列にする（```from_toml```で読み込める）
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("重みをTOMLにできない")
+    }
+
+    /// TOMLファイルに書き込む
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), WeightsError> {
+        fs::write(path, self.to_toml())?;
+        Ok(())
+    }
+
+    /// 値が使えるものかどうかを確かめる
+    ///
+    /// 全ての重みの絶対値は```MAX_WEIGHT```以下でなければならない。
+    /// マス目の重みは、盤面を斜めに折り返しても同じ評価になるように対称でなければならない。
+    pub fn validate(&self) -> Result<(), WeightsError> {
+        let p = &self.positional;
+        let c = &self.composite;
+        let mut values: Vec<(String, i32)> = vec![("positional.mobility".to_string(), p.mobility)];
+        for (i, row) in p.squares.iter().enumerate() {
+            for (j, &w) in row.iter().enumerate() {
+                values.push((format!("positional.squares[{}][{}]", i, j), w));
+            }
+        }
+        for (name, pair) in [
+            ("mobility", c.mobility),
+            ("stability", c.stability),
+            ("frontier", c.frontier),
+            ("parity", c.parity),
+        ]
+        .iter()
+        {
+            for (k, &w) in pair.iter().enumerate() {
+                values.push((format!("composite.{}[{}]", name, k), w));
+            }
+        }
+        if let Some((name, value)) = values.into_iter().find(|(_, w)| w.abs() > MAX_WEIGHT) {
+            return Err(WeightsError::OutOfRange { name, value });
+        }
+
+        for i in 0..4 {
+            for j in 0..i {
+                if p.squares[i][j] != p.squares[j][i] {
+                    return Err(WeightsError::Asymmetric { row: i, col: j });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// マス目の重みによる評価関数（```Engine::new```で使われる）
+///
+/// 終盤は駒の個数の差だけで評価する。
+/// それまではマス目の重みの合計の差に、置けるマスの個数の差を加える。
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct PositionalEvaluator {
+    weights: PositionalWeights,
+}
+
+impl PositionalEvaluator {
+    /// 重みを指定して作成する
+    pub fn new(weights: PositionalWeights) -> PositionalEvaluator {
+        PositionalEvaluator { weights }
+    }
+}
+
+impl Evaluator for PositionalEvaluator {
+    fn evaluate(&self, bs: &BoardState) -> i32 {
+        let me = bs.turn();
+        if is_endgame(bs) {
+            return bs.disc_difference(me);
+        }
+        let op = me.opponent();
+        let w = &self.weights;
+        let mobility = bs.mobility(me) as i32 - bs.mobility(op) as i32;
+        positional_score(bs, me, &w.squares) - positional_score(bs, op, &w.squares)
+            + mobility * w.mobility
+    }
+}
+
+/// 置けるマスの数、確定石、開放度、偶数理論を組み合わせた評価関数
+///
+/// それぞれの重みは、序盤から終盤にかけて（埋まったマスの割合に応じて）変える。
+/// 既定の重みでは、序盤は置けるマスの数と、外側に面した駒（frontier）の少なさを重く見て、
+/// 終盤に近づくほど確定石と、最後の1マスを打てるかどうか（偶数理論）を重く見る。
+/// 終盤は駒の個数の差だけで評価する。
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct CompositeEvaluator {
+    weights: CompositeWeights,
+}
+
+impl CompositeEvaluator {
+    /// 重みを指定して作成する
+    pub fn new(weights: CompositeWeights) -> CompositeEvaluator {
+        CompositeEvaluator { weights }
+    }
+}
+
+impl Evaluator for CompositeEvaluator {
+    fn evaluate(&self, bs: &BoardState) -> i32 {
+        let me = bs.turn();
+        if is_endgame(bs) {
+            return bs.disc_difference(me);
+        }
+        let op = me.opponent();
+        let w = &self.weights;
+
+        // 埋まったマスの割合（0から100）
+        let score = bs.score();
+        let filled = score.black + score.white;
+        let phase = (filled * 100 / (filled + score.empty)) as i32;
+        let weight = |pair: [i32; 2]| (pair[0] * (100 - phase) + pair[1] * phase) / 100;
+
+        let mobility = bs.mobility(me) as i32 - bs.mobility(op) as i32;
+        let stable = bs.stable_discs(me).len() as i32 - bs.stable_discs(op).len() as i32;
+        let frontier = bs.frontier_discs(me) as i32 - bs.frontier_discs(op) as i32;
+        // 空きマスが奇数なら、このまま交互に打てば手番の側が最後の1マスを打てる
+        let parity = if score.empty % 2 == 1 { 1 } else { -1 };
+
+        mobility * weight(w.mobility) + stable * weight(w.stability) - frontier * weight(w.frontier)
+            + parity * weight(w.parity)
+    }
+}
+
+/// 終盤かどうか（空きマスの数が盤面の一辺の長さ以下）
+pub(crate) fn is_endgame(bs: &BoardState) -> bool {
+    bs.score().empty <= bs.get_size()
+}
+
+/// playerの駒が置かれたマス目の重みの合計
+fn positional_score(bs: &BoardState, player: Player, squares: &[[i32; 4]; 4]) -> i32 {
+    bs.cells()
+        .filter(|&(_, cell)| cell == Cell::Occupied(player))
+        .map(|(c, _)| square_weight(bs.get_size(), c, squares))
+        .sum()
+}
+
+/// 一辺の長さがsizeの盤面での、coordのマス目の重み
+fn square_weight(size: usize, coord: Coord, squares: &[[i32; 4]; 4]) -> i32 {
+    let r = coord.row.min(size - 1 - coord.row).min(3);
+    let c = coord.col.min(size - 1 - coord.col).min(3);
+    squares[r][c]
+}
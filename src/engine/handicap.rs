@@ -0,0 +1,88 @@
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::boardstate::BoardState;
+use crate::engine::eval::Evaluator;
+use crate::engine::strategy::{make_rng, Strategy};
+use crate::engine::{Engine, SearchResult};
+use crate::game::Move;
+
+/// 最善手の評価値からの下がり幅の既定値
+const DEFAULT_MARGIN: i32 = 100;
+
+/// 強いエンジンに、わざと最善でない手を混ぜさせる（人間らしく間違える）CPU
+///
+/// rateの確率で、最善手以外の手のうち、評価値の下がり幅がmargin以下のものからランダムに選ぶ
+/// （そのような手がなければ最善手を打つ）。
+/// 全ての手を読むので（```Engine::analyze```）、同じエンジンをそのまま使うより時間がかかる。
+/// 制限時間のあるエンジンなら、その時間内に読み終えた一番深い結果から選ぶ。
+pub struct HandicapStrategy<E> {
+    engine: Engine<E>,
+    rate: f64,
+    margin: i32,
+    rng: SmallRng,
+}
+
+impl<E: Evaluator> HandicapStrategy<E> {
+    /// engineに、rate（0以上1以下）の確率で最善でない手を打たせる
+    pub fn new(engine: Engine<E>, rate: f64) -> HandicapStrategy<E> {
+        assert!((0.0..=1.0).contains(&rate));
+        HandicapStrategy {
+            engine,
+            rate,
+            margin: DEFAULT_MARGIN,
+            rng: make_rng(None),
+        }
+    }
+
+    /// 最善でない手を選ぶときの、評価値の下がり幅の上限を指定する
+    pub fn margin(self, margin: i32) -> HandicapStrategy<E> {
+        HandicapStrategy { margin, ..self }
+    }
+
+    /// 乱数の種を指定する
+    pub fn seed(self, seed: u64) -> HandicapStrategy<E> {
+        HandicapStrategy {
+            rng: make_rng(Some(seed)),
+            ..self
+        }
+    }
+
+    /// 評価値の高い順に並んだ探索の結果から打つ手を選ぶ（置けるマスがなければNone）
+    fn pick(&mut self, mut results: Vec<SearchResult>) -> Option<SearchResult> {
+        let best = results.first()?.score;
+        if self.rng.gen_bool(self.rate) {
+            let candidates: Vec<usize> = (1..results.len())
+                .filter(|&i| best - results[i].score <= self.margin)
+                .collect();
+            if let Some(&i) = candidates.choose(&mut self.rng) {
+                return Some(results.swap_remove(i));
+            }
+        }
+        Some(results.swap_remove(0))
+    }
+}
+
+impl<E: Evaluator> Strategy for HandicapStrategy<E> {
+    fn choose_move(&mut self, bs: &BoardState) -> Move {
+        let results = self.engine.analyze(bs);
+        self.pick(results)
+            .map_or(Move::Pass, |r| Move::Place(r.best_move))
+    }
+
+    /// 投了するかどうかは、最善手の評価値で判断する（```Engine::resign_threshold```）
+    fn choose_move_or_resign(&mut self, bs: &BoardState) -> Option<Move> {
+        let results = self.engine.analyze(bs);
+        if results
+            .first()
+            .is_some_and(|r| self.engine.resigns_after(r))
+        {
+            return None;
+        }
+        Some(
+            self.pick(results)
+                .map_or(Move::Pass, |r| Move::Place(r.best_move)),
+        )
+    }
+}
@@ -0,0 +1,237 @@
+use std::time::{Duration, Instant};
+
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::boardstate::{BoardState, Coord, Player};
+use crate::engine::strategy::{make_rng, Strategy};
+use crate::game::Move;
+
+/// UCTの探索の強さ（大きいほど試した回数の少ない手を試す）
+const EXPLORATION: f64 = std::f64::consts::SQRT_2;
+
+/// プレイアウト（終局まで適当に打つ部分）での手の選び方
+///
+/// ```Random```は置けるマスから一様にランダムに選ぶ。
+/// ```Heuristic```は隅に置けるなら隅を選び、それ以外では隅の斜め隣（X打ち）を避ける。
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Playout {
+    Random,
+    Heuristic,
+}
+
+/// モンテカルロ木探索（UCT）で手を選ぶ思考エンジン
+///
+/// iterationsとtime_limitのどちらか（両方指定した場合は先に達した方）で探索を打ち切る。
+/// rngはプレイアウトなどに使う乱数生成器。
+#[derive(Clone, Debug)]
+pub struct Mcts {
+    iterations: Option<u32>,
+    time_limit: Option<Duration>,
+    playout: Playout,
+    rng: SmallRng,
+}
+
+/// 探索木のノード
+///
+/// mvはこのノードに来るときに打った手、moverはそれを打った側（根ではNone）。
+/// untriedはまだ子ノードを作っていない手、winsはmoverから見た勝ち数（引き分けは0.5）。
+struct Node {
+    mv: Option<Coord>,
+    mover: Option<Player>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<Coord>,
+    visits: u32,
+    wins: f64,
+}
+
+impl Node {
+    fn new(
+        mv: Option<Coord>,
+        mover: Option<Player>,
+        parent: Option<usize>,
+        bs: &BoardState,
+    ) -> Node {
+        let untried = if bs.is_game_over() {
+            Vec::new()
+        } else {
+            bs.legal_moves().collect()
+        };
+        Node {
+            mv,
+            mover,
+            parent,
+            children: Vec::new(),
+            untried,
+            visits: 0,
+            wins: 0.0,
+        }
+    }
+}
+
+impl Mcts {
+    /// iterations回プレイアウトするエンジンを作成する
+    pub fn with_iterations(iterations: u32) -> Mcts {
+        Mcts {
+            iterations: Some(iterations),
+            time_limit: None,
+            playout: Playout::Heuristic,
+            rng: make_rng(None),
+        }
+    }
+
+    /// 制限時間いっぱいまでプレイアウトするエンジンを作成する
+    pub fn with_time_limit(limit: Duration) -> Mcts {
+        Mcts {
+            iterations: None,
+            time_limit: Some(limit),
+            playout: Playout::Heuristic,
+            rng: make_rng(None),
+        }
+    }
+
+    /// プレイアウトでの手の選び方を指定する
+    pub fn playout(self, playout: Playout) -> Mcts {
+        Mcts { playout, ..self }
+    }
+
+    /// 乱数の種を指定する
+    ///
+    /// 回数で区切る場合は、同じ種なら同じ局面で同じ手を選ぶ（時間で区切る場合はその限りでない）。
+    pub fn seed(self, seed: u64) -> Mcts {
+        Mcts {
+            rng: make_rng(Some(seed)),
+            ..self
+        }
+    }
+
+    /// 手番の側にとって最善と思われる手を選ぶ（置けるマスがなければNone）
+    ///
+    /// 最も多く試された手を選ぶ。
+    pub fn best_move(&mut self, bs: &BoardState) -> Option<Coord> {
+        let deadline = self.time_limit.map(|t| Instant::now() + t);
+        let mut tree: Vec<Node> = vec![Node::new(None, None, None, bs)];
+        if tree[0].untried.is_empty() {
+            return None;
+        }
+
+        let mut count: u32 = 0;
+        loop {
+            if self.iterations.is_some_and(|n| count >= n)
+                || deadline.is_some_and(|d| Instant::now() >= d)
+            {
+                break;
+            }
+            count += 1;
+
+            // 選択: 全ての手を試したノードでは、UCTの値が最大の子ノードに進む
+            let mut board = bs.clone();
+            let mut node = 0;
+            while tree[node].untried.is_empty() && !tree[node].children.is_empty() {
+                let parent_visits = tree[node].visits as f64;
+                node = *tree[node]
+                    .children
+                    .iter()
+                    .max_by(|&&a, &&b| {
+                        uct(&tree[a], parent_visits)
+                            .partial_cmp(&uct(&tree[b], parent_visits))
+                            .unwrap()
+                    })
+                    .unwrap();
+                let c = tree[node].mv.unwrap();
+                board.put(c.row, c.col);
+            }
+
+            // 展開: まだ試していない手があれば1つ選んで子ノードを作る
+            if !tree[node].untried.is_empty() {
+                let k = self.rng.gen_range(0, tree[node].untried.len());
+                let c = tree[node].untried.swap_remove(k);
+                let mover = board.turn();
+                board.put(c.row, c.col);
+                tree.push(Node::new(Some(c), Some(mover), Some(node), &board));
+                let child = tree.len() - 1;
+                tree[node].children.push(child);
+                node = child;
+            }
+
+            // プレイアウト: 終局まで打って勝敗を決める
+            let winner = play_out(self.playout, board, &mut self.rng);
+
+            // 逆伝播: 根まで戻りながら結果を記録する
+            let mut current = Some(node);
+            while let Some(i) = current {
+                let n = &mut tree[i];
+                n.visits += 1;
+                n.wins += match (winner, n.mover) {
+                    (None, _) => 0.5,
+                    (Some(w), Some(m)) if w == m => 1.0,
+                    _ => 0.0,
+                };
+                current = n.parent;
+            }
+        }
+
+        tree[0]
+            .children
+            .iter()
+            .max_by_key(|&&i| tree[i].visits)
+            .and_then(|&i| tree[i].mv)
+    }
+}
+
+impl Strategy for Mcts {
+    fn choose_move(&mut self, bs: &BoardState) -> Move {
+        self.best_move(bs).map_or(Move::Pass, Move::Place)
+    }
+}
+
+/// 終局まで打って勝った側を返す（引き分けならNone）
+fn play_out<R: Rng>(playout: Playout, mut bs: BoardState, rng: &mut R) -> Option<Player> {
+    while !bs.is_game_over() {
+        let moves: Vec<Coord> = bs.legal_moves().collect();
+        let c = match playout {
+            Playout::Random => *moves.choose(rng).unwrap(),
+            Playout::Heuristic => heuristic_choice(&bs, &moves, rng),
+        };
+        bs.put(c.row, c.col);
+    }
+    match bs.disc_difference(Player::Black) {
+        d if d > 0 => Some(Player::Black),
+        d if d < 0 => Some(Player::White),
+        _ => None,
+    }
+}
+
+/// 隅があれば隅を、なければX打ち以外からランダムに選ぶ
+fn heuristic_choice<R: Rng>(bs: &BoardState, moves: &[Coord], rng: &mut R) -> Coord {
+    let corners = bs.corners();
+    let corner_moves: Vec<Coord> = moves
+        .iter()
+        .copied()
+        .filter(|c| corners.contains(c))
+        .collect();
+    if let Some(&c) = corner_moves.choose(rng) {
+        return c;
+    }
+    let x_squares = bs.x_squares();
+    let safe_moves: Vec<Coord> = moves
+        .iter()
+        .copied()
+        .filter(|c| !x_squares.contains(c))
+        .collect();
+    *safe_moves
+        .choose(rng)
+        .or_else(|| moves.choose(rng))
+        .unwrap()
+}
+
+/// UCTの値（勝率＋試した回数の少なさによるボーナス）
+fn uct(node: &Node, parent_visits: f64) -> f64 {
+    if node.visits == 0 {
+        return f64::INFINITY;
+    }
+    let n = node.visits as f64;
+    node.wins / n + EXPLORATION * (parent_visits.ln() / n).sqrt()
+}
@@ -0,0 +1,218 @@
+use crate::boardstate::{BoardState, Coord};
+use crate::engine::eval::{is_endgame, CompositeEvaluator, Evaluator};
+
+/// パターンで評価する盤面の一辺の長さ
+const PATTERN_SIZE: usize = 8;
+
+/// 置けるマスの個数の差の重み（パターンでは測れないので別に加える）
+const MOBILITY_WEIGHT: i32 = 4;
+
+/// パターンの表の種類（```PatternEvaluator```の```tables```の添字）
+const EDGE: usize = 0;
+const CORNER: usize = 1;
+const DIAGONAL: usize = 2;
+
+/// 盤面上の1つのパターン
+///
+/// cellsはパターンに含まれるマス目（隅に近い方から並べる）、tableは評価値を引く表の種類。
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Pattern {
+    cells: Vec<Coord>,
+    table: usize,
+}
+
+impl Pattern {
+    /// 手番の側から見たパターンの番号
+    ///
+    /// k番目のマス目が空なら0、手番の側の駒なら1、相手の駒なら2として、3のk乗の位に置いた数。
+    fn index(&self, bs: &BoardState) -> usize {
+        let me = bs.turn();
+        self.cells.iter().rev().fold(0, |acc, &c| {
+            let digit = match bs.get(c) {
+                None => 0,
+                Some(p) if p == me => 1,
+                Some(_) => 2,
+            };
+            acc * 3 + digit
+        })
+    }
+}
+
+/// 辺、隅、対角線の駒の並び（パターン）ごとの評価値を表から引いて合計する評価関数
+///
+/// 8×8の盤面では、4本の辺（8マス）、4つの隅の3×3の領域（9マス）、2本の対角線（8マス）について、
+/// 駒の並びを3進数の番号にして表から評価値を引き、置けるマスの個数の差を加える。
+/// 表は作成するときに、確定石や、空いている隅の隣に置いた駒の危うさなどの決まりから計算しておく。
+/// 他の大きさの盤面では```CompositeEvaluator```で評価する。
+/// 終盤は駒の個数の差だけで評価する。
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PatternEvaluator {
+    tables: [Vec<i32>; 3],
+    patterns: Vec<Pattern>,
+    fallback: CompositeEvaluator,
+}
+
+impl PatternEvaluator {
+    /// 表を計算して作成する（8×8以外の盤面では既定の重みの```CompositeEvaluator```を使う）
+    pub fn new() -> PatternEvaluator {
+        PatternEvaluator::with_fallback(CompositeEvaluator::default())
+    }
+
+    /// 8×8以外の盤面で使う評価関数を指定して作成する
+    pub fn with_fallback(fallback: CompositeEvaluator) -> PatternEvaluator {
+        PatternEvaluator {
+            tables: [
+                build_table(8, edge_merit),
+                build_table(9, corner_merit),
+                build_table(8, diagonal_merit),
+            ],
+            patterns: patterns(),
+            fallback,
+        }
+    }
+}
+
+impl Default for PatternEvaluator {
+    fn default() -> PatternEvaluator {
+        PatternEvaluator::new()
+    }
+}
+
+impl Evaluator for PatternEvaluator {
+    fn evaluate(&self, bs: &BoardState) -> i32 {
+        if bs.get_size() != PATTERN_SIZE {
+            return self.fallback.evaluate(bs);
+        }
+        let me = bs.turn();
+        if is_endgame(bs) {
+            return bs.disc_difference(me);
+        }
+        let op = me.opponent();
+        let pattern: i32 = self
+            .patterns
+            .iter()
+            .map(|p| self.tables[p.table][p.index(bs)])
+            .sum();
+        let mobility = bs.mobility(me) as i32 - bs.mobility(op) as i32;
+        pattern + mobility * MOBILITY_WEIGHT
+    }
+}
+
+/// 8×8の盤面上の全てのパターン
+fn patterns() -> Vec<Pattern> {
+    let last = PATTERN_SIZE - 1;
+    let line = |f: &dyn Fn(usize) -> Coord| (0..PATTERN_SIZE).map(f).collect::<Vec<Coord>>();
+    let mut patterns = Vec::new();
+
+    // 辺
+    for cells in [
+        line(&|i| Coord::new(0, i)),
+        line(&|i| Coord::new(last, i)),
+        line(&|i| Coord::new(i, 0)),
+        line(&|i| Coord::new(i, last)),
+    ]
+    .iter()
+    {
+        patterns.push(Pattern {
+            cells: cells.clone(),
+            table: EDGE,
+        });
+    }
+
+    // 隅の3×3の領域（隅から行ごとに並べる）
+    for &(bottom, right) in [(false, false), (false, true), (true, false), (true, true)].iter() {
+        let cells = (0..9)
+            .map(|k| {
+                let (r, c) = (k / 3, k % 3);
+                Coord::new(
+                    if bottom { last - r } else { r },
+                    if right { last - c } else { c },
+                )
+            })
+            .collect();
+        patterns.push(Pattern {
+            cells,
+            table: CORNER,
+        });
+    }
+
+    // 対角線
+    for cells in [
+        line(&|i| Coord::new(i, i)),
+        line(&|i| Coord::new(i, last - i)),
+    ]
+    .iter()
+    {
+        patterns.push(Pattern {
+            cells: cells.clone(),
+            table: DIAGONAL,
+        });
+    }
+    patterns
+}
+
+/// len個のマス目からなるパターンの表を作成する
+///
+/// 各番号について、手番の側（1）から見た価値から相手（2）から見た価値を引いたものを評価値にする。
+fn build_table(len: usize, merit: fn(&[u8], u8) -> i32) -> Vec<i32> {
+    (0..3usize.pow(len as u32))
+        .map(|index| {
+            let digits: Vec<u8> = (0..len)
+                .map(|k| (index / 3usize.pow(k as u32) % 3) as u8)
+                .collect();
+            merit(&digits, 1) - merit(&digits, 2)
+        })
+        .collect()
+}
+
+/// 辺のパターンでのsideの駒の価値
+///
+/// 隅からsideの駒が途切れずに続いている駒と、辺が全て埋まっている場合の駒は確定石とみなす。
+/// 隅が空いているときの隅の隣（C打ち）は隅を取られやすいので減点する。
+fn edge_merit(cells: &[u8], side: u8) -> i32 {
+    let last = cells.len() - 1;
+    let full = cells.iter().all(|&d| d != 0);
+    let stable = |i: usize| {
+        full || cells[..=i].iter().all(|&d| d == side) || cells[i..].iter().all(|&d| d == side)
+    };
+    (0..cells.len())
+        .filter(|&i| cells[i] == side)
+        .map(|i| {
+            if stable(i) {
+                20
+            } else if (i == 1 && cells[0] == 0) || (i == last - 1 && cells[last] == 0) {
+                -25
+            } else {
+                2
+            }
+        })
+        .sum()
+}
+
+/// 隅の3×3の領域のパターンでのsideの駒の価値
+///
+/// 隅の駒を高く評価し、隅が空いているときは隅の斜め隣（X打ち）と隣（C打ち）を減点する。
+fn corner_merit(cells: &[u8], side: u8) -> i32 {
+    let corner_empty = cells[0] == 0;
+    (0..cells.len())
+        .filter(|&k| cells[k] == side)
+        .map(|k| match (k, corner_empty) {
+            (0, _) => 60,
+            (4, true) => -50,
+            (1, true) | (3, true) => -15,
+            (_, true) => -1,
+            (4, false) => 2,
+            (1, false) | (3, false) => 5,
+            (_, false) => 1,
+        })
+        .sum()
+}
+
+/// 対角線のパターンでのsideの駒の価値
+///
+/// 隅からsideの駒が途切れずに続いている駒を評価する。
+fn diagonal_merit(cells: &[u8], side: u8) -> i32 {
+    let from_start = cells.iter().take_while(|&&d| d == side).count();
+    let from_end = cells.iter().rev().take_while(|&&d| d == side).count();
+    (from_start + from_end).min(cells.len()) as i32 * 8
+}
@@ -0,0 +1,73 @@
+use crate::engine::SearchResult;
+
+/// 評価値から勝つ確率を見積もるときの尺度の既定値
+///
+/// 8×8の盤面で2手先まで読むエンジン同士の自己対局（```Tuner::win_samples```）から
+/// ```WinProbability::calibrate```で求めた値。
+const DEFAULT_SCALE: f64 = 105.0;
+
+/// 探索の評価値を、手番の側が勝つ確率に変換するモデル
+///
+/// 途中の局面の評価値scoreは、ロジスティック関数 1 / (1 + exp(-score / scale)) で確率にする。
+/// 終局まで読み切れた場合は、勝ちなら1、負けなら0、引き分けなら0.5にする。
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WinProbability {
+    scale: f64,
+}
+
+impl WinProbability {
+    /// 尺度scaleを指定して作成する（大きいほど評価値の差が確率の差になりにくい）
+    pub fn new(scale: f64) -> WinProbability {
+        assert!(scale > 0.0);
+        WinProbability { scale }
+    }
+
+    /// 尺度
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// 途中の局面の評価値を勝つ確率にする
+    pub fn of_score(&self, score: i32) -> f64 {
+        1.0 / (1.0 + (-(score as f64) / self.scale).exp())
+    }
+
+    /// 探索の結果から、手番の側が勝つ確率を見積もる
+    pub fn of(&self, result: &SearchResult) -> f64 {
+        match result.final_disc_difference() {
+            Some(d) if d > 0 => 1.0,
+            Some(d) if d < 0 => 0.0,
+            Some(_) => 0.5,
+            None => self.of_score(result.score),
+        }
+    }
+
+    /// （評価値, 実際の結果）の組から、最も当てはまりのよい尺度を求める
+    ///
+    /// 実際の結果は、手番の側が勝ったら1、負けたら0、引き分けなら0.5。
+    /// 尺度を1から1000まで変えて、対数損失が最小になるものを選ぶ（組がなければ既定値）。
+    pub fn calibrate(samples: &[(i32, f64)]) -> WinProbability {
+        if samples.is_empty() {
+            return WinProbability::default();
+        }
+        let loss = |model: &WinProbability| -> f64 {
+            samples
+                .iter()
+                .map(|&(score, outcome)| {
+                    let p = model.of_score(score).clamp(1e-6, 1.0 - 1e-6);
+                    -(outcome * p.ln() + (1.0 - outcome) * (1.0 - p).ln())
+                })
+                .sum()
+        };
+        (1..=1000)
+            .map(|s| WinProbability::new(s as f64))
+            .min_by(|a, b| loss(a).partial_cmp(&loss(b)).unwrap())
+            .unwrap()
+    }
+}
+
+impl Default for WinProbability {
+    fn default() -> WinProbability {
+        WinProbability::new(DEFAULT_SCALE)
+    }
+}
@@ -0,0 +1,77 @@
+use crate::boardstate::Player;
+use crate::engine::eval::Evaluator;
+use crate::engine::{Engine, SearchResult};
+use crate::game::{Game, Move};
+
+/// 対局を振り返ったときの1手分の結果
+///
+/// numberは1から数えた手数、playedは実際に打った手を読んだ結果、
+/// bestはエンジンが最善と判断した手を読んだ結果（どちらも打った側から見た評価値）。
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MoveReview {
+    pub number: usize,
+    pub played: SearchResult,
+    pub best: SearchResult,
+}
+
+impl MoveReview {
+    /// 最善手と比べて評価値がどれだけ下がったか（0以上）
+    pub fn loss(&self) -> i32 {
+        (self.best.score - self.played.score).max(0)
+    }
+
+    /// 評価値がthreshold以上下がった手（悪手）かどうか
+    pub fn is_blunder(&self, threshold: i32) -> bool {
+        self.loss() >= threshold
+    }
+
+    /// 棋譜につける記号
+    ///
+    /// 最善手と同じ評価なら```!```、thresholdの半分以上下がれば```?```、threshold以上下がれば```??```、
+    /// それ以外は空文字列。
+    pub fn symbol(&self, threshold: i32) -> &'static str {
+        match self.loss() {
+            0 => "!",
+            loss if loss >= threshold => "??",
+            loss if loss * 2 >= threshold => "?",
+            _ => "",
+        }
+    }
+}
+
+/// 対局を始めから並べ直して、playerが打った各手をエンジンの最善手と比べる
+///
+/// パスは振り返りに含めない。
+pub fn review_game<E: Evaluator>(
+    engine: &Engine<E>,
+    game: &Game,
+    player: Player,
+) -> Vec<MoveReview> {
+    let mut bs = game.start().clone();
+    let mut reviews = Vec::new();
+    for r in game.history() {
+        let c = match r.mv {
+            Move::Place(c) => c,
+            Move::Pass => {
+                // 置いた手の後の自動のパスは```put```で済んでいる
+                if bs.must_pass() {
+                    bs.pass().unwrap();
+                }
+                continue;
+            }
+        };
+        if r.player == player {
+            let results = engine.analyze(&bs);
+            let played = results.iter().find(|x| x.best_move == c).cloned();
+            if let (Some(best), Some(played)) = (results.first().cloned(), played) {
+                reviews.push(MoveReview {
+                    number: r.number,
+                    played,
+                    best,
+                });
+            }
+        }
+        bs.put(c.row, c.col);
+    }
+    reviews
+}
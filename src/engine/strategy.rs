@@ -0,0 +1,230 @@
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::boardstate::{BoardState, Coord};
+use crate::game::Move;
+
+/// CPUの手の選び方を表すトレイト
+///
+/// 手番の側の手を選んで返す。置けるマスがなければ```Move::Pass```を返す。
+/// 状態を持つCPUのために```&mut self```を受け取る。
+pub trait Strategy {
+    /// 手番の側の手を選ぶ
+    fn choose_move(&mut self, bs: &BoardState) -> Move;
+
+    /// 投了するかどうかも判断して手を選ぶ（投了する場合はNone）
+    ///
+    /// 既定では投了せず、```choose_move```で選んだ手を返す。
+    fn choose_move_or_resign(&mut self, bs: &BoardState) -> Option<Move> {
+        Some(self.choose_move(bs))
+    }
+}
+
+impl<S: Strategy + ?Sized> Strategy for Box<S> {
+    fn choose_move(&mut self, bs: &BoardState) -> Move {
+        (**self).choose_move(bs)
+    }
+
+    fn choose_move_or_resign(&mut self, bs: &BoardState) -> Option<Move> {
+        (**self).choose_move_or_resign(bs)
+    }
+}
+
+/// 乱数生成器を作成する
+///
+/// seedが```Some```なら毎回同じ乱数列になり、Noneなら実行するたびに異なる乱数列になる。
+pub fn make_rng(seed: Option<u64>) -> SmallRng {
+    match seed {
+        Some(s) => SmallRng::seed_from_u64(s),
+        None => SmallRng::from_entropy(),
+    }
+}
+
+/// 置けるマス目から、裏返せる枚数で重み付けしてランダムに選ぶCPU（隅に置けるなら隅を優先）
+#[derive(Clone, Debug)]
+pub struct RandomStrategy {
+    rng: SmallRng,
+}
+
+impl RandomStrategy {
+    /// 実行するたびに異なる手を選ぶCPUを作成する
+    pub fn new() -> RandomStrategy {
+        RandomStrategy::with_seed(None)
+    }
+
+    /// 乱数の種を指定して作成する（Noneなら```new```と同じ）
+    pub fn with_seed(seed: Option<u64>) -> RandomStrategy {
+        RandomStrategy {
+            rng: make_rng(seed),
+        }
+    }
+}
+
+impl Default for RandomStrategy {
+    fn default() -> RandomStrategy {
+        RandomStrategy::new()
+    }
+}
+
+impl Strategy for RandomStrategy {
+    fn choose_move(&mut self, bs: &BoardState) -> Move {
+        // 置けるマス目を重み付けしつつVecで管理
+        let mut options: Vec<Coord> = Vec::new();
+        let mut options_corners: Vec<Coord> = Vec::new();
+        let vec = bs.cnt_reversable();
+        let corners = bs.corners();
+        for c in bs.legal_moves() {
+            for _ in 0..vec[c.row][c.col] {
+                options.push(c);
+            }
+            if corners.contains(&c) {
+                options_corners.push(c);
+            }
+        }
+
+        // ランダムに選ぶ
+        if options_corners.is_empty() {
+            options
+        } else {
+            options_corners
+        }
+        .choose(&mut self.rng)
+        .map_or(Move::Pass, |&c| Move::Place(c))
+    }
+}
+
+/// 一番多く裏返せるマス目に置くCPU（同じ枚数のマス目が複数あればその中からランダムに選ぶ）
+#[derive(Clone, Debug)]
+pub struct GreedyStrategy {
+    rng: SmallRng,
+}
+
+impl GreedyStrategy {
+    /// 実行するたびに異なる手を選ぶCPUを作成する
+    pub fn new() -> GreedyStrategy {
+        GreedyStrategy::with_seed(None)
+    }
+
+    /// 乱数の種を指定して作成する（Noneなら```new```と同じ）
+    pub fn with_seed(seed: Option<u64>) -> GreedyStrategy {
+        GreedyStrategy {
+            rng: make_rng(seed),
+        }
+    }
+}
+
+impl Default for GreedyStrategy {
+    fn default() -> GreedyStrategy {
+        GreedyStrategy::new()
+    }
+}
+
+impl Strategy for GreedyStrategy {
+    fn choose_move(&mut self, bs: &BoardState) -> Move {
+        let vec = bs.cnt_reversable();
+        let max = match bs.legal_moves().map(|c| vec[c.row][c.col]).max() {
+            Some(m) => m,
+            None => return Move::Pass,
+        };
+        let options: Vec<Coord> = bs
+            .legal_moves()
+            .filter(|c| vec[c.row][c.col] == max)
+            .collect();
+        Move::Place(*options.choose(&mut self.rng).unwrap())
+    }
+}
+
+/// 簡単な決まりに従って手を選ぶCPU
+///
+/// 隅に置けるなら隅に置く。
+/// そうでなければ、空いている隅の隣（X打ちやC打ち）をなるべく避けて、
+/// 置いた後に相手が置けるマスの数が最も少なくなる手を選ぶ（同じ数の手が複数あればランダムに選ぶ）。
+#[derive(Clone, Debug)]
+pub struct HeuristicStrategy {
+    rng: SmallRng,
+}
+
+impl HeuristicStrategy {
+    /// 実行するたびに異なる手を選ぶCPUを作成する
+    pub fn new() -> HeuristicStrategy {
+        HeuristicStrategy::with_seed(None)
+    }
+
+    /// 乱数の種を指定して作成する（Noneなら```new```と同じ）
+    pub fn with_seed(seed: Option<u64>) -> HeuristicStrategy {
+        HeuristicStrategy {
+            rng: make_rng(seed),
+        }
+    }
+}
+
+impl Default for HeuristicStrategy {
+    fn default() -> HeuristicStrategy {
+        HeuristicStrategy::new()
+    }
+}
+
+impl Strategy for HeuristicStrategy {
+    fn choose_move(&mut self, bs: &BoardState) -> Move {
+        let rng = &mut self.rng;
+        let moves: Vec<Coord> = bs.legal_moves().collect();
+        if moves.is_empty() {
+            return Move::Pass;
+        }
+
+        // 隅に置けるなら隅に置く
+        let corners = bs.corners();
+        let corner_moves: Vec<Coord> = moves
+            .iter()
+            .copied()
+            .filter(|c| corners.contains(c))
+            .collect();
+        if let Some(&c) = corner_moves.choose(rng) {
+            return Move::Place(c);
+        }
+
+        // 空いている隅の隣は、他に置けるマスがあれば避ける
+        let size = bs.get_size();
+        let dangerous: Vec<Coord> = bs
+            .x_squares()
+            .into_iter()
+            .chain(bs.c_squares())
+            .filter(|&c| bs.is_empty(nearest_corner(size, c)))
+            .collect();
+        let safe_moves: Vec<Coord> = moves
+            .iter()
+            .copied()
+            .filter(|c| !dangerous.contains(c))
+            .collect();
+        let candidates = if safe_moves.is_empty() {
+            moves
+        } else {
+            safe_moves
+        };
+
+        // 相手が置けるマスの数が最も少なくなる手を選ぶ
+        let op = bs.turn().opponent();
+        let scored: Vec<(Coord, usize)> = candidates
+            .into_iter()
+            .map(|c| {
+                let mut next = bs.clone();
+                next.put(c.row, c.col);
+                (c, next.mobility(op))
+            })
+            .collect();
+        let min = scored.iter().map(|&(_, m)| m).min().unwrap();
+        let options: Vec<Coord> = scored
+            .into_iter()
+            .filter(|&(_, m)| m == min)
+            .map(|(c, _)| c)
+            .collect();
+        Move::Place(*options.choose(rng).unwrap())
+    }
+}
+
+/// 一辺の長さがsizeの盤面で、coordに一番近い隅
+fn nearest_corner(size: usize, coord: Coord) -> Coord {
+    let edge = |x: usize| if x < size / 2 { 0 } else { size - 1 };
+    Coord::new(edge(coord.row), edge(coord.col))
+}
@@ -0,0 +1,66 @@
+use std::sync::Mutex;
+
+use crate::boardstate::Coord;
+
+/// 置換表に記録した評価値の種類
+///
+/// ```Exact```は正確な値、```Lower```は実際の値がこれ以上（βカットした）、
+/// ```Upper```は実際の値がこれ以下（αを更新できなかった）であることを表す。
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+/// 置換表の1項目
+///
+/// hashは局面のZobristハッシュ（別の局面と衝突していないかの確認用）、
+/// depthはこの評価値を求めたときに読んだ深さ、best_moveはその局面での最善手。
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Entry {
+    pub hash: u64,
+    pub depth: u32,
+    pub score: i32,
+    pub bound: Bound,
+    pub best_move: Option<Coord>,
+}
+
+/// 探索済みの局面の評価値を覚えておく置換表
+///
+/// Zobristハッシュの下位ビットで場所を決める固定長の表で、
+/// 同じ場所に別の局面が来た場合は、深く読んだ方を残す。
+/// 複数のスレッドで共有できるように、項目ごとに```Mutex```で守る。
+pub(crate) struct TranspositionTable {
+    entries: Vec<Mutex<Option<Entry>>>,
+    mask: u64,
+}
+
+impl TranspositionTable {
+    /// 2のbits乗個の項目を持つ置換表を作成する
+    pub fn new(bits: u32) -> TranspositionTable {
+        TranspositionTable {
+            entries: (0..1 << bits).map(|_| Mutex::new(None)).collect(),
+            mask: (1 << bits) - 1,
+        }
+    }
+
+    /// 局面の項目を探す（なければNone）
+    pub fn probe(&self, hash: u64) -> Option<Entry> {
+        self.entries[(hash & self.mask) as usize]
+            .lock()
+            .unwrap()
+            .filter(|e| e.hash == hash)
+    }
+
+    /// 局面の項目を記録する
+    pub fn store(&self, entry: Entry) {
+        let mut slot = self.entries[(entry.hash & self.mask) as usize]
+            .lock()
+            .unwrap();
+        match *slot {
+            Some(e) if e.hash != entry.hash && e.depth > entry.depth => {}
+            _ => *slot = Some(entry),
+        }
+    }
+}
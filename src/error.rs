@@ -0,0 +1,312 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use crate::boardstate::Coord;
+
+/// 盤面の作成などに失敗したときのエラー
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReversiError {
+    /// 盤面のサイズが不正（0や奇数）
+    InvalidSize(usize),
+    /// 行の長さが盤面のサイズと合わない
+    RowLength { row: usize, len: usize, size: usize },
+    /// 盤面を表すのに使えない文字
+    UnknownChar { row: usize, col: usize, ch: char },
+    /// 中央の4マスに駒が置かれていない
+    EmptyCenter(Coord),
+    /// 盤面の外の座標
+    OutOfRange(Coord),
+    /// 塞がれたマスに駒を置こうとした
+    BlockedSquare(Coord),
+    /// そのマスには置けない
+    IllegalMove(Coord),
+    /// 置けるマスがあるのにパスしようとした
+    IllegalPass,
+    /// もう終局している
+    GameOver,
+    /// 手として読めない文字列
+    UnknownMove(String),
+    /// マス目として読めない文字列
+    UnknownCoord(String),
+    /// 局面の文字列（```BoardState::to_position_string```の形式）として読めない文字列
+    InvalidPosition(String),
+}
+
+impl fmt::Display for ReversiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReversiError::InvalidSize(n) => {
+                write!(f, "盤面のサイズ{}は2以上の偶数ではありません", n)
+            }
+            ReversiError::RowLength { row, len, size } => write!(
+                f,
+                "{}行目の長さが{}ですが，盤面のサイズは{}です",
+                row + 1,
+                len,
+                size
+            ),
+            ReversiError::UnknownChar { row, col, ch } => write!(
+                f,
+                "{}行{}列目の文字'{}'は盤面に使えません",
+                row + 1,
+                col + 1,
+                ch
+            ),
+            ReversiError::EmptyCenter(c) => write!(
+                f,
+                "中央の{}行{}列目に駒が置かれていません",
+                c.row + 1,
+                c.col + 1
+            ),
+            ReversiError::OutOfRange(c) => {
+                write!(f, "{}行{}列目は盤面の外です", c.row + 1, c.col + 1)
+            }
+            ReversiError::BlockedSquare(c) => write!(
+                f,
+                "{}行{}列目は塞がれているので駒を置けません",
+                c.row + 1,
+                c.col + 1
+            ),
+            ReversiError::IllegalMove(c) => {
+                write!(f, "{}行{}列目には置けません", c.row + 1, c.col + 1)
+            }
+            ReversiError::IllegalPass => write!(f, "置けるマスがあるのでパスできません"),
+            ReversiError::GameOver => write!(f, "もう終局しています"),
+            ReversiError::UnknownMove(s) => write!(f, "'{}'は手として読めません", s),
+            ReversiError::UnknownCoord(s) => write!(f, "'{}'はマス目として読めません", s),
+            ReversiError::InvalidPosition(s) => write!(f, "'{}'は局面として読めません", s),
+        }
+    }
+}
+
+impl Error for ReversiError {}
+
+/// 手順をまとめて適用するときに失敗したことを表すエラー
+///
+/// indexは適用できなかった手の（0始まりの）位置、errorはその理由。
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReplayError {
+    pub index: usize,
+    pub error: ReversiError,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}手目: {}", self.index + 1, self.error)
+    }
+}
+
+impl Error for ReplayError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// 対局のJSONファイルの読み書きに失敗したことを表すエラー
+#[derive(Debug)]
+pub enum GameFileError {
+    /// ファイルを読み書きできなかった
+    Io(io::Error),
+    /// JSONとして読み書きできなかった、または項目が足りなかった
+    Json(serde_json::Error),
+    /// 始めの局面を作れなかった
+    Board(ReversiError),
+    /// 手順を適用できなかった
+    Moves(ReplayError),
+    /// 手順を適用した盤面が、保存された盤面と一致しなかった
+    Mismatch,
+}
+
+impl fmt::Display for GameFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameFileError::Io(e) => write!(f, "{}", e),
+            GameFileError::Json(e) => write!(f, "JSONの書き方が正しくありません: {}", e),
+            GameFileError::Board(e) => write!(f, "始めの局面が正しくありません: {}", e),
+            GameFileError::Moves(e) => write!(f, "{}", e),
+            GameFileError::Mismatch => write!(f, "手順と保存された盤面が一致しません"),
+        }
+    }
+}
+
+impl Error for GameFileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GameFileError::Io(e) => Some(e),
+            GameFileError::Json(e) => Some(e),
+            GameFileError::Board(e) => Some(e),
+            GameFileError::Moves(e) => Some(e),
+            GameFileError::Mismatch => None,
+        }
+    }
+}
+
+impl From<io::Error> for GameFileError {
+    fn from(e: io::Error) -> GameFileError {
+        GameFileError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for GameFileError {
+    fn from(e: serde_json::Error) -> GameFileError {
+        GameFileError::Json(e)
+    }
+}
+
+/// SGFの棋譜を読めなかったことを表すエラー
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SgfError {
+    /// 文字列の（0始まりの）その位置がSGFの書き方に合わない
+    Syntax(usize),
+    /// そのプロパティの値が正しくない
+    Property(String),
+    /// 始めの局面を作れなかった
+    Board(ReversiError),
+    /// 手順を適用できなかった
+    Moves(ReplayError),
+}
+
+impl fmt::Display for SgfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SgfError::Syntax(pos) => write!(f, "SGFの{}文字目の書き方が正しくありません", pos + 1),
+            SgfError::Property(key) => write!(f, "SGFの{}の値が正しくありません", key),
+            SgfError::Board(e) => write!(f, "始めの局面が正しくありません: {}", e),
+            SgfError::Moves(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for SgfError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SgfError::Board(e) => Some(e),
+            SgfError::Moves(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// WTHOR形式のファイルを読めなかったことを表すエラー
+#[derive(Debug)]
+pub enum WthorError {
+    /// ファイルを読めなかった
+    Io(io::Error),
+    /// ヘッダーに書かれた数のレコードがない
+    Truncated,
+    /// 8×8でない盤面（ヘッダーに書かれた大きさ）
+    Unsupported(u8),
+    /// game局目のindex手目（どちらも0始まり）が盤面の外
+    Move { game: usize, index: usize },
+}
+
+impl fmt::Display for WthorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WthorError::Io(e) => write!(f, "{}", e),
+            WthorError::Truncated => write!(f, "ファイルが途中で終わっています"),
+            WthorError::Unsupported(n) => write!(f, "盤面の大きさ{}の対局は読めません", n),
+            WthorError::Move { game, index } => {
+                write!(f, "{}局目の{}手目が盤面の外です", game + 1, index + 1)
+            }
+        }
+    }
+}
+
+impl Error for WthorError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WthorError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for WthorError {
+    fn from(e: io::Error) -> WthorError {
+        WthorError::Io(e)
+    }
+}
+
+/// 定石集の読み込みに失敗したことを表すエラー
+#[derive(Debug)]
+pub enum BookError {
+    /// ファイルを読めなかった
+    Io(io::Error),
+    /// lineの行目（0始まり）の手順を適用できなかった
+    Line { line: usize, error: ReplayError },
+}
+
+impl fmt::Display for BookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BookError::Io(e) => write!(f, "定石集を読み込めません: {}", e),
+            BookError::Line { line, error } => write!(f, "定石集の{}行目の{}", line + 1, error),
+        }
+    }
+}
+
+impl Error for BookError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BookError::Io(e) => Some(e),
+            BookError::Line { error, .. } => Some(error),
+        }
+    }
+}
+
+impl From<io::Error> for BookError {
+    fn from(e: io::Error) -> BookError {
+        BookError::Io(e)
+    }
+}
+
+/// 評価関数の重みの読み込みに失敗したことを表すエラー
+#[derive(Debug)]
+pub enum WeightsError {
+    /// ファイルを読めなかった
+    Io(io::Error),
+    /// TOMLとして読めなかった、または知らない項目があった
+    Parse(toml::de::Error),
+    /// nameの重みvalueが大きすぎる（または小さすぎる）
+    OutOfRange { name: String, value: i32 },
+    /// マス目の重みのrow行col列目と、col行row列目が異なる（0始まり）
+    Asymmetric { row: usize, col: usize },
+}
+
+impl fmt::Display for WeightsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WeightsError::Io(e) => write!(f, "重みのファイルを読み込めません: {}", e),
+            WeightsError::Parse(e) => write!(f, "重みのファイルの書き方が正しくありません: {}", e),
+            WeightsError::OutOfRange { name, value } => {
+                write!(f, "重み{}の値{}は範囲外です", name, value)
+            }
+            WeightsError::Asymmetric { row, col } => write!(
+                f,
+                "マス目の重みの{}行{}列目と{}行{}列目が異なります",
+                row + 1,
+                col + 1,
+                col + 1,
+                row + 1
+            ),
+        }
+    }
+}
+
+impl Error for WeightsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WeightsError::Io(e) => Some(e),
+            WeightsError::Parse(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for WeightsError {
+    fn from(e: io::Error) -> WeightsError {
+        WeightsError::Io(e)
+    }
+}
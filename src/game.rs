@@ -0,0 +1,645 @@
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::de::{self, DeserializeOwned, Deserializer};
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::boardstate::{BoardBuilder, BoardState, Coord, Player};
+use crate::engine::strategy::Strategy;
+use crate::error::{GameFileError, ReplayError, ReversiError};
+
+/// 1手の内容
+///
+/// ```Place```はそのマスに駒を置く手、```Pass```は置けるマスがなくて手番を渡したことを表す。
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Move {
+    Place(Coord),
+    Pass,
+}
+
+/// 棋譜用の表示（置く手は「行-列」の1始まりの番号、パスは```pass```）
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Move::Place(c) => write!(f, "{}-{}", c.row + 1, c.col + 1),
+            Move::Pass => write!(f, "pass"),
+        }
+    }
+}
+
+/// 棋譜用の表示と同じ形式（「行-列」の1始まりの番号か```pass```）から読み込む
+impl FromStr for Move {
+    type Err = ReversiError;
+
+    fn from_str(s: &str) -> Result<Move, ReversiError> {
+        let s = s.trim();
+        if s == "pass" {
+            return Ok(Move::Pass);
+        }
+        let err = || ReversiError::UnknownMove(s.to_string());
+        let mut it = s.splitn(2, '-');
+        let mut number = || match it.next().map(|x| x.parse::<usize>()) {
+            Some(Ok(n)) if n >= 1 => Ok(n - 1),
+            _ => Err(err()),
+        };
+        let row = number()?;
+        let col = number()?;
+        Ok(Move::Place(Coord::new(row, col)))
+    }
+}
+
+/// JSONなどには棋譜用の表示と同じ文字列で書く
+impl Serialize for Move {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Move {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Move, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// 記録された1手
+///
+/// numberは1から数えた手数（パスも1手と数える）、playerは打った側。
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RecordedMove {
+    pub number: usize,
+    pub player: Player,
+    #[serde(rename = "move")]
+    pub mv: Move,
+}
+
+/// 対局の結果
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    /// 両者とも置けるマスがなくなって終局した（white、blackはそれぞれの駒の個数）
+    Completed { white: usize, black: usize },
+    /// resignedの側が投了した
+    Resignation { resigned: Player },
+    /// flaggedの側の持ち時間が切れた
+    Timeout { flagged: Player },
+}
+
+impl Outcome {
+    /// 勝った方（引き分けならNone）
+    pub fn winner(&self) -> Option<Player> {
+        match *self {
+            Outcome::Completed { white, black } => {
+                if white > black {
+                    Some(Player::White)
+                } else if white < black {
+                    Some(Player::Black)
+                } else {
+                    None
+                }
+            }
+            Outcome::Resignation { resigned } => Some(resigned.opponent()),
+            Outcome::Timeout { flagged } => Some(flagged.opponent()),
+        }
+    }
+}
+
+/// 対局中の出来事を受け取るためのトレイト
+///
+/// ```Game::add_observer```で登録すると、手が打たれるたびに呼び出される。
+/// 必要なメソッドだけ実装すればよい（既定では何もしない）。
+/// playerは打った（パスした）側。
+pub trait GameObserver {
+    /// 駒が置かれたとき
+    fn on_place(&mut self, _player: Player, _coord: Coord) {}
+
+    /// 駒が裏返されたとき（裏返った駒の座標をまとめて渡す）
+    fn on_flip(&mut self, _player: Player, _flipped: &[Coord]) {}
+
+    /// パスしたとき
+    fn on_pass(&mut self, _player: Player) {}
+
+    /// 終局したとき
+    fn on_game_over(&mut self, _outcome: &Outcome) {}
+}
+
+/// 対局の進行を管理する構造体
+///
+/// startは対局を始めたときの盤面、boardは現在の盤面、
+/// historyはこれまでに打たれた手（パスを含む）の記録。
+/// outcomeは終局していればその結果を持つ。
+/// undo_stackは待ったのために、各手を打つ直前の盤面と```history```の長さを持つ。
+/// redo_stackは待ったで取り消した手を、新しいものが後ろになるように持つ。
+/// observersは登録された```GameObserver```の一覧。
+pub struct Game {
+    start: BoardState,
+    board: BoardState,
+    history: Vec<RecordedMove>,
+    outcome: Option<Outcome>,
+    undo_stack: Vec<(BoardState, usize)>,
+    redo_stack: Vec<Move>,
+    observers: Vec<Box<dyn GameObserver>>,
+}
+
+impl Game {
+    /// 盤面から対局を始める
+    ///
+    /// 手番の側に置けるマスがない場合はパスしてから始め、
+    /// どちらも置けない場合は最初から終局した状態になる。
+    pub fn new(board: BoardState) -> Game {
+        let mut game = Game {
+            start: board.clone(),
+            board,
+            history: Vec::new(),
+            outcome: None,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            observers: Vec::new(),
+        };
+        if game.board.must_pass() {
+            let player = game.board.turn();
+            game.record(player, Move::Pass);
+            game.board.pass().unwrap();
+        } else if game.board.is_game_over() {
+            game.finish();
+        }
+        game
+    }
+
+    /// 対局中の出来事を受け取る```GameObserver```を登録する
+    pub fn add_observer(&mut self, observer: Box<dyn GameObserver>) {
+        self.observers.push(observer);
+    }
+
+    /// 対局を始めたときの盤面（最初に自動でパスした場合はその前の盤面）
+    pub fn start(&self) -> &BoardState {
+        &self.start
+    }
+
+    /// 現在の盤面
+    pub fn board(&self) -> &BoardState {
+        &self.board
+    }
+
+    /// これまでに打たれた手（パスを含む）の記録
+    pub fn history(&self) -> &[RecordedMove] {
+        &self.history
+    }
+
+    /// 次に打つ手が何手目か（1から数え、パスも1手と数える）
+    pub fn move_number(&self) -> usize {
+        self.history.len() + 1
+    }
+
+    /// 今の盤面の空きマス（塞がれたマスを除く）の数
+    ///
+    /// 終盤に読み切りへ切り替えるかどうかの判断などに使う。
+    pub fn empties(&self) -> usize {
+        self.board.score().empty
+    }
+
+    /// 直前に置かれた駒の座標と、それによって裏返った駒の座標の一覧（まだ置かれていなければNone）
+    pub fn last_placement(&self) -> Option<(Coord, Vec<Coord>)> {
+        let (before, len) = self.undo_stack.last()?;
+        match self.history[*len].mv {
+            Move::Place(c) => Some((c, before.flips_for(c))),
+            Move::Pass => None,
+        }
+    }
+
+    /// 棋譜を1手1行の文字列で出力する
+    ///
+    /// 各行は「手数. 駒の文字 手」の形式（例: ```1. # 3-4```）。
+    pub fn transcript(&self) -> String {
+        self.history
+            .iter()
+            .map(|r| format!("{}. {} {}\n", r.number, r.player.piece(), r.mv))
+            .collect()
+    }
+
+    /// 棋譜（```transcript```の形式）を読んで、startから手順を適用した対局を作る
+    ///
+    /// 各行の最後の語を手として読み、空行と```[```で始まる行（対局者の名前などの情報）は読み飛ばす。
+    /// 読めない手や打てない手があった場合は、その位置（空行を除いて何番目の手か）をエラーで返す。
+    pub fn from_transcript(start: BoardState, text: &str) -> Result<Game, ReplayError> {
+        let mut moves = Vec::new();
+        for (index, word) in text
+            .lines()
+            .filter(|l| !l.starts_with('['))
+            .filter_map(|l| l.split_whitespace().last())
+            .enumerate()
+        {
+            match word.parse::<Move>() {
+                Ok(mv) => moves.push(mv),
+                Err(error) => return Err(ReplayError { index, error }),
+            }
+        }
+        let mut game = Game::new(start);
+        game.apply_moves(&moves)?;
+        Ok(game)
+    }
+
+    /// 棋譜を、列の英字（大文字）と1始まりの行番号を続けた手を並べた1つの文字列にする
+    ///
+    /// 8×8の対局の棋譜でよく使われる形式（例: ```F5D6C3D3C4```）で、パスは書かない。
+    pub fn compact_transcript(&self) -> String {
+        self.history
+            .iter()
+            .filter_map(|r| match r.mv {
+                Move::Place(c) => Some(c.to_string().to_uppercase()),
+                Move::Pass => None,
+            })
+            .collect()
+    }
+
+    /// ```compact_transcript```の形式の文字列を読んで、startから手順を適用した対局を作る
+    ///
+    /// 大文字と小文字は区別せず、手の間の空白、```,```、```-```は読み飛ばす。
+    /// パスは書かれていなくても自動で行う。
+    /// 読めない手や打てない手があった場合は、その位置（何番目の手か）をエラーで返す。
+    pub fn from_compact_transcript(start: BoardState, text: &str) -> Result<Game, ReplayError> {
+        let separator = |c: char| c.is_whitespace() || c == ',' || c == '-';
+        let mut moves = Vec::new();
+        let mut rest = text.trim_matches(separator);
+        while !rest.is_empty() {
+            // 英字の後に数字が続く部分を1手とする（英字で始まらなければ1文字だけ取って読めない手とする）
+            let letters = rest
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(rest.len());
+            let end = match rest[letters..].find(|c: char| !c.is_ascii_digit()) {
+                Some(0) if letters == 0 => rest.chars().next().map_or(0, char::len_utf8),
+                Some(digits) => letters + digits,
+                None => rest.len(),
+            };
+            let index = moves.len();
+            let c = rest[..end]
+                .parse::<Coord>()
+                .map_err(|error| ReplayError { index, error })?;
+            moves.push(Move::Place(c));
+            rest = rest[end..].trim_start_matches(separator);
+        }
+        let mut game = Game::new(start);
+        game.apply_moves(&moves)?;
+        Ok(game)
+    }
+
+    /// 空白で区切った手の並び（例: ```3-4 3-3 pass```）を読んで、startから手順を適用した対局を作る
+    ///
+    /// 読めない手や打てない手があった場合は、その位置（何番目の手か）をエラーで返す。
+    pub fn from_moves(start: BoardState, text: &str) -> Result<Game, ReplayError> {
+        let moves = text
+            .split_whitespace()
+            .enumerate()
+            .map(|(index, word)| {
+                word.parse::<Move>()
+                    .map_err(|error| ReplayError { index, error })
+            })
+            .collect::<Result<Vec<Move>, ReplayError>>()?;
+        let mut game = Game::new(start);
+        game.apply_moves(&moves)?;
+        Ok(game)
+    }
+
+    /// 始めの局面、現在の盤面、棋譜、結果と、settingsをJSONでpathのファイルに書き出す
+    ///
+    /// settingsには対局者の名前や持ち時間など、```Game```の外で管理している設定を渡す
+    /// （何もなければ```()```）。```load_json```で読み込める。
+    pub fn save_json<T: Serialize>(
+        &self,
+        path: impl AsRef<Path>,
+        settings: &T,
+    ) -> Result<(), GameFileError> {
+        let file = GameFile {
+            start: rows(&self.start),
+            start_turn: self.start.turn(),
+            board: rows(&self.board),
+            turn: self.board.turn(),
+            history: self.history.clone(),
+            outcome: self.outcome,
+            settings,
+        };
+        let text = serde_json::to_string_pretty(&file)?;
+        std::fs::write(path, text + "\n")?;
+        Ok(())
+    }
+
+    /// ```save_json```で書き出したファイルを読んで、対局と一緒に保存した設定を返す
+    ///
+    /// 始めの局面から棋譜の手順を適用し直すので、読み込んだ後も待ったできる。
+    /// 適用した結果が保存された盤面と一致しない場合はエラーを返す。
+    pub fn load_json<T: DeserializeOwned>(
+        path: impl AsRef<Path>,
+    ) -> Result<(Game, T), GameFileError> {
+        let text = std::fs::read_to_string(path)?;
+        let file: GameFile<T> = serde_json::from_str(&text)?;
+        let start = from_rows(&file.start, file.start_turn).map_err(GameFileError::Board)?;
+        let moves: Vec<Move> = file.history.iter().map(|r| r.mv).collect();
+        let mut game = Game::new(start);
+        game.apply_moves(&moves).map_err(GameFileError::Moves)?;
+        let ended = match file.outcome {
+            Some(Outcome::Resignation { resigned }) => game.resign(resigned),
+            Some(Outcome::Timeout { flagged }) => game.time_out(flagged),
+            _ => Ok(()),
+        };
+        if ended.is_err()
+            || game.outcome != file.outcome
+            || rows(&game.board) != file.board
+            || game.board.turn() != file.turn
+        {
+            return Err(GameFileError::Mismatch);
+        }
+        Ok((game, file.settings))
+    }
+
+    /// 終局しているかどうか
+    pub fn is_over(&self) -> bool {
+        self.outcome.is_some()
+    }
+
+    /// 対局の結果（終局していなければNone）
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
+
+    /// 1手進める
+    ///
+    /// 次の手番の側に置けるマスがなければ、自動でパスを記録して手番を戻す。
+    /// 両者とも置けなくなったら終局する。
+    /// 新しく手を打つと、待ったで取り消した手はやり直せなくなる。
+    pub fn apply_move(&mut self, mv: Move) -> Result<(), ReversiError> {
+        self.play(mv)?;
+        self.redo_stack.clear();
+        Ok(())
+    }
+
+    /// 手順をまとめて適用する
+    ///
+    /// 棋譜をそのまま渡せるように、自動で行われたパスに対応する```Move::Pass```は読み飛ばす。
+    /// まだ1手も打っていなければ、始めの局面で自動で行われたパスも棋譜に含まれているものとして照合する。
+    /// 途中に打てない手があった場合は、その位置をエラーで返し、盤面は適用前の状態に戻す
+    /// （ただし```GameObserver```にはそれまでの手が通知される）。
+    pub fn apply_moves(&mut self, moves: &[Move]) -> Result<(), ReplayError> {
+        let saved_redo = self.redo_stack.clone();
+        let mut applied: usize = 0;
+        // 次の手が棋譜上で何番目に記録されるはずか（```Game::new```で記録したパスは待ったできないので、
+        // 待ったできる手がなければ棋譜の最初から照合する）
+        let mut pos = if self.undo_stack.is_empty() {
+            0
+        } else {
+            self.history.len()
+        };
+        for (index, &mv) in moves.iter().enumerate() {
+            if mv == Move::Pass && self.history.get(pos).map(|r| r.mv) == Some(Move::Pass) {
+                pos += 1;
+                continue;
+            }
+            let before = self.history.len();
+            if let Err(error) = self.apply_move(mv) {
+                for _ in 0..applied {
+                    self.undo();
+                }
+                self.redo_stack = saved_redo;
+                return Err(ReplayError { index, error });
+            }
+            applied += 1;
+            pos = before + 1;
+        }
+        Ok(())
+    }
+
+    /// 待ったできる手の数
+    pub fn undo_count(&self) -> usize {
+        self.undo_stack.len()
+    }
+
+    /// やり直せる手の数
+    pub fn redo_count(&self) -> usize {
+        self.redo_stack.len()
+    }
+
+    /// 直前の1手（とそれに伴う自動のパス）を取り消す
+    ///
+    /// 取り消せた場合はtrue、取り消せる手がない場合はfalseを返す。
+    pub fn undo(&mut self) -> bool {
+        let (board, len) = match self.undo_stack.pop() {
+            Some(x) => x,
+            None => return false,
+        };
+        self.redo_stack.push(self.history[len].mv);
+        self.board = board;
+        self.history.truncate(len);
+        self.outcome = None;
+        true
+    }
+
+    /// 待ったで取り消した手を打ち直す
+    ///
+    /// 打ち直せた場合はtrue、打ち直せる手がない場合はfalseを返す。
+    pub fn redo(&mut self) -> bool {
+        match self.redo_stack.pop() {
+            Some(mv) => {
+                self.play(mv).expect("取り消した手は打ち直せるはず");
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 黒と白をそれぞれ```Strategy```に打たせて、終局まで進める
+    ///
+    /// どちらかが打てない手を選んだ場合はそのエラーを返す（盤面はその手の直前の状態になる）。
+    pub fn play_out(
+        &mut self,
+        black: &mut dyn Strategy,
+        white: &mut dyn Strategy,
+    ) -> Result<(), ReversiError> {
+        while !self.is_over() {
+            let player = self.board.turn();
+            let mv = match player {
+                Player::Black => black.choose_move_or_resign(&self.board),
+                Player::White => white.choose_move_or_resign(&self.board),
+            };
+            match mv {
+                Some(mv) => self.apply_move(mv)?,
+                None => self.resign(player)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// playerが投了して終局する
+    ///
+    /// 投了した後に```undo```すると、投了と直前の1手がまとめて取り消される。
+    pub fn resign(&mut self, player: Player) -> Result<(), ReversiError> {
+        if self.is_over() {
+            return Err(ReversiError::GameOver);
+        }
+        self.end(Outcome::Resignation { resigned: player });
+        Ok(())
+    }
+
+    /// playerの持ち時間が切れて終局する
+    ///
+    /// ```resign```と同じく、後で```undo```すると直前の1手とまとめて取り消される。
+    pub fn time_out(&mut self, player: Player) -> Result<(), ReversiError> {
+        if self.is_over() {
+            return Err(ReversiError::GameOver);
+        }
+        self.end(Outcome::Timeout { flagged: player });
+        Ok(())
+    }
+
+    /// 1手進める処理の本体（やり直し用の記録は触らない）
+    fn play(&mut self, mv: Move) -> Result<(), ReversiError> {
+        if self.is_over() {
+            return Err(ReversiError::GameOver);
+        }
+        let c = match mv {
+            Move::Place(c) => c,
+            // 置けるマスがないときは自動でパスしているので、ここでパスできることはない
+            Move::Pass => return Err(ReversiError::IllegalPass),
+        };
+        if !self.board.is_legal(c) {
+            return Err(ReversiError::IllegalMove(c));
+        }
+
+        self.undo_stack
+            .push((self.board.clone(), self.history.len()));
+        let player = self.board.turn();
+        let flipped = self.board.flips_for(c);
+        self.record(player, mv);
+        let can_continue = self.board.put(c.row, c.col);
+        for o in self.observers.iter_mut() {
+            o.on_place(player, c);
+            o.on_flip(player, &flipped);
+        }
+
+        if !can_continue {
+            self.finish();
+        } else if self.board.turn() == player {
+            // 手番が戻ってきた場合は相手がパスしている
+            let passed = player.opponent();
+            self.record(passed, Move::Pass);
+            for o in self.observers.iter_mut() {
+                o.on_pass(passed);
+            }
+        }
+        Ok(())
+    }
+
+    /// playerが打った手として記録する
+    fn record(&mut self, player: Player, mv: Move) {
+        self.history.push(RecordedMove {
+            number: self.history.len() + 1,
+            player,
+            mv,
+        });
+    }
+
+    /// 駒の個数から結果を決めて終局する
+    fn finish(&mut self) {
+        let score = self.board.score();
+        self.end(Outcome::Completed {
+            white: score.white,
+            black: score.black,
+        });
+    }
+
+    /// 結果を記録して終局する
+    fn end(&mut self, outcome: Outcome) {
+        self.outcome = Some(outcome);
+        for o in self.observers.iter_mut() {
+            o.on_game_over(&outcome);
+        }
+    }
+}
+
+/// ```Game::save_json```で書き出す内容
+///
+/// startとboardは盤面の各行を```show_board```の文字で表した文字列の一覧、
+/// start_turnとturnはそれぞれの手番、settingsは呼び出し側が一緒に保存するもの。
+#[derive(Serialize, Deserialize)]
+struct GameFile<T> {
+    start: Vec<String>,
+    start_turn: Player,
+    board: Vec<String>,
+    turn: Player,
+    history: Vec<RecordedMove>,
+    outcome: Option<Outcome>,
+    settings: T,
+}
+
+/// 盤面の各行を文字列にしたもの
+fn rows(bs: &BoardState) -> Vec<String> {
+    bs.show_board()
+        .iter()
+        .map(|row| row.iter().collect())
+        .collect()
+}
+
+/// ```rows```の形式の各行と手番から盤面を作る（中央の4マスが空いていてもよい）
+fn from_rows(rows: &[String], turn: Player) -> Result<BoardState, ReversiError> {
+    let size = rows.len();
+    let mut builder = BoardBuilder::new(size).without_initial_discs().turn(turn);
+    for (row, line) in rows.iter().enumerate() {
+        let len = line.chars().count();
+        if len != size {
+            return Err(ReversiError::RowLength { row, len, size });
+        }
+        for (col, ch) in line.chars().enumerate() {
+            let c = Coord::new(row, col);
+            builder = match ch {
+                _ if ch == BoardState::no_piece() => builder,
+                _ if ch == BoardState::blocked_square() => builder.blocked(c),
+                _ => match Player::from_piece(ch) {
+                    Some(p) => builder.disc(c, p),
+                    None => return Err(ReversiError::UnknownChar { row, col, ch }),
+                },
+            };
+        }
+    }
+    builder.build()
+}
+
+/// ```GameObserver```は表示できないので、登録数だけを表示する
+impl fmt::Debug for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Game")
+            .field("start", &self.start)
+            .field("board", &self.board)
+            .field("history", &self.history)
+            .field("outcome", &self.outcome)
+            .field("undo_stack", &self.undo_stack)
+            .field("redo_stack", &self.redo_stack)
+            .field("observers", &self.observers.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 始めの局面で黒が置けず、自動でパスしてから始まる4×4の盤面
+    fn forced_pass_start() -> BoardState {
+        BoardState::from_position_string("o#2/4/4/4 b 4").unwrap()
+    }
+
+    #[test]
+    fn apply_moves_replays_forced_pass_at_start() {
+        let mut game = Game::new(forced_pass_start());
+        assert_eq!(game.history()[0].mv, Move::Pass);
+        game.apply_move(Move::Place(Coord::new(0, 2))).unwrap();
+
+        let moves: Vec<Move> = game.history().iter().map(|r| r.mv).collect();
+        let mut replayed = Game::new(forced_pass_start());
+        replayed.apply_moves(&moves).unwrap();
+        assert_eq!(replayed.history(), game.history());
+        assert_eq!(replayed.board(), game.board());
+
+        let loaded = Game::from_transcript(forced_pass_start(), &game.transcript()).unwrap();
+        assert_eq!(loaded.history(), game.history());
+    }
+}
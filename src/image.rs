@@ -0,0 +1,386 @@
+//! 盤面の画像（SVGとPNG）の書き出し
+//!
+//! どちらも同じ配置で、周りの余白に列の英字と行番号（PNGでは省略）、緑の盤面に黒と白の駒を描く。
+//! PNGは外部のライブラリを使わずに、圧縮しない形式で書き出す。
+
+use crate::boardstate::{Cell, Coord, Player};
+use crate::game::{Game, Move};
+
+/// 1マスの大きさ（ピクセル）
+const CELL: usize = 40;
+
+/// 盤面の周りの余白（ピクセル）
+const MARGIN: usize = 24;
+
+/// 駒の半径（ピクセル）
+const RADIUS: f64 = 17.0;
+
+const BACKGROUND: [u8; 3] = [0xff, 0xff, 0xff];
+const BOARD: [u8; 3] = [0x2e, 0x7d, 0x32];
+const LINE: [u8; 3] = [0x1b, 0x3d, 0x1c];
+const BLACK: [u8; 3] = [0x10, 0x10, 0x10];
+const WHITE: [u8; 3] = [0xf5, 0xf5, 0xf5];
+const BLOCKED: [u8; 3] = [0x55, 0x55, 0x55];
+const MARK: [u8; 3] = [0xe5, 0x39, 0x35];
+
+/// 数字を描くための3×5のドット絵（各行の下位3ビットが左から右のドット）
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+/// PNGで数字のドット1つを描く大きさ（ピクセル）
+const DOT: usize = 3;
+
+impl Game {
+    /// 今の局面（終局していれば最後の局面）の盤面をSVGの画像にする
+    ///
+    /// numbersがtrueなら各駒の上に何手目に置かれたか（パスは数えない）を表示し、
+    /// falseなら直前に置かれた駒に赤い印をつける。
+    pub fn render_svg(&self, numbers: bool) -> String {
+        let n = self.board().get_size();
+        let side = n * CELL + 2 * MARGIN;
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\" viewBox=\"0 0 {0} {0}\">\n",
+            side
+        );
+        svg.push_str(&format!(
+            "<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+            side,
+            side,
+            hex(BACKGROUND)
+        ));
+        svg.push_str(&format!(
+            "<rect x=\"{0}\" y=\"{0}\" width=\"{1}\" height=\"{1}\" fill=\"{2}\"/>\n",
+            MARGIN,
+            n * CELL,
+            hex(BOARD)
+        ));
+
+        // 塞がれたマス
+        for (c, cell) in self.board().cells() {
+            if cell == Cell::Blocked {
+                svg.push_str(&format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{2}\" height=\"{2}\" fill=\"{3}\"/>\n",
+                    MARGIN + c.col * CELL,
+                    MARGIN + c.row * CELL,
+                    CELL,
+                    hex(BLOCKED)
+                ));
+            }
+        }
+
+        // 罫線
+        for i in 0..=n {
+            let p = MARGIN + i * CELL;
+            let end = MARGIN + n * CELL;
+            svg.push_str(&format!(
+                "<line x1=\"{0}\" y1=\"{1}\" x2=\"{0}\" y2=\"{2}\" stroke=\"{3}\"/>\n",
+                p,
+                MARGIN,
+                end,
+                hex(LINE)
+            ));
+            svg.push_str(&format!(
+                "<line x1=\"{1}\" y1=\"{0}\" x2=\"{2}\" y2=\"{0}\" stroke=\"{3}\"/>\n",
+                p,
+                MARGIN,
+                end,
+                hex(LINE)
+            ));
+        }
+
+        // 列の英字と行番号
+        for i in 0..n {
+            let center = MARGIN + i * CELL + CELL / 2;
+            svg.push_str(&text(center, MARGIN / 2, &Coord::column_name(i), BLACK));
+            svg.push_str(&text(MARGIN / 2, center, &(i + 1).to_string(), BLACK));
+        }
+
+        // 駒と手数
+        let order = self.placement_numbers();
+        for (c, cell) in self.board().cells() {
+            let player = match cell {
+                Cell::Occupied(p) => p,
+                _ => continue,
+            };
+            let (cx, cy) = center(c);
+            let (fill, ink) = colors(player);
+            svg.push_str(&format!(
+                "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\" stroke=\"{}\"/>\n",
+                cx,
+                cy,
+                RADIUS,
+                hex(fill),
+                hex(BLACK)
+            ));
+            if let Some(k) = order[c.row][c.col].filter(|_| numbers) {
+                svg.push_str(&text(cx, cy, &k.to_string(), ink));
+            }
+        }
+        if let Some((c, _)) = self.last_placement().filter(|_| !numbers) {
+            let (cx, cy) = center(c);
+            svg.push_str(&format!(
+                "<circle cx=\"{}\" cy=\"{}\" r=\"4\" fill=\"{}\"/>\n",
+                cx,
+                cy,
+                hex(MARK)
+            ));
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// ```render_svg```と同じ盤面をPNGの画像にする（列の英字と行番号は描かない）
+    pub fn render_png(&self, numbers: bool) -> Vec<u8> {
+        let n = self.board().get_size();
+        let side = n * CELL + 2 * MARGIN;
+        let mut canvas = Canvas::new(side, side, BACKGROUND);
+        canvas.fill_rect(MARGIN, MARGIN, n * CELL, n * CELL, BOARD);
+        for (c, cell) in self.board().cells() {
+            if cell == Cell::Blocked {
+                canvas.fill_rect(
+                    MARGIN + c.col * CELL,
+                    MARGIN + c.row * CELL,
+                    CELL,
+                    CELL,
+                    BLOCKED,
+                );
+            }
+        }
+        for i in 0..=n {
+            let p = MARGIN + i * CELL;
+            canvas.fill_rect(p, MARGIN, 1, n * CELL + 1, LINE);
+            canvas.fill_rect(MARGIN, p, n * CELL + 1, 1, LINE);
+        }
+
+        let order = self.placement_numbers();
+        for (c, cell) in self.board().cells() {
+            let player = match cell {
+                Cell::Occupied(p) => p,
+                _ => continue,
+            };
+            let (cx, cy) = center(c);
+            let (fill, ink) = colors(player);
+            canvas.fill_circle(cx as f64, cy as f64, RADIUS + 1.0, BLACK);
+            canvas.fill_circle(cx as f64, cy as f64, RADIUS, fill);
+            if let Some(k) = order[c.row][c.col].filter(|_| numbers) {
+                canvas.draw_number(cx, cy, k, ink);
+            }
+        }
+        if let Some((c, _)) = self.last_placement().filter(|_| !numbers) {
+            let (cx, cy) = center(c);
+            canvas.fill_circle(cx as f64, cy as f64, 4.0, MARK);
+        }
+        canvas.to_png()
+    }
+
+    /// 各マス目の駒が何手目に置かれたか（パスは数えない。始めの局面からあった駒はNone）
+    fn placement_numbers(&self) -> Vec<Vec<Option<usize>>> {
+        let n = self.board().get_size();
+        let mut order = vec![vec![None; n]; n];
+        let placements = self.history().iter().filter_map(|r| match r.mv {
+            Move::Place(c) => Some(c),
+            Move::Pass => None,
+        });
+        for (k, c) in placements.enumerate() {
+            order[c.row][c.col] = Some(k + 1);
+        }
+        order
+    }
+}
+
+/// マス目の中心の座標（ピクセル）
+fn center(c: Coord) -> (usize, usize) {
+    (
+        MARGIN + c.col * CELL + CELL / 2,
+        MARGIN + c.row * CELL + CELL / 2,
+    )
+}
+
+/// playerの駒の色と、その上に書く文字の色
+fn colors(player: Player) -> ([u8; 3], [u8; 3]) {
+    match player {
+        Player::Black => (BLACK, WHITE),
+        Player::White => (WHITE, BLACK),
+    }
+}
+
+/// ```#rrggbb```の形式の色
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// (x, y)を中心にした文字のSVGの要素
+fn text(x: usize, y: usize, s: &str, color: [u8; 3]) -> String {
+    format!(
+        "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"14\" text-anchor=\"middle\" dominant-baseline=\"central\" fill=\"{}\">{}</text>\n",
+        x,
+        y,
+        hex(color),
+        s
+    )
+}
+
+/// PNGに書き出すための、RGBのピクセルの並び
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Canvas {
+    /// 全体をbackgroundで塗ったものを作成する
+    fn new(width: usize, height: usize, background: [u8; 3]) -> Canvas {
+        Canvas {
+            width,
+            height,
+            pixels: vec![background; width * height],
+        }
+    }
+
+    /// (x, y)のピクセルにcolorを割合alphaで重ねる（画像の外なら何もしない）
+    fn blend(&mut self, x: usize, y: usize, color: [u8; 3], alpha: f64) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let p = &mut self.pixels[y * self.width + x];
+        for i in 0..3 {
+            p[i] = (p[i] as f64 * (1.0 - alpha) + color[i] as f64 * alpha).round() as u8;
+        }
+    }
+
+    /// 左上が(x, y)の長方形を塗る
+    fn fill_rect(&mut self, x: usize, y: usize, w: usize, h: usize, color: [u8; 3]) {
+        for py in y..y + h {
+            for px in x..x + w {
+                self.blend(px, py, color, 1.0);
+            }
+        }
+    }
+
+    /// 中心が(cx, cy)の円を塗る（縁は1ピクセルを4×4に分けて覆う割合で滑らかにする）
+    fn fill_circle(&mut self, cx: f64, cy: f64, r: f64, color: [u8; 3]) {
+        let (x0, x1) = ((cx - r).floor() as usize, (cx + r).ceil() as usize);
+        let (y0, y1) = ((cy - r).floor() as usize, (cy + r).ceil() as usize);
+        for py in y0..=y1 {
+            for px in x0..=x1 {
+                let mut covered = 0;
+                for sy in 0..4 {
+                    for sx in 0..4 {
+                        let dx = px as f64 + (sx as f64 + 0.5) / 4.0 - cx;
+                        let dy = py as f64 + (sy as f64 + 0.5) / 4.0 - cy;
+                        if dx * dx + dy * dy <= r * r {
+                            covered += 1;
+                        }
+                    }
+                }
+                if covered > 0 {
+                    self.blend(px, py, color, covered as f64 / 16.0);
+                }
+            }
+        }
+    }
+
+    /// 中心が(cx, cy)になるように数字を描く
+    fn draw_number(&mut self, cx: usize, cy: usize, number: usize, color: [u8; 3]) {
+        let digits: Vec<usize> = number
+            .to_string()
+            .bytes()
+            .map(|b| (b - b'0') as usize)
+            .collect();
+        // 数字1つの幅は3ドット、数字の間は1ドット空ける
+        let width = (digits.len() * 4 - 1) * DOT;
+        let left = cx - width / 2;
+        let top = cy - 5 * DOT / 2;
+        for (i, &d) in digits.iter().enumerate() {
+            for (row, bits) in DIGITS[d].iter().enumerate() {
+                for col in 0..3 {
+                    if bits & (0b100 >> col) != 0 {
+                        let x = left + (i * 4 + col) * DOT;
+                        self.fill_rect(x, top + row * DOT, DOT, DOT, color);
+                    }
+                }
+            }
+        }
+    }
+
+    /// PNGの形式（圧縮しないdeflateのブロックを使う）にする
+    fn to_png(&self) -> Vec<u8> {
+        // 各行の先頭にフィルタの種類（0: なし）をつける
+        let mut raw = Vec::with_capacity((self.width * 3 + 1) * self.height);
+        for row in self.pixels.chunks(self.width) {
+            raw.push(0);
+            for p in row {
+                raw.extend_from_slice(p);
+            }
+        }
+
+        let mut zlib = vec![0x78, 0x01];
+        let blocks: Vec<&[u8]> = raw.chunks(0xffff).collect();
+        for (i, block) in blocks.iter().enumerate() {
+            zlib.push(u8::from(i + 1 == blocks.len()));
+            let len = block.len() as u16;
+            zlib.extend_from_slice(&len.to_le_bytes());
+            zlib.extend_from_slice(&(!len).to_le_bytes());
+            zlib.extend_from_slice(block);
+        }
+        zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+        let mut header = Vec::new();
+        header.extend_from_slice(&(self.width as u32).to_be_bytes());
+        header.extend_from_slice(&(self.height as u32).to_be_bytes());
+        // ビット深度8、RGB、圧縮・フィルタ・インターレースの方式は既定
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+        let mut png = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+        chunk(&mut png, b"IHDR", &header);
+        chunk(&mut png, b"IDAT", &zlib);
+        chunk(&mut png, b"IEND", &[]);
+        png
+    }
+}
+
+/// PNGのチャンク（長さ、種類、データ、CRC）を書き加える
+fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// CRC-32（PNGのチャンクの検査用）
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &b in bytes {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Adler-32（zlibの検査用）
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &x in bytes {
+        a = (a + x as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
@@ -0,0 +1,214 @@
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Mutex, Once, OnceLock};
+use std::time::{Duration, Instant};
+
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+
+/// Ctrl-Cが押されたかどうか（```catch_interrupts```で受け取るようにしている間だけ立てる）
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Ctrl-Cですぐに終了せずに，```INTERRUPTED```を立てるかどうか
+static CATCHING: AtomicBool = AtomicBool::new(false);
+
+/// 入力を待つ間に```INTERRUPTED```を確かめる間隔
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Ctrl-Cが押されたときの処理（受け取るようにしていなければ終了する）
+fn on_interrupt() {
+    if CATCHING.load(Ordering::SeqCst) {
+        INTERRUPTED.store(true, Ordering::SeqCst);
+    } else {
+        std::process::exit(130);
+    }
+}
+
+/// Ctrl-Cですぐに終了せずに，```take_interrupt```で受け取るようにする（onがfalseなら元に戻す）
+///
+/// 対局中に使う．入力を待っている間に押されると，読んだ行の代わりに空の行（改行だけ）を返す．
+pub fn catch_interrupts(on: bool) {
+    install_handler();
+    CATCHING.store(on, Ordering::SeqCst);
+}
+
+/// Ctrl-Cのシグナルを受け取るようにする（何度呼んでも1回だけ行う）
+fn install_handler() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        ctrlc::set_handler(on_interrupt).ok();
+    });
+}
+
+/// 前に呼んでからCtrl-Cが押されたかどうか
+pub fn take_interrupt() -> bool {
+    INTERRUPTED.swap(false, Ordering::SeqCst)
+}
+
+/// 標準入力を読むスレッドとのやりとり
+///
+/// requestsに送ると1行読んでlinesに返す．pendingは頼んだ1行をまだ受け取っていないかどうか．
+struct Reader {
+    requests: Sender<()>,
+    lines: Receiver<String>,
+    pending: bool,
+}
+
+/// 標準入力を読むスレッド（最初に呼んだときに作る）
+///
+/// 時間制限つきで待てるように，標準入力は必ずこのスレッドを通して読む．
+/// 頼まれたときだけ読むので，読んでいない間に表示したものが入力の編集と混ざることはない．
+/// 端末から読む場合はカーソルでの編集や上下キーでの入力の呼び出しができる．
+fn reader() -> &'static Mutex<Reader> {
+    static READER: OnceLock<Mutex<Reader>> = OnceLock::new();
+    READER.get_or_init(|| {
+        install_handler();
+        let (request_tx, request_rx) = mpsc::channel();
+        let (line_tx, line_rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut editor = if std::io::stdin().is_terminal() {
+                DefaultEditor::new().ok()
+            } else {
+                None
+            };
+            for () in request_rx {
+                if line_tx.send(read_one(editor.as_mut())).is_err() {
+                    break;
+                }
+            }
+        });
+        Mutex::new(Reader {
+            requests: request_tx,
+            lines: line_rx,
+            pending: false,
+        })
+    })
+}
+
+/// 標準入力から1行読む（editorがあればそれを使う．入力が終わっていれば空文字列）
+///
+/// Ctrl-Cが押されたら，端末から読まない場合と同じように扱う．
+fn read_one(editor: Option<&mut DefaultEditor>) -> String {
+    match editor {
+        Some(ed) => match ed.readline("") {
+            Ok(line) => {
+                if !line.trim().is_empty() {
+                    ed.add_history_entry(line.as_str()).ok();
+                }
+                line + "\n"
+            }
+            Err(ReadlineError::Interrupted) => {
+                on_interrupt();
+                "\n".to_string()
+            }
+            Err(_) => String::new(),
+        },
+        None => {
+            let mut line = String::new();
+            std::io::stdin().read_line(&mut line).ok();
+            line
+        }
+    }
+}
+
+/// 日本語入力のまま打たれた行を読めるようにする
+///
+/// 全角の英数字・記号・空白を半角にし，```、```と```。```も```,```と```.```にしてから，
+/// 前後の空白と句読点・括弧を取り除く（```?```はコマンドなので残す）．入力が終わっている（空文字列）かどうかは変えない．
+pub fn normalize(line: &str) -> String {
+    if line.is_empty() {
+        return String::new();
+    }
+    let half: String = line
+        .chars()
+        .map(|c| match c {
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            '\u{3000}' => ' ',
+            '、' => ',',
+            '。' => '.',
+            _ => c,
+        })
+        .collect();
+    let stray = |c: char| c.is_whitespace() || ".,;:!「」『』()[]\"'".contains(c);
+    half.trim_matches(stray).to_string() + "\n"
+}
+
+/// 1行読む（入力が終わっていれば空文字列）
+///
+/// 読んだ行は```normalize```で整える．
+/// 待っている間にCtrl-Cが押されたら空の行（改行だけ）を返し，読みかけの行は次に読むときに受け取る．
+pub fn read_line() -> String {
+    let mut r = reader().lock().unwrap();
+    if !r.pending {
+        r.requests.send(()).ok();
+        r.pending = true;
+    }
+    loop {
+        match r.lines.recv_timeout(POLL_INTERVAL) {
+            Ok(line) => {
+                r.pending = false;
+                return normalize(&line);
+            }
+            Err(RecvTimeoutError::Timeout) if INTERRUPTED.load(Ordering::SeqCst) => {
+                return "\n".to_string();
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                r.pending = false;
+                return String::new();
+            }
+        }
+    }
+}
+
+/// deadlineまでに1行読む（間に合わなければNone）
+///
+/// 間に合わなかった行は，次に読むときに受け取る．Ctrl-Cが押された場合は```read_line```と同じ．
+pub fn read_line_until(deadline: Instant) -> Option<String> {
+    let mut r = reader().lock().unwrap();
+    if !r.pending {
+        r.requests.send(()).ok();
+        r.pending = true;
+    }
+    loop {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        match r.lines.recv_timeout(timeout.min(POLL_INTERVAL)) {
+            Ok(line) => {
+                r.pending = false;
+                return Some(normalize(&line));
+            }
+            Err(RecvTimeoutError::Timeout) if INTERRUPTED.load(Ordering::SeqCst) => {
+                return Some("\n".to_string());
+            }
+            Err(RecvTimeoutError::Timeout) if timeout <= POLL_INTERVAL => return None,
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                r.pending = false;
+                return Some(String::new());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::{EditCommand, GameCommand};
+
+    #[test]
+    fn question_mark_is_help() {
+        assert_eq!(GameCommand::parse(&normalize("?")), Some(GameCommand::Help));
+        assert_eq!(
+            GameCommand::parse(&normalize("？\n")),
+            Some(GameCommand::Help)
+        );
+        assert_eq!(EditCommand::parse(&normalize("?")), Some(EditCommand::Help));
+    }
+
+    #[test]
+    fn strips_punctuation_around_squares() {
+        assert_eq!(normalize("「ｄ３」。\n"), "d3\n");
+        assert_eq!(normalize(""), "");
+    }
+}
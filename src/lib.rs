@@ -0,0 +1,14 @@
+//! 簡易的なリバーシ（いわゆるオセロ）のライブラリ部分
+
+pub mod boardstate;
+pub mod clock;
+pub mod csv;
+pub mod display;
+pub mod engine;
+pub mod error;
+pub mod game;
+pub mod image;
+pub mod sgf;
+pub mod tournament;
+pub mod tuning;
+pub mod wthor;
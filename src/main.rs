@@ -1,94 +1,1526 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process;
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use rand::seq::SliceRandom;
-use rand::thread_rng;
+mod archive;
+mod cli;
+mod command;
+mod config;
+mod editor;
+mod input;
+mod messages;
+mod saved;
+#[cfg(feature = "tui")]
+mod tui;
+use cli::{cli, Color, Command, HistoryAction, Layout, SearchMethod, Theme, TimeoutAction};
+use command::{GameCommand, DEFAULT_SAVE_FILE};
+use config::Config;
+use messages::msg;
+use saved::{Mode, SavedGame};
 
-pub mod boardstate;
-use boardstate::BoardState;
+use crossterm::cursor::MoveUp;
+use crossterm::execute;
+use crossterm::terminal::{Clear, ClearType};
+use simple_reversi::boardstate::{BoardState, Cell, Coord, Player};
+use simple_reversi::clock::Clock;
+use simple_reversi::csv::{self, GameRow};
+use simple_reversi::display::{supports_color, terminal_size, BoardStyle, CellLayout, ColorTheme};
+use simple_reversi::engine::book::{Book, BookStrategy};
+use simple_reversi::engine::eval::EvalWeights;
+use simple_reversi::engine::handicap::HandicapStrategy;
+use simple_reversi::engine::mcts::Mcts;
+use simple_reversi::engine::probability::WinProbability;
+use simple_reversi::engine::review::{review_game, MoveReview};
+use simple_reversi::engine::strategy::{GreedyStrategy, RandomStrategy, Strategy};
+use simple_reversi::engine::{AdaptiveLevel, Engine, Level, SearchInfo, SearchResult};
+use simple_reversi::error::WthorError;
+use simple_reversi::game::{Game, GameObserver, Move, Outcome, RecordedMove};
+use simple_reversi::sgf::{self, Annotation, SgfInfo};
+use simple_reversi::tournament::Tournament;
+use simple_reversi::tuning::Tuner;
+use simple_reversi::wthor::{self, Wthor, WthorGame};
 
 /// 整数の入力が不正である旨のメッセージ
 fn err_not_int() {
-    println!("半角数字で整数を入力してください．");
+    println!("{}", msg().not_int());
 }
 
 /// 入力が不適切な旨のメッセージ
 fn err_input() {
-    println!("入力が不適切です．");
+    println!("{}", msg().invalid_input());
 }
 
 /// 入力が範囲外の旨のメッセージ
 fn err_not_range() {
-    println!("入力が範囲外です．");
+    println!("{}", msg().out_of_range());
 }
 
-/// 盤面を表示させる
-fn preview_board(bs: &BoardState) {
-    let v = bs.show_board();
+/// 盤面の見た目（端末が対応していれば```--theme```の配色で色付き，```--no-color```なら常に色なし）
+///
+/// ```--unicode```なら駒を●と○で表示し，```--pieces```で指定した文字があればそれを使う．```--wide```なら幅が曖昧な文字を全角として列を揃える．
+fn board_style() -> BoardStyle {
+    let style = if cli().unicode {
+        BoardStyle::unicode()
+    } else {
+        BoardStyle::default()
+    };
+    let style = match cli().pieces {
+        Some(p) => style.with_pieces(p.black, p.white, p.empty),
+        None => style,
+    };
+    let theme = match cli().theme {
+        None | Some(Theme::Classic) => ColorTheme::Classic,
+        Some(Theme::HighContrast) => ColorTheme::HighContrast,
+        Some(Theme::Mono) => ColorTheme::Monochrome,
+    };
+    style
+        .with_color(!cli().no_color && supports_color())
+        .with_theme(theme)
+        .with_wide(cli().wide)
+        .with_letters(true)
+}
+
+/// 置けるマス目につける印
+fn hint_mark() -> char {
+    if cli().unicode {
+        '◇'
+    } else {
+        '+'
+    }
+}
+
+/// メッセージの中で使う駒の文字（盤面の見た目に合わせる）
+fn piece(player: Player) -> char {
+    match player {
+        Player::Black => board_style().black,
+        Player::White => board_style().white,
+    }
+}
+
+/// 端末の大きさに合わせたマス目の並べ方（```--layout```で指定した場合はそれに従う）
+///
+/// 罫線で囲んでも下にメッセージを表示する余裕があれば```Large```，
+/// 1文字分ずつ空けて幅が収まれば```Normal```，それ以外は```Compact```にする．
+fn fit_layout(bs: &BoardState, style: BoardStyle) -> BoardStyle {
+    let layout = match cli().layout {
+        Layout::Compact => CellLayout::Compact,
+        Layout::Normal => CellLayout::Normal,
+        Layout::Large => CellLayout::Large,
+        Layout::Auto => {
+            let (cols, rows) = match terminal_size() {
+                Some(size) => size,
+                None => return style,
+            };
+            let (width, height) = bs.styled(style.with_layout(CellLayout::Large)).dimensions();
+            if width <= cols && height + 8 <= rows {
+                CellLayout::Large
+            } else if bs.styled(style).dimensions().0 <= cols {
+                CellLayout::Normal
+            } else {
+                CellLayout::Compact
+            }
+        }
+    };
+    style.with_layout(layout)
+}
+
+/// 対局中の盤面を表示させる
+///
+/// with_helpがtrueなら置けるマス目に印をつける．直前に置かれた駒の左に>を，
+/// ```--highlight-flips```ならそれで裏返った駒の左に*をつける（```--no-highlight```ならつけない）．
+/// 幅が端末に収まらない場合は，centerの列（Noneなら直前に置かれた駒の列）の周りの収まる列だけを表示する．
+fn preview_board(game: &Game, with_help: bool, center: Option<Coord>) {
+    let bs = game.board();
+    let style = if with_help {
+        board_style().with_hint(hint_mark())
+    } else {
+        board_style()
+    };
+    let style = fit_layout(bs, style);
+    let mut highlights: Vec<(Coord, char)> = Vec::new();
+    if let Some((c, flipped)) = game.last_placement().filter(|_| !cli().no_highlight) {
+        if cli().highlight_flips {
+            highlights.extend(flipped.iter().map(|&f| (f, '*')));
+        }
+        highlights.push((c, '>'));
+    }
     let n = bs.get_size();
-    print!("  ");
-    for i in 1..=n {
-        print!("{:2}", i);
+    let mut board = bs.styled(style).with_highlights(&highlights);
+    if let Some((cols, _)) = terminal_size().filter(|&(cols, _)| board.dimensions().0 > cols) {
+        // 行番号の幅と1列あたりの幅から，収まる列の数を求める
+        let base = bs.styled(style).with_window(0..n, 0..0).dimensions().0;
+        let per_col = (board.dimensions().0 - base) / n;
+        let visible = (cols.saturating_sub(base) / per_col).clamp(1, n);
+        let center = center
+            .or_else(|| game.last_placement().map(|(c, _)| c))
+            .map_or(n / 2, |c| c.col);
+        let first = center.saturating_sub(visible / 2).min(n - visible);
+        board = board.with_window(0..n, first..first + visible);
+        println!(
+            "{}",
+            msg().board_cropped(
+                &Coord::column_name(first),
+                &Coord::column_name(first + visible - 1)
+            )
+        );
+    }
+    print!("{}", board);
+}
+
+/// 直前の手で裏返った駒を，途中の様子を挟みながら少しずつ裏返して表示する
+///
+/// 同じ場所に表示し直すので，端末に出力していない場合や```--no-animation```なら何もしない．
+/// 最後は表示した盤面を消して，次に表示する盤面に任せる．
+fn animate_flips(game: &Game) {
+    if cli().no_animation || !io::stdout().is_terminal() {
+        return;
+    }
+    let (placed, flipped) = match game.last_placement() {
+        Some(p) => p,
+        None => return,
+    };
+    let player = match game.board().get(placed) {
+        Some(p) => p,
+        None => return,
+    };
+    let halfway = if cli().unicode { '◐' } else { '%' };
+    for frame in [piece(player.opponent()), halfway] {
+        let marks: Vec<(Coord, char)> = flipped.iter().map(|&c| (c, frame)).collect();
+        let board = game
+            .board()
+            .styled(board_style())
+            .with_marks(&marks)
+            .to_string();
+        print!("{}", board);
+        io::stdout().flush().ok();
+        sleep(Duration::from_millis(150));
+        let lines = board.lines().count() as u16;
+        execute!(
+            io::stdout(),
+            MoveUp(lines),
+            Clear(ClearType::FromCursorDown)
+        )
+        .ok();
+    }
+}
+
+/// CPUが打って自分（player）の番になったことを知らせる
+///
+/// ```--bell```なら端末のベルを鳴らし，```--notify```ならデスクトップに通知する
+/// （通知するコマンドがなければ何もしない）．
+fn notify_turn(player: Player) {
+    if cli().bell {
+        print!("\x07");
+        io::stdout().flush().ok();
+    }
+    if cli().notify {
+        let text = msg().your_turn(piece(player));
+        let command = if cfg!(target_os = "macos") {
+            process::Command::new("osascript")
+                .arg("-e")
+                .arg(format!("display notification {:?}", text))
+                .stdout(process::Stdio::null())
+                .stderr(process::Stdio::null())
+                .spawn()
+        } else {
+            process::Command::new("notify-send")
+                .arg("simple-reversi")
+                .arg(text)
+                .stdout(process::Stdio::null())
+                .stderr(process::Stdio::null())
+                .spawn()
+        };
+        command.ok();
     }
-    println!("");
-    for i in 0..n {
-        print!("{:2}", i + 1);
-        for j in 0..n {
-            print!(" {}", v[i][j]);
+}
+
+/// 盤面を文章で表示する（```--describe```か```describe```コマンドで切り替える）
+///
+/// 直前に置かれた駒と裏返った駒，手番の側が置けるマス目を順に表示する．
+fn describe_board(game: &Game) {
+    if let Some((c, flipped)) = game.last_placement() {
+        if let Some(player) = game.board().get(c) {
+            println!("{}", msg().describe_last_move(piece(player), c, &flipped));
         }
-        println!("");
+    }
+    let moves: Vec<Coord> = game.board().legal_moves().collect();
+    if !game.is_over() && !moves.is_empty() {
+        println!("{}", msg().describe_legal_moves(&moves));
     }
 }
 
-/// 盤面を表示し，置けるマス目に+印をつける
-fn preview_board_with_help(bs: &BoardState) {
-    let v = bs.show_board();
-    let cnt = bs.cnt_reversable();
-    let n = bs.get_size();
-    print!("  ");
-    for i in 1..=n {
-        print!("{:2}", i);
+/// 局面をJSONで1行に出力する（```--json```）
+///
+/// 盤面は行ごとのマス目の配列（```"black"```，```"white"```，```"empty"```，```"blocked"```），
+/// 手は棋譜と同じ形式（```"3-4"```や```"pass"```）で表す．終局していれば手番はnull．
+fn print_json(game: &Game) {
+    let bs = game.board();
+    let name = |player| match player {
+        Player::Black => "black",
+        Player::White => "white",
+    };
+    let board: Vec<Vec<&str>> = bs
+        .grid()
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|&cell| match cell {
+                    Cell::Occupied(p) => name(p),
+                    Cell::Empty => "empty",
+                    Cell::Blocked => "blocked",
+                })
+                .collect()
+        })
+        .collect();
+    let over = game.is_over();
+    let legal_moves: Vec<String> = if over {
+        Vec::new()
+    } else {
+        bs.legal_moves()
+            .map(|c| Move::Place(c).to_string())
+            .collect()
+    };
+    let score = bs.score();
+    let value = serde_json::json!({
+        "move_number": game.history().len(),
+        "board": board,
+        "turn": Some(name(bs.turn())).filter(|_| !over),
+        "legal_moves": legal_moves,
+        "counts": { "black": score.black, "white": score.white },
+        "last_move": game.history().last().map(|r| r.mv.to_string()),
+        "game_over": over,
+    });
+    println!("{}", value);
+}
+
+/// 対局者の名前（黒，白の順．名前がなければNone）
+#[derive(Clone, Debug, Default)]
+struct Names([Option<String>; 2]);
+
+impl Names {
+    /// playerの名前
+    fn get(&self, player: Player) -> Option<&str> {
+        match player {
+            Player::Black => self.0[0].as_deref(),
+            Player::White => self.0[1].as_deref(),
+        }
     }
-    println!("");
-    for i in 0..n {
-        print!("{:2}", i + 1);
-        for j in 0..n {
-            print!(" {}", if cnt[i][j] > 0 { '+' } else { v[i][j] });
+
+    /// メッセージの中で対局者を表す文字列（名前があれば名前と駒の文字，なければ駒の文字だけ）
+    fn label(&self, player: Player) -> String {
+        match self.get(player) {
+            Some(name) => msg().named(name, piece(player)),
+            None => piece(player).to_string(),
         }
-        println!("");
     }
 }
 
 /// どちらのターンかを表示する
-fn preview_turn(bs: &BoardState) {
-    println!("{}のターン．", bs.which_turn());
+fn preview_turn(bs: &BoardState, names: &Names) {
+    println!("{}", msg().turn(&names.label(bs.turn())));
+}
+
+/// 先読みして，おすすめの手とその評価値を表示する
+fn show_hint(bs: &BoardState) {
+    let result = match Engine::new(1).search_within(bs, Duration::from_millis(500)) {
+        Some(r) => r,
+        None => return,
+    };
+    println!(
+        "{}",
+        msg().hint(&Move::Place(result.best_move), &describe_score(&result))
+    );
+}
+
+/// 探索の結果の評価値（と，その手を打った側が勝つ確率の見積もり）の説明
+fn describe_score(result: &SearchResult) -> String {
+    let description = match result.final_disc_difference() {
+        Some(d) => msg().score_exact(d),
+        None => msg().score_eval(result.depth, result.score),
+    };
+    msg().with_win_chance(&description, win_probability().of(result) * 100.0)
+}
+
+/// 評価値から勝つ確率を見積もるモデル（```--win-scale 数```で尺度を指定，既定は自己対局で求めた値）
+fn win_probability() -> WinProbability {
+    match cli().win_scale {
+        Some(x) if x > 0.0 => WinProbability::new(x),
+        _ => WinProbability::default(),
+    }
+}
+
+/// 置ける全てのマス目を先読みして，評価値の高い順に表示する
+///
+/// with_boardがtrueなら，盤面の置けるマス目に順位（10位以下は*）を重ねて表示する．
+fn show_analysis(bs: &BoardState, with_board: bool) {
+    let results = Engine::new(cli().analysis_depth)
+        .threads(thread_count())
+        .analyze(bs);
+    for (i, r) in results.iter().enumerate() {
+        println!(
+            "{}",
+            msg().analysis_line(i + 1, &Move::Place(r.best_move), &describe_score(r))
+        );
+    }
+    if with_board {
+        let marks: Vec<(Coord, char)> = results
+            .iter()
+            .enumerate()
+            .map(|(i, r)| {
+                (
+                    r.best_move,
+                    std::char::from_digit(i as u32 + 1, 10).unwrap_or('*'),
+                )
+            })
+            .collect();
+        print!("{}", bs.styled(board_style()).with_marks(&marks));
+    }
+}
+
+/// 人間（player）の各手をCPUの最善手と比べて表示し，その結果を返す
+///
+/// ```--blunder-threshold```で指定した値（既定は50）以上評価値が下がった手に!をつける．
+/// 読む深さは```show_analysis```と同じ．
+fn show_review(game: &Game, player: Player) -> Vec<MoveReview> {
+    let threshold = cli().blunder_threshold;
+    let engine = Engine::new(cli().analysis_depth).threads(thread_count());
+    println!("{}", msg().review_header(threshold));
+    let mut blunders = 0;
+    let reviews = review_game(&engine, game, player);
+    for r in &reviews {
+        let mark = if r.is_blunder(threshold) {
+            blunders += 1;
+            "!"
+        } else {
+            " "
+        };
+        println!(
+            "{}",
+            msg().review_line(
+                mark,
+                r.number,
+                &Move::Place(r.played.best_move),
+                &describe_score(&r.played),
+                &Move::Place(r.best.best_move),
+                &describe_score(&r.best)
+            )
+        );
+    }
+    println!("{}", msg().blunder_count(blunders));
+    reviews
+}
+
+/// 振り返りの結果を，各手の評価と記号をつけた棋譜にしてpathのファイルに書き込む（```--annotate```）
+///
+/// pathが```.sgf```で終わればSGFの```C```と```TE```・```BM```で，それ以外は1手1行の文章で書く．
+/// 記号は```MoveReview::symbol```で，```--blunder-threshold```の値を使う．
+fn save_annotated(game: &Game, reviews: &[MoveReview], names: &Names, path: &str) {
+    let threshold = cli().blunder_threshold;
+    let annotations: HashMap<usize, Annotation> = reviews
+        .iter()
+        .map(|r| {
+            let comment = msg().annotation(
+                &describe_score(&r.played),
+                &Move::Place(r.best.best_move),
+                &describe_score(&r.best),
+            );
+            let symbol = r.symbol(threshold);
+            (r.number, Annotation { symbol, comment })
+        })
+        .collect();
+    let text = if saved::is_sgf(path) {
+        let info = SgfInfo {
+            black: names.get(Player::Black).map(str::to_string),
+            white: names.get(Player::White).map(str::to_string),
+        };
+        sgf::to_annotated_sgf(game, &info, &annotations)
+    } else {
+        let mut text = result_text(game, names) + "\n";
+        for r in game.history() {
+            let line = match annotations.get(&r.number) {
+                Some(a) => format!("{}{:2} {}", r.mv, a.symbol, a.comment),
+                None => r.mv.to_string(),
+            };
+            text.push_str(&format!("{:3}. {} {}\n", r.number, piece(r.player), line));
+        }
+        text
+    };
+    match std::fs::write(path, text) {
+        Ok(()) => println!("{}", msg().saved(path)),
+        Err(e) => println!("{}", msg().save_failed(path, &e)),
+    }
+}
+
+/// パスしたことを表示するための```GameObserver```
+struct PassNotifier;
+
+impl GameObserver for PassNotifier {
+    fn on_pass(&mut self, player: Player) {
+        println!("{}", msg().pass(piece(player), piece(player.opponent())));
+    }
+}
+
+/// 考えている途中経過や，自分が勝つ確率の見積もりを表示しながら手を選ぶCPU
+///
+/// ```--stats```か```--win-rate```をつけると，先読みする強さのCPUの代わりに使う．
+/// statsがtrueなら途中経過（読んだ深さ，局面の数，1秒あたりの局面の数，読み筋）を，
+/// win_rateがSomeなら手を選んだ後に勝つ確率を表示する．
+struct VerboseEngine {
+    engine: Engine,
+    stats: bool,
+    win_rate: Option<WinProbability>,
+}
+
+impl VerboseEngine {
+    /// 読んで，指定された内容を表示する
+    fn think(&self, bs: &BoardState) -> Option<SearchResult> {
+        let stats = self.stats;
+        let result = self.engine.think_with(bs, |info| {
+            if stats {
+                VerboseEngine::print_info(info);
+            }
+        });
+        if let (Some(r), Some(w)) = (&result, &self.win_rate) {
+            println!("{}", msg().cpu_win_estimate(w.of(r) * 100.0));
+        }
+        result
+    }
+
+    /// 途中経過を1行表示する
+    fn print_info(info: &SearchInfo) {
+        let pv: Vec<String> = info
+            .pv
+            .iter()
+            .map(|&c| Move::Place(c).to_string())
+            .collect();
+        println!(
+            "{}",
+            msg().search_info(
+                info.depth,
+                info.score,
+                info.nodes,
+                info.nodes_per_second(),
+                &pv.join(" ")
+            )
+        );
+    }
+}
+
+impl Strategy for VerboseEngine {
+    fn choose_move(&mut self, bs: &BoardState) -> Move {
+        self.think(bs)
+            .map_or(Move::Pass, |r| Move::Place(r.best_move))
+    }
+
+    fn choose_move_or_resign(&mut self, bs: &BoardState) -> Option<Move> {
+        match self.think(bs) {
+            Some(r) if self.engine.resigns_after(&r) => None,
+            Some(r) => Some(Move::Place(r.best_move)),
+            None => Some(Move::Pass),
+        }
+    }
+}
+
+/// cに置いたときに裏返る駒を盤面に表示して，その手を打つか確かめる
+///
+/// 置く場所に@を，裏返る駒に*をつける．置けないマス目なら確かめずにtrueを返す．
+fn confirm_move(bs: &BoardState, c: Coord) -> bool {
+    let flipped = bs.flips_for(c);
+    if flipped.is_empty() {
+        return true;
+    }
+    let mut marks: Vec<(Coord, char)> = flipped.iter().map(|&f| (f, '*')).collect();
+    marks.push((c, '@'));
+    print!("{}", bs.styled(board_style()).with_marks(&marks));
+    println!("{}", msg().confirm_move(flipped.len()));
+    let y_or_no = input::read_line();
+    y_or_no.trim() == "y"
+}
+
+/// 両者の残りの持ち時間を表示する
+fn show_clock(clock: &Clock) {
+    let format = |player: Player| {
+        let secs = clock.remaining(player).as_secs();
+        format!("{}:{:02}", secs / 60, secs % 60)
+    };
+    println!(
+        "{}",
+        msg().clock(
+            piece(Player::Black),
+            &format(Player::Black),
+            piece(Player::White),
+            &format(Player::White)
+        )
+    );
+}
+
+/// 考えていた側の時計を止め，時間が切れていればその側の負けにする（負けにしたらtrue）
+///
+/// 手を打つまでにかかった時間も数えるため，手を受け取ったらそれを打つ前に呼ぶ．
+fn stop_clock(clock: &mut Option<Clock>, game: &mut Game) -> bool {
+    let Some(clock) = clock.as_mut() else {
+        return false;
+    };
+    match clock.stop().filter(|&p| clock.is_out_of_time(p)) {
+        Some(player) => {
+            game.time_out(player).unwrap();
+            true
+        }
+        None => false,
+    }
+}
+
+/// これまでの棋譜を表示する
+fn show_history(game: &Game) {
+    if game.history().is_empty() {
+        println!("{}", msg().history_empty());
+    } else {
+        print!("{}", game.transcript());
+    }
+}
+
+/// 今の駒の個数を表示する
+fn show_score(bs: &BoardState) {
+    let score = bs.score();
+    println!(
+        "{}",
+        msg().score(
+            piece(Player::Black),
+            score.black,
+            piece(Player::White),
+            score.white
+        )
+    );
+}
+
+/// それぞれの置けるマス目の数を表示する（```--show-mobility```）
+fn show_mobility(bs: &BoardState, names: &Names) {
+    println!("{}", mobility_text(bs, names));
+}
+
+/// それぞれの置けるマス目の数の説明
+fn mobility_text(bs: &BoardState, names: &Names) -> String {
+    msg().mobility(
+        &names.label(Player::Black),
+        bs.mobility(Player::Black),
+        &names.label(Player::White),
+        bs.mobility(Player::White),
+    )
+}
+
+/// 棋譜をpathのファイルに保存する
+///
+/// 対局者の名前などの設定は，棋譜の前に```[Black "名前"]```のような行で書いておく．
+/// pathが```.json```で終われば，盤面と設定も含めてJSONで保存する．
+fn save_game(game: &Game, path: &str, saved: &SavedGame) {
+    match saved.save(game, path) {
+        Ok(()) => println!("{}", msg().saved(path)),
+        Err(e) => println!("{}", msg().save_failed(path, &e)),
+    }
+}
+
+/// 待ったする（humanがSomeなら，その側の直前の手までCPUの手もまとめて取り消す）
+///
+/// 取り消せた場合はtrue，取り消せる手がない場合はfalseを返す．
+fn take_back(game: &mut Game, human: Option<Player>) -> bool {
+    let player = match human {
+        Some(p) => p,
+        None => return game.undo(),
+    };
+    let placed = |r: &RecordedMove| r.player == player && r.mv != Move::Pass;
+    if !game.history().iter().any(placed) {
+        return false;
+    }
+    while game.undo() {
+        if game.board().turn() == player {
+            break;
+        }
+    }
+    true
 }
 
 /// 結果を表示する
-fn show_result(bs: &BoardState) {
-    let ((c1, s1), (c2, s2)) = bs.count_pieces();
-    if s1 > s2 {
-        println!("{0}が{1}個，{2}が{3}個で{0}の勝ち！", c1, s1, c2, s2);
-    } else if s1 < s2 {
-        println!("{0}が{1}個，{2}が{3}個で{2}の勝ち！", c1, s1, c2, s2);
+fn show_result(game: &Game, names: &Names) {
+    println!("{}", result_text(game, names));
+}
+
+/// 対局中にCtrl-Cが押されたときに，棋譜を保存して終了するか，保存せずに終了するか，続けるかを聞く
+///
+/// 保存する場合は```save```と同じように既定のファイルに保存する．聞いている間にもう一度押されたら終了する．
+fn handle_interrupt(game: &Game, saved: &SavedGame) {
+    println!();
+    println!("{}", msg().ask_interrupt(DEFAULT_SAVE_FILE));
+    let line = input::read_line();
+    if input::take_interrupt() {
+        process::exit(130);
+    }
+    match line.trim() {
+        "s" => {
+            save_game(game, DEFAULT_SAVE_FILE, saved);
+            remove_autosave();
+            process::exit(130);
+        }
+        "q" => {
+            remove_autosave();
+            process::exit(130);
+        }
+        _ => {}
+    }
+}
+
+/// 自動で保存するファイル（設定ファイルで```autosave = false```にしていればNone）
+fn autosave_path() -> Option<PathBuf> {
+    if !cli().autosave {
+        return None;
+    }
+    Some(match &cli().autosave_path {
+        Some(path) => PathBuf::from(path),
+        None => std::env::temp_dir().join("simple-reversi-autosave.txt"),
+    })
+}
+
+/// 対局中の棋譜を```save```と同じ形式で自動で保存する（表示はしない）
+fn autosave(game: &Game, saved: &SavedGame) {
+    if let Some(path) = autosave_path() {
+        saved.save(game, &path.to_string_lossy()).ok();
+    }
+}
+
+/// 対局が正常に終わったので，自動で保存したファイルを消す
+fn remove_autosave() {
+    if let Some(path) = autosave_path() {
+        std::fs::remove_file(path).ok();
+    }
+}
+
+/// 自動で保存したファイルが残っていれば（前回の対局が異常終了していれば），再開するか聞く
+///
+/// 再開しない場合や読み込めなかった場合はファイルを消してNoneを返す．
+fn recover_autosave() -> Option<(SavedGame, Game)> {
+    let path = autosave_path().filter(|p| p.exists())?;
+    let path = path.to_string_lossy();
+    println!("{}", msg().ask_recover());
+    let recovered = if input::read_line().trim() == "y" {
+        let start = BoardState::new(cli().size.unwrap_or(8) / 2, false);
+        SavedGame::load(&path, start)
+            .map_err(|e| println!("{}", msg().replay_load_failed(&path, &e)))
+            .ok()
     } else {
-        println!("{0}が{1}個，{2}が{3}個で引き分け！", c1, s1, c2, s2);
+        None
+    };
+    if recovered.is_none() {
+        remove_autosave();
     }
+    recovered
 }
 
-fn main() {
-    println!("オセロをします．");
+/// 結果の説明
+fn result_text(game: &Game, names: &Names) -> String {
+    match game.outcome() {
+        Some(Outcome::Resignation { resigned }) => {
+            msg().resigned(&names.label(resigned), &names.label(resigned.opponent()))
+        }
+        Some(Outcome::Timeout { flagged }) => {
+            msg().timed_out(&names.label(flagged), &names.label(flagged.opponent()))
+        }
+        _ => {
+            let score = game.board().score();
+            msg().result(
+                &names.label(Player::White),
+                score.white,
+                &names.label(Player::Black),
+                score.black,
+            )
+        }
+    }
+}
+
+/// 結果を表す1行（```--quiet```．例: ```B 36 - 28 W #=win```）
+///
+/// 勝った側は```#```か```o```，引き分けなら```draw```，終局していなければ```unfinished```で表し，
+/// 投了や時間切れならその理由を後ろにつける．
+fn result_line(game: &Game) -> String {
+    let score = game.board().score();
+    let result = match game.outcome() {
+        None => "unfinished".to_string(),
+        Some(outcome) => {
+            let winner = match outcome.winner() {
+                Some(p) => format!("{}=win", p.piece()),
+                None => "draw".to_string(),
+            };
+            match outcome {
+                Outcome::Resignation { .. } => format!("{} (resign)", winner),
+                Outcome::Timeout { .. } => format!("{} (timeout)", winner),
+                Outcome::Completed { .. } => winner,
+            }
+        }
+    };
+    format!("B {} - {} W {}", score.black, score.white, result)
+}
+
+/// CPU同士の対局を表示せずに1局行い，結果を1行だけ表示する（```--watch --quiet```）
+///
+/// 盤面のサイズは```--size```（既定は8），強さは```--level```（既定はかんたん）で，
+/// ```--search mcts```でなければαβ法で読む．```--csv```で結果をCSVのファイルに追記する．
+fn run_quiet_watch() {
+    let size = cli().size.unwrap_or(8);
+    let level = match cli().level {
+        Some(n) => Level::ALL[n as usize - 1],
+        None => Level::Easy,
+    };
+    let use_mcts = cli().search == Some(SearchMethod::Mcts);
+    let start = BoardState::new(size / 2, false);
+    let mut cpu = make_cpu(level, use_mcts, &eval_weights(), &start);
+    let mut game = Game::new(start);
+    let started = Instant::now();
+    while !game.is_over() {
+        if cli().json {
+            print_json(&game);
+        }
+        let player = game.board().turn();
+        match cpu.choose_move_or_resign(game.board()) {
+            Some(mv) => game.apply_move(mv).unwrap(),
+            None => game.resign(player).unwrap(),
+        }
+    }
+    if cli().json {
+        print_json(&game);
+    }
+    println!("{}", result_line(&game));
+    let name = if use_mcts && level.mcts().is_some() {
+        msg().mcts_name(msg().level_name(level))
+    } else {
+        msg().level_name(level).to_string()
+    };
+    append_csv(&GameRow {
+        seed: rng_seed(),
+        black: &name,
+        white: &name,
+        game: &game,
+        duration: started.elapsed(),
+    });
+}
+
+/// ```--csv```が指定されていれば，そのファイルに1局の結果を追記する
+fn append_csv(row: &GameRow) {
+    if let Some(path) = &cli().csv {
+        if let Err(e) = csv::append(path, row) {
+            println!("{}", msg().csv_failed(path, &e));
+        }
+    }
+}
+
+/// 対局の相手になるCPU（levelの強さで，use_mctsならモンテカルロ木探索で読む）
+///
+/// 先読みする強さなら```--no-probcut```，```--resign```，```--blunder-rate```，```--stats```，```--win-rate```を反映し，
+/// ```--book```が指定されていればstartの局面から定石集を使う．
+fn make_cpu(
+    level: Level,
+    use_mcts: bool,
+    weights: &EvalWeights,
+    start: &BoardState,
+) -> Box<dyn Strategy> {
+    let mut cpu: Box<dyn Strategy> = match level.mcts() {
+        Some(m) if use_mcts => Box::new(seeded_mcts(m, rng_seed())),
+        _ => match level.engine(thread_count(), weights) {
+            Some(engine) => {
+                // ```--no-probcut```なら枝刈りを予想に頼らず正確に読む
+                let engine = if cli().no_probcut {
+                    engine.probcut(None)
+                } else {
+                    engine
+                };
+                let engine = match cli().resign {
+                    Some(t) => engine.resign_threshold(t),
+                    None => engine,
+                };
+                if let Some(rate) = cli().blunder_rate {
+                    let handicap = HandicapStrategy::new(engine, rate).margin(cli().blunder_margin);
+                    match rng_seed() {
+                        Some(s) => Box::new(handicap.seed(s)),
+                        None => Box::new(handicap),
+                    }
+                } else if cli().stats || cli().win_rate {
+                    // ```--blunder-rate```とは一緒に指定できない（cli.rs）
+                    Box::new(VerboseEngine {
+                        engine,
+                        stats: cli().stats,
+                        win_rate: if cli().win_rate {
+                            Some(win_probability())
+                        } else {
+                            None
+                        },
+                    })
+                } else {
+                    Box::new(engine)
+                }
+            }
+            None => level.strategy(thread_count(), rng_seed(), weights),
+        },
+    };
+
+    // 定石集（```--book ファイル名```で指定された場合）
+    if let Some(path) = &cli().book {
+        match Book::load(path, start) {
+            Ok(book) => {
+                let book_cpu = BookStrategy::new(book, cpu);
+                cpu = match rng_seed() {
+                    Some(s) => Box::new(book_cpu.seed(s)),
+                    None => Box::new(book_cpu),
+                };
+            }
+            Err(e) => println!("{}", msg().book_error(&e)),
+        }
+    }
+    cpu
+}
+
+/// CPUが先読みに使うスレッドの数（```--threads 数```で指定、既定ではCPUのコア数）
+///
+/// ```--seed```を指定して```--threads```を指定しなければ，毎回同じ手を選ぶように1つにする．
+fn thread_count() -> usize {
+    match cli().threads {
+        Some(n) => n as usize,
+        None if rng_seed().is_some() => 1,
+        None => std::thread::available_parallelism().map_or(1, |n| n.get()),
+    }
+}
+
+/// CPUが使う乱数の種（```--seed 数```で指定，指定しなければNone）
+///
+/// 指定すると，同じ手順で打てばCPUも毎回同じ手を選ぶ（時間で区切って読むCPUと，
+/// ```--threads```で2つ以上のスレッドを指定した場合を除く）．
+fn rng_seed() -> Option<u64> {
+    cli().seed
+}
+
+/// CPUの評価関数の重み（```--weights ファイル名```で指定，指定しなければ既定値）
+///
+/// ファイルを読み込めなかった場合は，理由を表示して既定値を使う．
+fn eval_weights() -> EvalWeights {
+    match &cli().weights {
+        Some(path) => EvalWeights::load(path).unwrap_or_else(|e| {
+            println!("{}", msg().weights_fallback(&msg().weights_error(&e)));
+            EvalWeights::default()
+        }),
+        None => EvalWeights::default(),
+    }
+}
+
+/// 乱数の種が指定されていれば，それをモンテカルロ木探索のCPUに設定する
+fn seeded_mcts(m: Mcts, seed: Option<u64>) -> Mcts {
+    match seed {
+        Some(s) => m.seed(s),
+        None => m,
+    }
+}
+
+/// CPU同士の総当たり戦（```tournament```サブコマンド）
+///
+/// ```--size```で盤面のサイズ（既定は8），gamesは1つの組み合わせの対局数，
+/// levelsは強さの番号（空なら全て）．
+/// mctsがtrueならモンテカルロ木探索のCPUも参加させる．
+/// ```--csv```で1局ごとの結果をCSVのファイルに追記する．
+fn run_tournament(games: usize, levels: &[u8], mcts: bool) {
+    let size = cli().size.unwrap_or(8);
+    let levels: Vec<Level> = if levels.is_empty() {
+        Level::ALL.to_vec()
+    } else {
+        levels.iter().map(|&n| Level::ALL[n as usize - 1]).collect()
+    };
+
+    let threads = thread_count();
+    let weights = eval_weights();
+    let mut tournament = Tournament::new(BoardState::new(size / 2, false), games);
+    let mut names: Vec<String> = Vec::new();
+    for level in levels {
+        // 乱数の種は参加者ごとにずらす
+        let seed = rng_seed().map(|s| s.wrapping_add(names.len() as u64));
+        let name = msg().level_name(level);
+        tournament.add(name, level.strategy(threads, seed, &weights));
+        names.push(name.to_string());
+        if let Some(m) = level.mcts().filter(|_| mcts) {
+            let seed = rng_seed().map(|s| s.wrapping_add(names.len() as u64));
+            let name = msg().mcts_name(msg().level_name(level));
+            tournament.add(&name, Box::new(seeded_mcts(m, seed)));
+            names.push(name);
+        }
+    }
+
+    println!("{}", msg().tournament_intro(size));
+    // 対局は順に行われるので，前の対局が終わってからの時間をその対局にかかった時間とする
+    let mut started = Instant::now();
+    let table = tournament.run(|black, white, game| {
+        let score = game.board().score();
+        println!(
+            "{}",
+            msg().tournament_game(
+                &names[black],
+                piece(Player::Black),
+                &names[white],
+                piece(Player::White),
+                score.black,
+                score.white
+            )
+        );
+        append_csv(&GameRow {
+            seed: rng_seed(),
+            black: &names[black],
+            white: &names[white],
+            game,
+            duration: started.elapsed(),
+        });
+        started = Instant::now();
+    });
+    println!();
+    print!("{}", table.to_text(msg().crosstable_total()));
+}
+
+/// 読む手の順番の工夫の効果を測る（```bench```サブコマンド）
+///
+/// 8×8の盤面の初期局面と，そこから乱数の種を固定した「よくばり」同士で進めたいくつかの局面を，
+/// 工夫しない場合とする場合でdepth手先まで読んで，調べた局面の数と時間を比べる．
+fn run_bench(depth: u32) {
+    let mut positions = Vec::new();
+    let mut bs = BoardState::new(4, false);
+    let mut cpu = GreedyStrategy::with_seed(Some(0));
+    for ply in 0..=30usize {
+        if bs.is_game_over() {
+            break;
+        }
+        if ply.is_multiple_of(10) {
+            positions.push((ply, bs.clone()));
+        }
+        match cpu.choose_move(&bs) {
+            Move::Place(c) => {
+                bs.put(c.row, c.col);
+            }
+            Move::Pass => {
+                bs.pass().ok();
+            }
+        }
+    }
+
+    println!("{}", msg().bench_intro(depth));
+    let (mut total_before, mut total_after) = (0, 0);
+    for (ply, bs) in positions {
+        let mut nodes = [0; 2];
+        let mut times = [Duration::from_secs(0); 2];
+        for (k, &ordering) in [false, true].iter().enumerate() {
+            let engine = Engine::new(depth)
+                .endgame_empties(0)
+                .threads(thread_count())
+                .move_ordering(ordering);
+            let start = std::time::Instant::now();
+            nodes[k] = engine.search(&bs).map_or(0, |r| r.nodes);
+            times[k] = start.elapsed();
+        }
+        println!(
+            "{}",
+            msg().bench_line(ply, nodes, [times[0].as_secs_f64(), times[1].as_secs_f64()])
+        );
+        total_before += nodes[0];
+        total_after += nodes[1];
+    }
+    if total_before > 0 {
+        println!(
+            "{}",
+            msg().bench_total(
+                total_before,
+                total_after,
+                total_after as f64 * 100.0 / total_before as f64
+            )
+        );
+    }
+}
+
+/// 自己対局で評価関数の重みを調整する（```tune```サブコマンド）
+///
+/// ```--weights```の重み（指定しなければ既定値）から始めてiterations回試行し，
+/// 重みが改善されるたびにoutputのファイルに書き込む．
+/// gamesは1回の試行の対局数，depthは読む深さ，stepは重みをずらす幅．
+/// 盤面のサイズは```--size```（既定は8）．
+fn run_tune(iterations: usize, games: usize, depth: u32, step: i32, output: &str) {
+    let size = cli().size.unwrap_or(8);
+    let mut tuner = Tuner::new(BoardState::new(size / 2, false))
+        .games(games)
+        .depth(depth)
+        .step(step);
+    if let Some(s) = rng_seed() {
+        tuner = tuner.seed(s);
+    }
+
+    println!("{}", msg().tune_intro(size, iterations));
+    tuner.run(eval_weights(), iterations, |trial, weights| {
+        println!(
+            "{}",
+            msg().tune_trial(trial.iteration, trial.score * 100.0, trial.accepted)
+        );
+        if trial.accepted {
+            if let Err(e) = weights.save(output) {
+                println!("{}", msg().weights_error(&e));
+            }
+        }
+    });
+    println!("{}", msg().tune_done(output));
+}
+
+/// 自己対局の結果から，評価値を勝つ確率にするときの尺度を求める（```calibrate```サブコマンド）
+///
+/// depth手先まで読むCPU同士をgames局対局させて，求めた尺度を表示する（```--win-scale```に指定できる）．
+/// ```--size```，```--weights```，```--seed```は```tune```と同じ．
+fn run_calibrate(games: usize, depth: u32) {
+    let size = cli().size.unwrap_or(8);
+    let mut tuner = Tuner::new(BoardState::new(size / 2, false))
+        .games(games)
+        .depth(depth);
+    if let Some(s) = rng_seed() {
+        tuner = tuner.seed(s);
+    }
+
+    println!("{}", msg().calibrate_intro(size));
+    let samples = tuner.win_samples(&eval_weights());
+    let model = WinProbability::calibrate(&samples);
+    println!("{}", msg().calibrate_done(samples.len(), model.scale()));
+}
+
+/// ```replay```や```export-image```で見る棋譜を読み込む（読み込めなければ理由を表示してNone）
+///
+/// 始めの局面が保存されていなければ，```--size```の大きさ（既定は8）の最初の配置から始める．
+/// WTHOR形式のデータベースなら，number局目を読み込んでその対局者などを表示する．
+fn load_game_file(path: &str, number: usize) -> Option<Game> {
+    let loaded: Result<Game, Box<dyn Error>> = if is_wthor(path) {
+        load_wthor(path).and_then(|(db, names)| match db.games.get(number - 1) {
+            Some(g) => {
+                println!("{}", wthor_line(number, &db, g, &names));
+                Ok(g.to_game().map_err(|e| msg().replay_error(&e))?)
+            }
+            None => Err(msg().wthor_no_game(number, db.games.len()).into()),
+        })
+    } else {
+        let start = BoardState::new(cli().size.unwrap_or(8) / 2, false);
+        SavedGame::load(path, start)
+            .map(|(_, game)| game)
+            .map_err(Into::into)
+    };
+    loaded
+        .map_err(|e| println!("{}", msg().replay_load_failed(path, &e)))
+        .ok()
+}
+
+/// 保存した棋譜のat手目の後（Noneなら最後）の局面を画像に書き出す（```export-image```サブコマンド）
+///
+/// WTHOR形式のデータベースなら，number局目を書き出す．
+fn run_export_image(path: &str, output: &str, at: Option<usize>, numbers: bool, number: usize) {
+    let mut game = match load_game_file(path, number) {
+        Some(game) => game,
+        None => process::exit(1),
+    };
+    if let Some(at) = at {
+        while game.history().len() > at && game.undo() {}
+    }
+    export_image(&game, output, numbers);
+}
+
+/// 今の盤面を画像としてpathのファイルに書き出す（```.png```で終わればPNG，それ以外はSVG）
+fn export_image(game: &Game, path: &str, numbers: bool) {
+    let bytes = if path.to_ascii_lowercase().ends_with(".png") {
+        game.render_png(numbers)
+    } else {
+        game.render_svg(numbers).into_bytes()
+    };
+    match std::fs::write(path, bytes) {
+        Ok(()) => println!("{}", msg().image_written(path)),
+        Err(e) => println!("{}", msg().image_failed(path, &e)),
+    }
+}
+
+/// 保存した棋譜を1手ずつ見る（```replay```サブコマンド）
+///
+/// 始めの局面が保存されていなければ，```--size```の大きさ（既定は8）の最初の配置から始める．
+/// WTHOR形式のデータベースなら，number局目を見る．
+fn run_replay(path: &str, number: usize) {
+    let mut game = match load_game_file(path, number) {
+        Some(game) => game,
+        None => return,
+    };
+    let total = game.history().len();
+    while game.undo() {}
 
-    // 盤面サイズの入力・決定
-    let size: usize;
     loop {
-        println!("盤面のサイズを4以上の偶数で入力してください．Returnキーで確定します．");
-        let mut size_string = String::new();
-        std::io::stdin().read_line(&mut size_string).ok();
+        println!("{}", msg().replay_position(game.history().len(), total));
+        if let Some(r) = game.history().last() {
+            println!("{}. {} {}", r.number, piece(r.player), r.mv);
+        }
+        show_score(game.board());
+        preview_board(&game, false, None);
+        println!("{}", msg().replay_prompt());
+
+        let line = input::read_line();
+        if line.is_empty() {
+            return;
+        }
+        let target = match line.trim() {
+            "" | "n" => game.history().len() + 1,
+            "p" => game.history().len().saturating_sub(1),
+            "q" => return,
+            s => match s.parse::<usize>() {
+                Ok(n) => n,
+                Err(_) => {
+                    err_not_int();
+                    continue;
+                }
+            },
+        };
+        // パスは前後の手とまとめて進めたり戻したりする
+        while game.history().len() > target && game.undo() {}
+        while game.history().len() < target && game.redo() {}
+    }
+}
+
+/// 記録した対局を一覧表示する（```history list```サブコマンド）
+///
+/// 相手は，CPUとの対局ならCPUの強さ，2人での対局なら両者の名前（なければ駒の文字）を表示する．
+fn run_history_list() {
+    let paths = archive::entries().unwrap_or_default();
+    if paths.is_empty() {
+        println!("{}", msg().archive_empty());
+    }
+    for (i, path) in paths.iter().enumerate() {
+        let path_str = path.to_string_lossy();
+        let (saved, game) = match SavedGame::load(&path_str, BoardState::new(4, false)) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                println!("{}", msg().replay_load_failed(&path_str, &e));
+                continue;
+            }
+        };
+        let opponent = match saved.mode {
+            Mode::Cpu { .. } => msg().opponent_cpu(saved.level),
+            Mode::Watch => msg().opponent_watch().to_string(),
+            Mode::Pvp => {
+                let names = Names(saved.names);
+                format!(
+                    "{} - {}",
+                    names.label(Player::Black),
+                    names.label(Player::White)
+                )
+            }
+        };
+        println!(
+            "{}",
+            msg().archive_line(
+                i + 1,
+                &archive::date(path),
+                &opponent,
+                game.board().get_size(),
+                &result_line(&game)
+            )
+        );
+    }
+}
+
+/// 記録したid番目の対局を1手ずつ見る（```history show```サブコマンド）
+fn run_history_show(id: usize) {
+    let paths = archive::entries().unwrap_or_default();
+    match paths.get(id - 1) {
+        Some(path) => run_replay(&path.to_string_lossy(), 1),
+        None => println!("{}", msg().archive_no_game(id, paths.len())),
+    }
+}
+
+/// ファイル名が```.wtb```で終わるかどうか（WTHOR形式のデータベースかどうか）
+fn is_wthor(path: &str) -> bool {
+    path.to_ascii_lowercase().ends_with(".wtb")
+}
+
+/// WTHOR形式の対局者と大会の名前の一覧
+type WthorNames = [Vec<String>; 2];
+
+/// WTHOR形式のデータベースと，同じディレクトリにある対局者と大会の名前の一覧を読み込む
+///
+/// 名前の一覧（```WTHOR.JOU```と```WTHOR.TRN```．小文字でもよい）がなければ空にする．
+fn load_wthor(path: &str) -> Result<(Wthor, WthorNames), Box<dyn Error>> {
+    let db = Wthor::load(path).map_err(|e| msg().wthor_error(&e))?;
+    let dir = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+    let names = |file: &str, load: fn(PathBuf) -> Result<Vec<String>, WthorError>| {
+        [file.to_string(), file.to_lowercase()]
+            .iter()
+            .map(|f| dir.join(f))
+            .find(|p| p.exists())
+            .map_or(Ok(Vec::new()), load)
+            .map_err(|e| msg().wthor_error(&e))
+    };
+    let players = names("WTHOR.JOU", wthor::load_players)?;
+    let tournaments = names("WTHOR.TRN", wthor::load_tournaments)?;
+    Ok((db, [players, tournaments]))
+}
+
+/// WTHOR形式のnumber局目の対局者・大会・結果の1行（namesは対局者と大会の名前の一覧）
+///
+/// 名前の一覧にない番号は```#番号```と表示する．
+fn wthor_line(number: usize, db: &Wthor, game: &WthorGame, names: &WthorNames) -> String {
+    let name = |list: &Vec<String>, i: u16| {
+        list.get(i as usize)
+            .cloned()
+            .unwrap_or_else(|| format!("#{}", i))
+    };
+    let [players, tournaments] = names;
+    msg().wthor_game(
+        number,
+        db.year,
+        &name(tournaments, game.tournament),
+        &name(players, game.black),
+        &name(players, game.white),
+        (game.black_discs, 64 - game.black_discs),
+    )
+}
+
+/// WTHOR形式のデータベースの対局を一覧表示する（```wthor```サブコマンド）
+///
+/// bookを指定すると，各対局の最初のdepth手を定石集の1行ずつとして書き込む．
+fn run_wthor(path: &str, book: Option<&str>, depth: usize) {
+    let (db, names) = match load_wthor(path) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            println!("{}", msg().replay_load_failed(path, &e));
+            process::exit(1);
+        }
+    };
+    for (i, g) in db.games.iter().enumerate() {
+        println!("{}", wthor_line(i + 1, &db, g, &names));
+    }
+    if let Some(book) = book {
+        let lines: String = db.games.iter().map(|g| g.book_line(depth) + "\n").collect();
+        match std::fs::write(book, lines) {
+            Ok(()) => println!("{}", msg().book_written(book, db.games.len())),
+            Err(e) => {
+                println!("{}", msg().save_failed(book, &e));
+                process::exit(1);
+            }
+        }
+    }
+}
+
+/// 手の並びを最後まで打って，盤面と結果を表示する（```script```サブコマンド）
+///
+/// 盤面は```--position```の局面か，```--size```の大きさ（既定は8）の最初の配置から始める．
+/// 読めない手や打てない手があれば，それを表示して終了コード1で終わる．
+fn run_script(path: Option<&str>) {
+    let size = cli().size.unwrap_or(8);
+    let start = match cli().position.as_deref() {
+        Some(path) => load_position(path),
+        None => BoardState::new(size / 2, false),
+    };
+    let path = path.unwrap_or("-");
+    let text = if path == "-" {
+        io::read_to_string(io::stdin())
+    } else {
+        std::fs::read_to_string(path)
+    };
+    let text = match text {
+        Ok(text) => text,
+        Err(e) => {
+            println!("{}", msg().replay_load_failed(path, &e));
+            process::exit(1);
+        }
+    };
+    let game = match Game::from_moves(start, &text) {
+        Ok(game) => game,
+        Err(e) => {
+            let word = text.split_whitespace().nth(e.index).unwrap_or_default();
+            let error = msg().reversi_error(&e.error);
+            println!("{}", msg().script_failed(e.index + 1, word, &error));
+            process::exit(1);
+        }
+    };
+    let names = Names([cli().black_name.clone(), cli().white_name.clone()]);
+    if cli().quiet {
+        println!("{}", result_line(&game));
+    } else {
+        show_score(game.board());
+        preview_board(&game, false, None);
+        if game.is_over() {
+            show_result(&game, &names);
+        } else {
+            preview_turn(game.board(), &names);
+        }
+    }
+    if cli().json {
+        print_json(&game);
+    }
+}
+
+/// ```--position```のファイルから局面を読み込む（読み込めなければ終了コード1で終わる）
+///
+/// ファイルが```8/8/8/3o#3/3#o3/8/8/8 b 8```のような1行の局面の文字列ならそれを，
+/// そうでなければ盤面を表示したときと同じ形式（今の```--pieces```などの文字でもよい）として読む．
+fn load_position(path: &str) -> BoardState {
+    let text = std::fs::read_to_string(path).unwrap_or_else(|e| {
+        println!("{}", msg().position_failed(path, &e));
+        process::exit(1);
+    });
+    BoardState::from_position_string(&text)
+        .or_else(|_| board_style().with_hint(hint_mark()).parse_board(&text))
+        .unwrap_or_else(|e| {
+            println!("{}", msg().position_failed(path, &msg().reversi_error(&e)));
+            process::exit(1);
+        })
+}
+
+/// ```--resume```で指定した保存した対局を読み込む（読み込めなければ終了コード1で終わる）
+///
+/// 始めの局面が保存されていなければ，```--size```の大きさ（既定は8）の最初の配置から始めたものとする．
+fn load_saved_game(path: &str) -> (SavedGame, Game) {
+    let start = BoardState::new(cli().size.unwrap_or(8) / 2, false);
+    match SavedGame::load(path, start) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            println!("{}", msg().replay_load_failed(path, &e));
+            process::exit(1);
+        }
+    }
+}
+
+/// 今の設定を設定ファイルに書き込む（```config```サブコマンド）
+fn run_config() {
+    let path = match Config::path(cli()) {
+        Some(path) => path,
+        None => return,
+    };
+    match Config::from_cli(cli()).save(&path) {
+        Ok(()) => println!("{}", msg().config_saved(&path.display())),
+        Err(e) => println!("{}", msg().config_save_failed(&path.display(), &e)),
+    }
+}
+
+fn main() {
+    if let Some(e) = &cli().config_error {
+        println!("{}", msg().config_failed(e));
+    }
+    if cli().quiet && !cli().watch && !matches!(cli().command, Some(Command::Script { .. })) {
+        println!("{}", msg().quiet_not_allowed());
+        process::exit(2);
+    }
+    match &cli().command {
+        Some(Command::Tournament {
+            games,
+            levels,
+            mcts,
+        }) => {
+            run_tournament(*games as usize, levels, *mcts);
+            return;
+        }
+        Some(Command::Bench { depth }) => {
+            run_bench(*depth);
+            return;
+        }
+        Some(Command::Tune {
+            iterations,
+            games,
+            depth,
+            step,
+            output,
+        }) => {
+            run_tune(*iterations as usize, *games as usize, *depth, *step, output);
+            return;
+        }
+        Some(Command::Script { file }) => {
+            run_script(file.as_deref());
+            return;
+        }
+        Some(Command::Config) => {
+            run_config();
+            return;
+        }
+        Some(Command::Replay { file, game }) => {
+            run_replay(file, *game as usize);
+            return;
+        }
+        Some(Command::ExportImage {
+            file,
+            output,
+            at,
+            numbers,
+            game,
+        }) => {
+            run_export_image(
+                file,
+                output,
+                at.map(|n| n as usize),
+                *numbers,
+                *game as usize,
+            );
+            return;
+        }
+        Some(Command::History { action }) => {
+            match action {
+                HistoryAction::List => run_history_list(),
+                HistoryAction::Show { id } => run_history_show(*id as usize),
+            }
+            return;
+        }
+        Some(Command::Wthor { file, book, depth }) => {
+            run_wthor(file, book.as_deref(), *depth as usize);
+            return;
+        }
+        Some(Command::Calibrate { games, depth }) => {
+            run_calibrate(*games as usize, *depth);
+            return;
+        }
+        None if cli().quiet => {
+            run_quiet_watch();
+            return;
+        }
+        None => {}
+    }
+
+    println!("{}", msg().welcome());
+
+    // 保存した対局を再開する場合（```--resume```）は，保存した設定で始めて入力を求めない
+    // 前回の対局が異常終了していれば，その続きから再開することもできる
+    let mut resumed = match cli().resume.as_deref() {
+        Some(path) => Some(load_saved_game(path)),
+        None if cli().from_transcript.is_none() && cli().position.is_none() && !cli().edit => {
+            recover_autosave()
+        }
+        None => None,
+    };
+    let saved = resumed.as_ref().map(|(s, _)| s.clone());
+    // 局面のファイルから始める場合（```--position```）は，その局面を始めの局面にする
+    // 1行の棋譜から始める場合（```--from-transcript```）は，その局面か```--size```の大きさ（既定は8）の盤面で手順を打つ
+    let positioned = cli().position.as_deref().map(load_position);
+    let mut transcribed = match (cli().from_transcript.as_deref(), positioned) {
+        (Some(text), start) => {
+            let start =
+                start.unwrap_or_else(|| BoardState::new(cli().size.unwrap_or(8) / 2, false));
+            Some(
+                Game::from_compact_transcript(start, text).unwrap_or_else(|e| {
+                    println!("{}", msg().transcript_failed(&msg().replay_error(&e)));
+                    process::exit(1);
+                }),
+            )
+        }
+        (None, Some(start)) => Some(Game::new(start)),
+        (None, None) => None,
+    };
+
+    // 盤面サイズの入力・決定（```--size```で指定されていれば入力を求めない）
+    let mut size: usize = match (&resumed, &transcribed) {
+        (Some((_, game)), _) | (None, Some(game)) => game.board().get_size(),
+        (None, None) => cli().size.unwrap_or(0),
+    };
+    while size == 0 {
+        println!("{}", msg().ask_size());
+        let size_string = input::read_line();
         if let Ok(n) = size_string.trim().parse::<usize>() {
             if n >= 4 && n % 2 == 0 {
                 size = n;
-                break;
             } else {
                 err_input();
             }
@@ -97,29 +1529,35 @@ fn main() {
         }
     }
 
-    // CPUとやるかどうかの入力・決定
-    let mut cpu_flag: bool = false;
-    let mut cpu_only_flag: bool = false;
-    println!("CPUと戦う場合は1，CPUだけが操作しているのを見る場合は2，自分で両方を操作する場合はそれ以外を入力してください．");
-    let mut y_or_no = String::new();
-    std::io::stdin().read_line(&mut y_or_no).ok();
-    if y_or_no.trim() == "1" {
-        cpu_flag = true;
-    } else if y_or_no.trim() == "2" {
-        cpu_only_flag = true;
+    // CPUとやるかどうかの入力・決定（```--cpu```，```--watch```，```--pvp```で指定されていれば入力を求めない）
+    let mut cpu_flag: bool = cli().cpu;
+    let mut cpu_only_flag: bool = cli().watch;
+    let mut i_am_white: bool = cli().color == Some(Color::White);
+    if let Some(s) = &saved {
+        cpu_flag = matches!(s.mode, Mode::Cpu { .. });
+        cpu_only_flag = s.mode == Mode::Watch;
+        i_am_white = s.mode
+            == (Mode::Cpu {
+                human: Player::White,
+            });
+    } else if !(cli().cpu || cli().watch || cli().pvp) {
+        println!("{}", msg().ask_mode());
+        let y_or_no = input::read_line();
+        if y_or_no.trim() == "1" {
+            cpu_flag = true;
+        } else if y_or_no.trim() == "2" {
+            cpu_only_flag = true;
+        }
     }
 
-    let mut i_am_white: bool = false;
-    if cpu_flag {
+    if cpu_flag && cli().color.is_none() && saved.is_none() {
         // どちらの番から始めるかの入力・決定
         loop {
             println!(
-                "{0}として始める場合は1を，{1}として始める場合は2を入力してください．{0}が先攻です．",
-                BoardState::black_piece(),
-                BoardState::white_piece()
+                "{}",
+                msg().ask_color(piece(Player::Black), piece(Player::White))
             );
-            let mut size_string = String::new();
-            std::io::stdin().read_line(&mut size_string).ok();
+            let size_string = input::read_line();
             if let Ok(n) = size_string.trim().parse::<usize>() {
                 match n {
                     1 => {
@@ -139,162 +1577,518 @@ fn main() {
         }
     }
 
-    // 盤面作成
-    let mut bs = BoardState::new(size / 2, false);
-
-    // ヘルプ（+印）を表示するかどうか
-    let mut with_help_or_not: bool = false;
-
-    // ゲーム実行
-    loop {
-        // 盤面の表示
-        if with_help_or_not {
-            preview_board_with_help(&bs);
-        } else {
-            preview_board(&bs);
-        }
-
-        // どちらのターンかの表示
-        preview_turn(&bs);
-
-        // CPUの番の場合
-        if (cpu_flag
-            && (i_am_white || bs.is_it_white_turn())
-            && !(i_am_white && bs.is_it_white_turn()))
-            || cpu_only_flag
-        {
-            // 乱数発生用
-            let mut rng = thread_rng();
-
-            // 時間を空けつつメッセージを表示
-            if cpu_flag {
-                sleep(Duration::from_millis(250));
-            }
-            println!("\nCPU操作中...\n");
-            sleep(Duration::from_millis(if cpu_only_flag { 500 } else { 750 }));
-
-            // 置けるマス目を重み付けしつつVecで管理
-            let mut options: Vec<(usize, usize)> = Vec::new();
-            let mut options_corners: Vec<(usize, usize)> = Vec::new();
-            let vec = &bs.cnt_reversable();
-            let n = bs.get_size();
-            for i in 0..n {
-                for j in 0..n {
-                    if vec[i][j] > 0 {
-                        for _ in 0..vec[i][j] {
-                            options.push((i, j));
-                        }
-                        if (i == 0 || i == n - 1) && (j == 0 || j == n - 1) {
-                            options_corners.push((i, j));
-                        }
-                    }
+    // CPUの強さの入力・決定
+    let mut level = match (&saved, cli().level) {
+        (Some(s), _) => s.level,
+        (None, Some(n)) => Level::ALL[n as usize - 1],
+        (None, None) => Level::Easy,
+    };
+    if (cpu_flag || cpu_only_flag) && cli().level.is_none() && saved.is_none() {
+        loop {
+            println!("{}", msg().ask_level());
+            for (i, &l) in Level::ALL.iter().enumerate() {
+                println!("{}: {}", i + 1, msg().level_name(l));
+            }
+            let level_string = input::read_line();
+            if let Ok(n) = level_string.trim().parse::<usize>() {
+                if n >= 1 && n <= Level::ALL.len() {
+                    level = Level::ALL[n - 1];
+                    break;
+                } else {
+                    err_not_range();
                 }
+            } else {
+                err_not_int();
             }
+        }
+    }
 
-            // ランダムに選ぶ
-            let &(i, j) = if options_corners.is_empty() {
-                options
+    // 先読みの方法の入力・決定
+    let mut use_mcts: bool = match &saved {
+        Some(s) => s.mcts,
+        None => cli().search == Some(SearchMethod::Mcts),
+    };
+    if level.mcts().is_some() && cli().search.is_none() && saved.is_none() {
+        loop {
+            println!("{}", msg().ask_search());
+            println!("1: {}", msg().search_alpha_beta());
+            println!("2: {}", msg().search_mcts());
+            let method_string = input::read_line();
+            if let Ok(n) = method_string.trim().parse::<usize>() {
+                match n {
+                    1 => {
+                        break;
+                    }
+                    2 => {
+                        use_mcts = true;
+                        break;
+                    }
+                    _ => {
+                        err_not_range();
+                    }
+                }
             } else {
-                options_corners
+                err_not_int();
             }
-            .choose(&mut rng)
-            .unwrap();
+        }
+    }
 
-            // マス目更新
-            let can_continue = bs.put(i, j);
+    // 対局ごとにCPUの強さを自動で調整するかどうか
+    let mut adaptive: Option<AdaptiveLevel> = None;
+    if cpu_flag && cli().adaptive {
+        adaptive = Some(AdaptiveLevel::new(level));
+    } else if cpu_flag && !cli().no_adaptive && saved.is_none() {
+        println!("{}", msg().ask_adaptive());
+        let adaptive_string = input::read_line();
+        if adaptive_string.trim() == "1" {
+            adaptive = Some(AdaptiveLevel::new(level));
+        }
+    }
 
-            // 続行できないときはループを抜けてゲームを終了
-            if !can_continue {
-                break;
+    // 対局者の名前（```--black-name```，```--white-name```．2人で対局するときは指定しなかった側の名前を入力する）
+    let mut names = match &saved {
+        Some(s) => Names(s.names.clone()),
+        None => Names([cli().black_name.clone(), cli().white_name.clone()]),
+    };
+    if !cpu_flag && !cpu_only_flag && saved.is_none() {
+        for (i, &player) in [Player::Black, Player::White].iter().enumerate() {
+            if names.0[i].is_none() {
+                println!("{}", msg().ask_name(piece(player)));
+                let name = input::read_line().trim().to_string();
+                names.0[i] = Some(name).filter(|n| !n.is_empty());
             }
-            continue;
         }
+    }
 
-        // 以下、自分の番の場合
+    // 何番勝負（```--best-of```）なら，それぞれの勝った数（最初に黒を持った側，白を持った側の順）を数える
+    let persons: Vec<String> = [Player::Black, Player::White]
+        .iter()
+        .enumerate()
+        .map(|(i, &p)| match names.get(p) {
+            Some(name) => name.to_string(),
+            None if cpu_flag && (p == Player::White) == i_am_white => msg().you().to_string(),
+            None if cpu_flag => "CPU".to_string(),
+            None => msg().player_number(i + 1),
+        })
+        .collect();
+    let mut series = [0usize; 2];
+    let mut series_played = 0;
+    // 最初の対局と色が入れ替わっているかどうか
+    let mut swapped = false;
+    if let Some(n) = cli().best_of {
+        println!("{}", msg().match_intro(n));
+    }
 
-        // 操作方法の表示
-        println!("駒を置く場所を，行番号，列番号の順で，Return区切りで入力してください．");
-        println!("もうゲームを終わって結果を見たい場合は，1つ目の数字として0を入力してください．");
-        if !with_help_or_not {
-            println!(
-                "駒が置ける場所のヒントを見たい場合は，1つ目の数字として{}を入力してください．",
-                size + 1
-            );
-        } else {
-            println!("");
+    // 対局を始める局面（```--edit```なら編集した局面，```--resume```や```--from-transcript```ならその対局の局面．
+    // 再戦でも同じ局面から始める）
+    let mut start_position = match (&resumed, &transcribed) {
+        (Some((_, game)), _) | (None, Some(game)) => game.start().clone(),
+        (None, None) => BoardState::new(size / 2, false),
+    };
+    if cli().edit && saved.is_none() {
+        start_position = editor::edit_position(start_position);
+    }
+
+    let weights = eval_weights();
+    // 盤面を文章で表示するかどうか（```describe```コマンドで切り替えると次の対局でもそのまま）
+    let mut describe = cli().describe;
+    // 対局中はCtrl-Cですぐに終了せずに，保存するか聞く
+    input::catch_interrupts(true);
+    loop {
+        // CPUの手の選び方
+        let start = start_position.clone();
+        let mut cpu = make_cpu(level, use_mcts, &weights, &start);
+
+        // 盤面作成（```--resume```や```--from-transcript```なら最初の対局はその続きから）
+        let (mut game, restored) = match (resumed.take(), transcribed.take()) {
+            (Some((s, game)), _) => (game, Some(s)),
+            (None, Some(game)) => (game, None),
+            (None, None) => (Game::new(start), None),
+        };
+
+        // 全画面表示（```--tui```）なら対局をそちらで行う
+        #[cfg(feature = "tui")]
+        if cli().tui {
+            let cpu_players: Vec<Player> = [Player::Black, Player::White]
+                .iter()
+                .copied()
+                .filter(|&p| cpu_only_flag || (cpu_flag && (p == Player::White) != i_am_white))
+                .collect();
+            if let Err(e) = tui::play(&mut game, cpu.as_mut(), &cpu_players, &names) {
+                println!("{}", e);
+            }
         }
+        game.add_observer(Box::new(PassNotifier));
 
-        // 1つ目の数字受け取り
-        let row_num: usize;
-        loop {
-            let mut row_num_string = String::new();
-            std::io::stdin().read_line(&mut row_num_string).ok();
-            if let Ok(n) = row_num_string.trim().parse::<usize>() {
-                if n < size + 1 || (n == size + 1 && !with_help_or_not) {
-                    row_num = n;
-                    break;
+        // 持ち時間（```--clock 分```で指定した場合）
+        let mut clock = match restored.as_ref().and_then(|s| s.clock) {
+            Some([black, white]) => Some(Clock::with_remaining(black, white)),
+            None => cli().clock.map(Clock::new),
+        };
+
+        // この対局で待ったした回数
+        let mut undos: usize = restored.map_or(0, |s| s.undos);
+
+        // ヘルプ（+印）を表示するかどうか（```--show-hints```なら常に表示する）
+        let mut with_help_or_not: bool = cli().show_hints;
+        // 盤面が端末に収まらないときに，どのマス目の周りを表示するか（Noneなら直前の手の周り）
+        let mut view: Option<Coord> = None;
+
+        // 保存するとき（```save```，Ctrl-C）の設定
+        let mode = if cpu_only_flag {
+            Mode::Watch
+        } else if cpu_flag {
+            Mode::Cpu {
+                human: if i_am_white {
+                    Player::White
                 } else {
-                    err_not_range();
-                }
-            } else {
-                err_not_int();
+                    Player::Black
+                },
             }
-        }
+        } else {
+            Mode::Pvp
+        };
+        let saved_game = |clock: &Option<Clock>, undos: usize| SavedGame {
+            names: names.0.clone(),
+            mode,
+            level,
+            mcts: use_mcts,
+            clock: clock
+                .as_ref()
+                .map(|c| [c.remaining(Player::Black), c.remaining(Player::White)]),
+            undos,
+        };
 
-        // 終了処理
-        if row_num == 0 {
-            println!("本当に終了しますか？はいならy，いいえならそれ以外を入力してください．");
-            let mut y_or_no = String::new();
-            std::io::stdin().read_line(&mut y_or_no).ok();
-            if y_or_no.trim() == "y" {
-                break;
+        // ```--json```で最後に出力した局面の手数（待ったでも出力し直す）
+        let mut json_shown: Option<usize> = None;
+        // 最後に自動で保存した局面の手数
+        let mut autosaved: Option<usize> = None;
+        // CPUが打ってから，自分の番になったことをまだ知らせていないか
+        let mut cpu_moved = false;
+
+        // ゲーム実行
+        while !game.is_over() {
+            // CPUが考えている間などにCtrl-Cが押されていれば，終了するか聞く
+            if input::take_interrupt() {
+                handle_interrupt(&game, &saved_game(&clock, undos));
+            }
+
+            if cli().json && json_shown != Some(game.history().len()) {
+                print_json(&game);
+                json_shown = Some(game.history().len());
+            }
+            if autosaved != Some(game.history().len()) {
+                autosave(&game, &saved_game(&clock, undos));
+                autosaved = Some(game.history().len());
+            }
+
+            // 直前に考えていた側の時計を止め，時間が切れていれば負けにする
+            // （手を打ったときに止めていれば，ここでは何もしない）
+            if stop_clock(&mut clock, &mut game) {
+                continue;
+            }
+            if let Some(clock) = clock.as_mut() {
+                show_clock(clock);
+                clock.start(game.board().turn());
+            }
+
+            let bs = game.board();
+
+            // 手数と駒の個数，盤面の表示
+            print!(
+                "{}  ",
+                msg().move_header(game.move_number(), game.empties())
+            );
+            show_score(bs);
+            if cli().show_mobility {
+                show_mobility(bs, &names);
+            }
+            if describe {
+                describe_board(&game);
             } else {
+                preview_board(&game, with_help_or_not, view);
+            }
+
+            // どちらのターンかの表示
+            preview_turn(bs, &names);
+
+            // CPUの番の場合
+            if (cpu_flag
+                && (i_am_white || bs.is_it_white_turn())
+                && !(i_am_white && bs.is_it_white_turn()))
+                || cpu_only_flag
+            {
+                // 時間を空けつつメッセージを表示
+                if cpu_flag {
+                    sleep(Duration::from_millis(250));
+                }
+                println!("{}", msg().cpu_thinking());
+                sleep(Duration::from_millis(if cpu_only_flag { 500 } else { 750 }));
+
+                // 置くマス目を選ぶ（勝ち目がなければ投了する）
+                let chosen = cpu.choose_move_or_resign(bs);
+                if stop_clock(&mut clock, &mut game) {
+                    continue;
+                }
+                match chosen {
+                    Some(mv) => {
+                        game.apply_move(mv).unwrap();
+                        cpu_moved = true;
+                        if !describe {
+                            animate_flips(&game);
+                        }
+                    }
+                    None => {
+                        let player = game.board().turn();
+                        println!("{}", msg().cpu_resigns(piece(player)));
+                        game.resign(player).unwrap();
+                    }
+                }
                 continue;
             }
+
+            // 以下、自分の番の場合
+
+            // CPUが打って自分の番になったら知らせる
+            // （手を打つ前の表示や打てない手のやり直しでは，もう一度知らせない）
+            if cpu_moved {
+                notify_turn(bs.turn());
+                cpu_moved = false;
+            }
+
+            // 操作方法の表示
+            println!("{}", msg().prompt_move());
+
+            // コマンドの受け取り（表示するだけのコマンドはその場で処理する）
+            // ```--move-time```なら，時間内に手を打たないと投了か，ランダムな手になる
+            // （時刻で表せないほど長ければ制限しない）
+            let deadline = cli()
+                .move_time
+                .and_then(|limit| Instant::now().checked_add(limit));
+            let command = loop {
+                let line = match deadline {
+                    Some(d) => match input::read_line_until(d) {
+                        Some(line) => line,
+                        None => break GameCommand::Timeout,
+                    },
+                    None => input::read_line(),
+                };
+                if input::take_interrupt() {
+                    handle_interrupt(&game, &saved_game(&clock, undos));
+                    println!("{}", msg().prompt_move());
+                    continue;
+                }
+                match GameCommand::parse(&line) {
+                    Some(GameCommand::Help) => println!("{}", msg().command_help()),
+                    Some(GameCommand::Rules) => {
+                        println!("{}", msg().rules());
+                        println!("{}", msg().command_help());
+                    }
+                    Some(GameCommand::Hint) => show_hint(bs),
+                    Some(GameCommand::Analyze { board }) => show_analysis(bs, board),
+                    Some(GameCommand::History) => show_history(&game),
+                    Some(GameCommand::Transcript) => println!("{}", game.compact_transcript()),
+                    Some(GameCommand::Position) => println!("{}", bs.to_position_string()),
+                    Some(GameCommand::Score) => show_score(bs),
+                    Some(GameCommand::Describe) => {
+                        describe = !describe;
+                        println!("{}", msg().describe_mode(describe));
+                    }
+                    Some(GameCommand::Save(path)) => {
+                        save_game(&game, &path, &saved_game(&clock, undos))
+                    }
+                    Some(GameCommand::Image { path, numbers }) => {
+                        export_image(&game, &path, numbers)
+                    }
+                    Some(GameCommand::Place(c)) if c.row >= size || c.col >= size => {
+                        err_not_range()
+                    }
+                    Some(GameCommand::View(c)) if c.row >= size || c.col >= size => err_not_range(),
+                    Some(command) => break command,
+                    None => println!("{}", msg().unknown_command()),
+                }
+            };
+
+            match command {
+                // 置けるマス目の表示
+                GameCommand::Moves => {
+                    with_help_or_not = true;
+                }
+                // 盤面の表示する場所の変更
+                GameCommand::View(c) => {
+                    view = Some(c);
+                }
+                // 待った処理（```--max-undos```で回数を制限できる）
+                GameCommand::Undo => {
+                    if cli().max_undos.is_some_and(|m| undos >= m) {
+                        println!("{}", msg().undo_limit(undos));
+                        continue;
+                    }
+                    let human = game.board().turn();
+                    if cpu_flag {
+                        println!("{}", msg().confirm_undo());
+                        let y_or_no = input::read_line();
+                        if y_or_no.trim() == "y" {
+                            if take_back(&mut game, Some(human)) {
+                                undos += 1;
+                            } else {
+                                println!("{}", msg().cannot_undo());
+                            }
+                        }
+                        continue;
+                    }
+                    // 2人で対局するときは，最後に駒を置いた側がその手の待ったを申し出て，相手が認めたら戻す
+                    let requester = match game.history().iter().rev().find(|r| r.mv != Move::Pass) {
+                        Some(r) => r.player,
+                        None => {
+                            println!("{}", msg().cannot_undo());
+                            continue;
+                        }
+                    };
+                    let opponent = names.label(requester.opponent());
+                    println!(
+                        "{}",
+                        msg().takeback_request(&names.label(requester), &opponent)
+                    );
+                    let y_or_no = input::read_line();
+                    if y_or_no.trim() == "y" {
+                        take_back(&mut game, Some(requester));
+                        undos += 1;
+                    } else {
+                        println!("{}", msg().takeback_declined(&opponent));
+                    }
+                }
+                // 投了処理
+                GameCommand::Quit => {
+                    println!("{}", msg().confirm_resign());
+                    let y_or_no = input::read_line();
+                    if y_or_no.trim() == "y" {
+                        let player = game.board().turn();
+                        game.resign(player).unwrap();
+                    }
+                }
+                // 1手の制限時間切れ
+                GameCommand::Timeout => {
+                    let player = game.board().turn();
+                    match cli().on_move_timeout {
+                        TimeoutAction::Forfeit => {
+                            game.time_out(player).unwrap();
+                        }
+                        TimeoutAction::Random => {
+                            if stop_clock(&mut clock, &mut game) {
+                                continue;
+                            }
+                            let mv =
+                                RandomStrategy::with_seed(rng_seed()).choose_move(game.board());
+                            println!("{}", msg().move_timeout_random(&mv));
+                            game.apply_move(mv).unwrap();
+                            if !describe {
+                                animate_flips(&game);
+                            }
+                        }
+                    }
+                }
+                // マス目更新（置けないマス目なら何もしない）
+                // ```--confirm-moves```なら打つ前に裏返る駒を見せて確かめる
+                GameCommand::Place(c) => {
+                    with_help_or_not = cli().show_hints;
+                    view = None;
+                    if cli().confirm_moves && !confirm_move(game.board(), c) {
+                        continue;
+                    }
+                    if stop_clock(&mut clock, &mut game) {
+                        continue;
+                    }
+                    if game.apply_move(Move::Place(c)).is_err() {
+                        println!("{}", msg().cannot_place());
+                    } else if !describe {
+                        animate_flips(&game);
+                    }
+                }
+                _ => {}
+            }
         }
+        // 終局したので，自動で保存した途中の棋譜は要らない
+        remove_autosave();
 
-        // ヘルプ表示処理
-        if row_num == size + 1 {
-            with_help_or_not = true;
-            continue;
+        // 盤面表示
+        if describe {
+            describe_board(&game);
+        } else {
+            preview_board(&game, false, None);
+        }
+        // 結果表示
+        show_result(&game, &names);
+        // 対局の記録（```history```サブコマンドで見られる）
+        if let Err(e) = archive::store(&game, &saved_game(&clock, undos)) {
+            println!("{}", msg().archive_failed(&e));
+        }
+        if cli().json {
+            print_json(&game);
         }
-        with_help_or_not = false;
 
-        // 2つ目の数字受け取り
-        let column_num: usize;
-        loop {
-            let mut column_num_string = String::new();
-            std::io::stdin().read_line(&mut column_num_string).ok();
-            if let Ok(n) = column_num_string.trim().parse::<usize>() {
-                if n > 0 && n <= size {
-                    column_num = n;
-                    break;
-                } else {
-                    err_not_range();
+        // 振り返り
+        let human = if i_am_white {
+            Player::White
+        } else {
+            Player::Black
+        };
+        if cpu_flag {
+            println!("{}", msg().ask_review());
+            let y_or_no = input::read_line();
+            if y_or_no.trim() == "y" {
+                let reviews = show_review(&game, human);
+                if let Some(path) = &cli().annotate {
+                    save_annotated(&game, &reviews, &names, path);
                 }
-            } else {
-                err_not_int();
             }
         }
 
-        // 置けるマス目かどうか判定
-        let v = bs.cnt_reversable();
-        if v[row_num - 1][column_num - 1] == 0 {
-            println!("そこには置けません．");
-            continue;
+        // 強さの自動調整
+        if let Some(adaptive) = adaptive.as_mut() {
+            let winner = game.outcome().and_then(|o| o.winner());
+            if let Some(new_level) = adaptive.record(winner, human) {
+                println!(
+                    "{}",
+                    msg().level_changed(msg().level_name(level), msg().level_name(new_level))
+                );
+                level = new_level;
+            }
         }
 
-        // マス目更新
-        let can_continue = bs.put(row_num - 1, column_num - 1);
-
-        // 続行できないときはループを抜けてゲームを終了
-        if !can_continue {
-            break;
+        // 何番勝負なら決着がつくまで続け，そうでなければもう一度対局するか聞く
+        if let Some(n) = cli().best_of {
+            if let Some(winner) = game.outcome().and_then(|o| o.winner()) {
+                series[usize::from((winner == Player::White) != swapped)] += 1;
+            }
+            println!(
+                "{}",
+                msg().series_score(&persons[0], series[0], &persons[1], series[1])
+            );
+            series_played += 1;
+            if series.iter().any(|&w| w > n as usize / 2) || series_played >= n as usize {
+                match series[0].cmp(&series[1]) {
+                    Ordering::Greater => {
+                        println!("{}", msg().match_winner(&persons[0], series[0], series[1]))
+                    }
+                    Ordering::Less => {
+                        println!("{}", msg().match_winner(&persons[1], series[1], series[0]))
+                    }
+                    Ordering::Equal => println!("{}", msg().match_drawn()),
+                }
+                break;
+            }
+        } else {
+            println!("{}", msg().ask_rematch());
+            let y_or_no = input::read_line();
+            if y_or_no.trim() != "y" {
+                break;
+            }
         }
-    }
 
-    // 盤面表示
-    preview_board(&bs);
-    // 結果表示
-    show_result(&bs);
+        // 盤面の大きさや強さはそのままで色を入れ替える
+        i_am_white = !i_am_white;
+        names.0.swap(0, 1);
+        swapped = !swapped;
+    }
 }
@@ -0,0 +1,1596 @@
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use simple_reversi::boardstate::Coord;
+use simple_reversi::engine::Level;
+use simple_reversi::error::{
+    BookError, GameFileError, ReplayError, ReversiError, SgfError, WeightsError, WthorError,
+};
+
+use crate::cli::parsed_cli;
+
+/// 表示に使う言語
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Lang {
+    /// 日本語
+    Ja,
+    /// 英語
+    En,
+}
+
+impl Lang {
+    /// 環境変数```LANG```の値から決める
+    ///
+    /// 設定されていないか，```ja```で始まるか，```C```や```POSIX```なら日本語，それ以外なら英語．
+    pub fn from_env() -> Lang {
+        match std::env::var("LANG") {
+            Ok(v) if !v.is_empty() && !v.starts_with("ja") && !Lang::is_posix_locale(&v) => {
+                Lang::En
+            }
+            _ => Lang::Ja,
+        }
+    }
+
+    /// 解析する前のコマンドライン引数の```--lang```の値（なければNone）
+    fn from_args() -> Option<Lang> {
+        let args: Vec<String> = std::env::args().collect();
+        let value = args
+            .iter()
+            .enumerate()
+            .find_map(|(i, a)| match a.strip_prefix("--lang") {
+                Some("") => args.get(i + 1).map(String::as_str),
+                Some(v) => v.strip_prefix('='),
+                None => None,
+            })?;
+        Lang::from_str(value, true).ok()
+    }
+
+    /// ```C```，```C.UTF-8```，```POSIX```のような，言語を指定しないロケールかどうか
+    fn is_posix_locale(v: &str) -> bool {
+        let name = v.split('.').next().unwrap_or(v);
+        name == "C" || name == "POSIX"
+    }
+}
+
+/// 表示するメッセージの一覧
+///
+/// 言語ごとに実装し，```msg```で```--lang```（指定しなければ環境変数```LANG```）に合ったものを取り出す．
+pub trait Messages: Sync {
+    /// 強さの表示用の名前
+    fn level_name(&self, level: Level) -> &'static str;
+
+    // 入力の誤り
+    fn not_int(&self) -> &'static str;
+    fn invalid_input(&self) -> &'static str;
+    fn out_of_range(&self) -> &'static str;
+
+    // 対局中の表示
+    fn turn(&self, player: &str) -> String;
+    fn move_header(&self, number: usize, empties: usize) -> String;
+    fn pass(&self, player: char, opponent: char) -> String;
+    fn cpu_thinking(&self) -> &'static str;
+    fn cpu_resigns(&self, piece: char) -> String;
+    fn cannot_place(&self) -> &'static str;
+    fn resigned(&self, loser: &str, winner: &str) -> String;
+    fn timed_out(&self, loser: &str, winner: &str) -> String;
+    fn move_timeout_random(&self, mv: &dyn Display) -> String;
+    fn clock(&self, black: char, black_time: &str, white: char, white_time: &str) -> String;
+    fn result(&self, c1: &str, s1: usize, c2: &str, s2: usize) -> String;
+
+    // 評価の表示
+    fn score_exact(&self, difference: i32) -> String;
+    fn score_eval(&self, depth: u32, score: i32) -> String;
+    fn with_win_chance(&self, description: &str, percent: f64) -> String;
+    fn hint(&self, mv: &dyn Display, description: &str) -> String;
+    fn analysis_line(&self, rank: usize, mv: &dyn Display, description: &str) -> String;
+    fn review_header(&self, threshold: i32) -> String;
+    fn review_line(
+        &self,
+        mark: &str,
+        number: usize,
+        played: &dyn Display,
+        played_description: &str,
+        best: &dyn Display,
+        best_description: &str,
+    ) -> String;
+    fn blunder_count(&self, blunders: usize) -> String;
+    fn annotation(
+        &self,
+        played_description: &str,
+        best: &dyn Display,
+        best_description: &str,
+    ) -> String;
+    fn cpu_win_estimate(&self, percent: f64) -> String;
+    fn search_info(&self, depth: u32, score: i32, nodes: u64, nps: u64, pv: &str) -> String;
+    fn weights_fallback(&self, error: &dyn Display) -> String;
+    fn config_failed(&self, error: &str) -> String;
+    fn config_saved(&self, path: &dyn Display) -> String;
+    fn config_save_failed(&self, path: &dyn Display, error: &dyn Display) -> String;
+
+    // 対局前の入力
+    fn welcome(&self) -> &'static str;
+    fn ask_recover(&self) -> &'static str;
+    fn ask_size(&self) -> &'static str;
+    fn ask_mode(&self) -> &'static str;
+    fn ask_color(&self, black: char, white: char) -> String;
+    fn ask_level(&self) -> &'static str;
+    fn ask_search(&self) -> &'static str;
+    fn search_alpha_beta(&self) -> &'static str;
+    fn search_mcts(&self) -> &'static str;
+    fn ask_adaptive(&self) -> &'static str;
+    fn ask_name(&self, piece: char) -> String;
+    fn named(&self, name: &str, piece: char) -> String;
+
+    // 自分の番の操作方法
+    fn prompt_move(&self) -> &'static str;
+    fn your_turn(&self, piece: char) -> String;
+    fn command_help(&self) -> &'static str;
+    fn rules(&self) -> &'static str;
+    fn unknown_command(&self) -> &'static str;
+    fn history_empty(&self) -> &'static str;
+    fn transcript_failed(&self, error: &dyn Display) -> String;
+    fn score(&self, black: char, black_count: usize, white: char, white_count: usize) -> String;
+    fn mobility(&self, black: &str, black_moves: usize, white: &str, white_moves: usize) -> String;
+    fn saved(&self, path: &str) -> String;
+    fn save_failed(&self, path: &str, error: &dyn Display) -> String;
+    fn image_written(&self, path: &str) -> String;
+    fn image_failed(&self, path: &str, error: &dyn Display) -> String;
+    fn confirm_resign(&self) -> &'static str;
+    fn confirm_move(&self, flips: usize) -> String;
+    fn confirm_undo(&self) -> &'static str;
+    fn cannot_undo(&self) -> &'static str;
+    fn takeback_request(&self, requester: &str, opponent: &str) -> String;
+    fn takeback_declined(&self, opponent: &str) -> String;
+    fn undo_limit(&self, used: usize) -> String;
+    fn ask_interrupt(&self, path: &str) -> String;
+    fn describe_last_move(&self, piece: char, square: Coord, flipped: &[Coord]) -> String;
+    fn describe_legal_moves(&self, squares: &[Coord]) -> String;
+    fn describe_mode(&self, on: bool) -> &'static str;
+    fn board_cropped(&self, first: &str, last: &str) -> String;
+
+    // 対局後
+    fn ask_review(&self) -> &'static str;
+    fn level_changed(&self, from: &str, to: &str) -> String;
+    fn ask_rematch(&self) -> &'static str;
+    fn you(&self) -> &'static str;
+    fn player_number(&self, number: usize) -> String;
+    fn match_intro(&self, games: u32) -> String;
+    fn series_score(&self, a: &str, a_wins: usize, b: &str, b_wins: usize) -> String;
+    fn match_winner(&self, winner: &str, wins: usize, losses: usize) -> String;
+    fn match_drawn(&self) -> &'static str;
+
+    // 局面の編集（--edit）
+    fn editor_help(&self) -> &'static str;
+    fn editor_error(&self, error: &dyn Display) -> String;
+    fn editor_game_over(&self) -> &'static str;
+
+    // 全画面表示（--tui）
+    #[cfg(feature = "tui")]
+    fn tui_board_title(&self) -> &'static str;
+    #[cfg(feature = "tui")]
+    fn tui_history_title(&self) -> &'static str;
+    #[cfg(feature = "tui")]
+    fn tui_keys(&self) -> &'static str;
+
+    // サブコマンド
+    fn mcts_name(&self, level: &str) -> String;
+    fn tournament_intro(&self, size: usize) -> String;
+    fn crosstable_total(&self) -> &'static str;
+    fn tournament_game(
+        &self,
+        black_name: &str,
+        black: char,
+        white_name: &str,
+        white: char,
+        black_count: usize,
+        white_count: usize,
+    ) -> String;
+    fn bench_intro(&self, depth: u32) -> String;
+    fn bench_line(&self, ply: usize, nodes: [u64; 2], seconds: [f64; 2]) -> String;
+    fn bench_total(&self, before: u64, after: u64, percent: f64) -> String;
+    fn tune_intro(&self, size: usize, iterations: usize) -> String;
+    fn tune_trial(&self, iteration: usize, percent: f64, accepted: bool) -> String;
+    fn tune_done(&self, output: &str) -> String;
+    fn calibrate_intro(&self, size: usize) -> String;
+    fn calibrate_done(&self, samples: usize, scale: f64) -> String;
+    fn replay_load_failed(&self, path: &str, error: &dyn Display) -> String;
+    fn position_failed(&self, path: &str, error: &dyn Display) -> String;
+    fn replay_position(&self, number: usize, total: usize) -> String;
+    fn replay_prompt(&self) -> &'static str;
+    fn wthor_game(
+        &self,
+        number: usize,
+        year: u16,
+        tournament: &str,
+        black: &str,
+        white: &str,
+        discs: (u8, u8),
+    ) -> String;
+    fn wthor_no_game(&self, number: usize, total: usize) -> String;
+    fn archive_empty(&self) -> &'static str;
+    fn archive_line(
+        &self,
+        id: usize,
+        date: &str,
+        opponent: &str,
+        size: usize,
+        result: &str,
+    ) -> String;
+    fn archive_no_game(&self, id: usize, total: usize) -> String;
+    fn archive_failed(&self, error: &dyn Display) -> String;
+    fn opponent_cpu(&self, level: Level) -> String;
+    fn opponent_watch(&self) -> &'static str;
+    fn book_written(&self, path: &str, games: usize) -> String;
+    fn csv_failed(&self, path: &str, error: &dyn Display) -> String;
+    fn quiet_not_allowed(&self) -> &'static str;
+    fn script_failed(&self, number: usize, word: &str, error: &dyn Display) -> String;
+
+    // コマンドラインの引数の誤り
+    fn invalid_size_arg(&self) -> &'static str;
+    fn pieces_one_char(&self) -> &'static str;
+    fn pieces_three(&self) -> &'static str;
+    fn pieces_distinct(&self, blocked: char) -> String;
+    fn pieces_width(&self) -> &'static str;
+    fn invalid_probability(&self) -> &'static str;
+    fn not_positive(&self) -> &'static str;
+    fn time_too_long(&self) -> &'static str;
+
+    // ライブラリのエラー（種類と値だけを持つので，ここで文章にする）
+    fn reversi_error(&self, error: &ReversiError) -> String;
+    fn replay_error(&self, error: &ReplayError) -> String;
+    fn game_file_error(&self, error: &GameFileError) -> String;
+    fn sgf_error(&self, error: &SgfError) -> String;
+    fn wthor_error(&self, error: &WthorError) -> String;
+    fn book_error(&self, error: &BookError) -> String;
+    fn weights_error(&self, error: &WeightsError) -> String;
+    fn invalid_value(&self, key: &str) -> String;
+}
+
+/// 日本語のメッセージ
+pub struct Japanese;
+
+/// 英語のメッセージ
+pub struct English;
+
+/// 使う言語のメッセージ（コマンドライン引数を解析し終えてから最初に呼んだときに言語を決める）
+///
+/// 引数を解析している途中（```value_parser```の中など）では，```--lang```の引数か環境変数で決める．
+pub fn msg() -> &'static dyn Messages {
+    static LANG: OnceLock<Lang> = OnceLock::new();
+    let lang = match LANG.get() {
+        Some(&lang) => lang,
+        None => match parsed_cli() {
+            Some(cli) => *LANG.get_or_init(|| cli.lang.unwrap_or_else(Lang::from_env)),
+            None => Lang::from_args().unwrap_or_else(Lang::from_env),
+        },
+    };
+    match lang {
+        Lang::Ja => &Japanese,
+        Lang::En => &English,
+    }
+}
+
+/// マス目の一覧をsepで区切って並べる
+fn join(squares: &[Coord], sep: &str) -> String {
+    squares
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>()
+        .join(sep)
+}
+
+impl Messages for Japanese {
+    fn level_name(&self, level: Level) -> &'static str {
+        match level {
+            Level::Greedy => "よくばり",
+            Level::Easy => "かんたん",
+            Level::Moderate => "そこそこ",
+            Level::Normal => "ふつう",
+            Level::Hard => "むずかしい",
+            Level::Expert => "達人",
+        }
+    }
+
+    fn not_int(&self) -> &'static str {
+        "半角数字で整数を入力してください．"
+    }
+    fn invalid_input(&self) -> &'static str {
+        "入力が不適切です．"
+    }
+    fn out_of_range(&self) -> &'static str {
+        "入力が範囲外です．"
+    }
+
+    fn turn(&self, player: &str) -> String {
+        format!("{}のターン．", player)
+    }
+    fn move_header(&self, number: usize, empties: usize) -> String {
+        format!("{}手目（残り{}マス）", number, empties)
+    }
+    fn pass(&self, player: char, opponent: char) -> String {
+        format!(
+            "{0}は置けるマスがないのでパスします．{1}の番が続きます．",
+            player, opponent
+        )
+    }
+    fn cpu_thinking(&self) -> &'static str {
+        "\nCPU操作中...\n"
+    }
+    fn cpu_resigns(&self, piece: char) -> String {
+        format!("{}は勝ち目がないので投了します．", piece)
+    }
+    fn cannot_place(&self) -> &'static str {
+        "そこには置けません．"
+    }
+    fn resigned(&self, loser: &str, winner: &str) -> String {
+        format!("{0}が投了したので{1}の勝ち！", loser, winner)
+    }
+    fn timed_out(&self, loser: &str, winner: &str) -> String {
+        format!("{0}の持ち時間が切れたので{1}の勝ち！", loser, winner)
+    }
+    fn move_timeout_random(&self, mv: &dyn Display) -> String {
+        format!("時間切れなので{}に打ちます．", mv)
+    }
+    fn clock(&self, black: char, black_time: &str, white: char, white_time: &str) -> String {
+        format!(
+            "残り時間 {}: {}  {}: {}",
+            black, black_time, white, white_time
+        )
+    }
+    fn result(&self, c1: &str, s1: usize, c2: &str, s2: usize) -> String {
+        if s1 > s2 {
+            format!("{0}が{1}個，{2}が{3}個で{0}の勝ち！", c1, s1, c2, s2)
+        } else if s1 < s2 {
+            format!("{0}が{1}個，{2}が{3}個で{2}の勝ち！", c1, s1, c2, s2)
+        } else {
+            format!("{0}が{1}個，{2}が{3}個で引き分け！", c1, s1, c2, s2)
+        }
+    }
+
+    fn score_exact(&self, difference: i32) -> String {
+        format!("終局まで読んで{:+}個差", difference)
+    }
+    fn score_eval(&self, depth: u32, score: i32) -> String {
+        format!("{}手先まで読んで評価値{:+}", depth, score)
+    }
+    fn with_win_chance(&self, description: &str, percent: f64) -> String {
+        format!("{}，勝つ確率{:.0}%", description, percent)
+    }
+    fn hint(&self, mv: &dyn Display, description: &str) -> String {
+        format!("おすすめの手: {}（{}）", mv, description)
+    }
+    fn analysis_line(&self, rank: usize, mv: &dyn Display, description: &str) -> String {
+        format!("{:2}. {}（{}）", rank, mv, description)
+    }
+    fn review_header(&self, threshold: i32) -> String {
+        format!(
+            "振り返り（評価値が{}以上下がった手に!をつけます）",
+            threshold
+        )
+    }
+    fn review_line(
+        &self,
+        mark: &str,
+        number: usize,
+        played: &dyn Display,
+        played_description: &str,
+        best: &dyn Display,
+        best_description: &str,
+    ) -> String {
+        format!(
+            "{} {:3}手目 {}（{}） CPUの候補: {}（{}）",
+            mark, number, played, played_description, best, best_description
+        )
+    }
+    fn blunder_count(&self, blunders: usize) -> String {
+        format!("悪手は{}手でした．", blunders)
+    }
+    fn annotation(
+        &self,
+        played_description: &str,
+        best: &dyn Display,
+        best_description: &str,
+    ) -> String {
+        format!(
+            "評価: {}，CPUの候補: {}（{}）",
+            played_description, best, best_description
+        )
+    }
+    fn cpu_win_estimate(&self, percent: f64) -> String {
+        format!("CPUは自分が勝つ確率を{:.0}%とみています．", percent)
+    }
+    fn search_info(&self, depth: u32, score: i32, nodes: u64, nps: u64, pv: &str) -> String {
+        format!(
+            "深さ{:2} 評価値{:+7} {:9}局面 {:8}局面/秒 読み筋: {}",
+            depth, score, nodes, nps, pv
+        )
+    }
+    fn weights_fallback(&self, error: &dyn Display) -> String {
+        format!("{}（既定の重みを使います）", error)
+    }
+    fn config_failed(&self, error: &str) -> String {
+        format!(
+            "設定ファイルを読み込めません: {}（設定ファイルを使わずに続けます）",
+            error
+        )
+    }
+    fn config_saved(&self, path: &dyn Display) -> String {
+        format!("設定を{}に保存しました．", path)
+    }
+    fn config_save_failed(&self, path: &dyn Display, error: &dyn Display) -> String {
+        format!("設定を{}に保存できません: {}", path, error)
+    }
+
+    fn welcome(&self) -> &'static str {
+        "オセロをします．"
+    }
+    fn ask_recover(&self) -> &'static str {
+        "前回の対局が途中で終了したようです．yで続きから再開し，それ以外なら破棄します．"
+    }
+    fn ask_size(&self) -> &'static str {
+        "盤面のサイズを4以上の偶数で入力してください．Returnキーで確定します．"
+    }
+    fn ask_mode(&self) -> &'static str {
+        "CPUと戦う場合は1，CPUだけが操作しているのを見る場合は2，自分で両方を操作する場合はそれ以外を入力してください．"
+    }
+    fn ask_color(&self, black: char, white: char) -> String {
+        format!(
+            "{0}として始める場合は1を，{1}として始める場合は2を入力してください．{0}が先攻です．",
+            black, white
+        )
+    }
+    fn ask_level(&self) -> &'static str {
+        "CPUの強さを選んでください．"
+    }
+    fn ask_search(&self) -> &'static str {
+        "CPUの先読みの方法を選んでください．"
+    }
+    fn search_alpha_beta(&self) -> &'static str {
+        "αβ法"
+    }
+    fn search_mcts(&self) -> &'static str {
+        "モンテカルロ木探索（大きな盤面向け）"
+    }
+    fn ask_adaptive(&self) -> &'static str {
+        "対局ごとにCPUの強さを自動で調整する場合は1を，しない場合はそれ以外を入力してください．"
+    }
+    fn ask_name(&self, piece: char) -> String {
+        format!(
+            "{}の対局者の名前を入力してください（名前をつけない場合は何も入力しないでください）．",
+            piece
+        )
+    }
+    fn named(&self, name: &str, piece: char) -> String {
+        format!("{}（{}）", name, piece)
+    }
+
+    fn prompt_move(&self) -> &'static str {
+        "駒を置くマス目（d3のような列の英字と行番号か，3 4のような行番号と列番号）かコマンドを入力してください．helpでコマンドの一覧を表示します．"
+    }
+    fn your_turn(&self, piece: char) -> String {
+        format!("あなた（{}）の番です．", piece)
+    }
+    fn command_help(&self) -> &'static str {
+        "d3 / 3 4      そのマス目に駒を置く
+help (?)      コマンドの一覧を表示する
+rules         ルールとマス目の指定のしかたを表示する
+hint (h)      CPUのおすすめの手を表示する
+moves (m)     駒を置けるマス目に印をつけて表示する
+analyze (a)   置ける全てのマス目の評価を表示する（analyze boardかabで盤面にも表示する）
+history       これまでの棋譜を表示する
+transcript    棋譜をF5D6C3のような1行の形式で表示する
+position      今の局面を8/8/8/3o#3/3#o3/8/8/8 b 8のような1行の形式で表示する
+score         今の駒の個数を表示する
+describe      盤面を文章で表示するかどうかを切り替える
+view d3       盤面が端末に収まらないときに，そのマス目の周りを表示する
+save [file]   棋譜と設定をファイルに保存する（既定はgame.txt．.jsonか.sgfで終わればその形式で保存する．--resumeで再開できる）
+image [file] [numbers]
+              今の盤面を画像に書き出す（既定はboard.svg．.pngで終わればPNG．numbersで駒に手数を重ねる）
+undo (u)      待ったする（2人で対局するときは相手が認めたら）
+quit (q)      投了する"
+    }
+    fn rules(&self) -> &'static str {
+        "【ルール】
+黒（先手）と白が交互に駒を置きます．駒は，相手の駒を縦・横・斜めのいずれかの向きに
+自分の駒で挟めるマス目にだけ置けて，挟んだ相手の駒は全て自分の色に裏返ります．
+置けるマス目がない場合はパスになり，相手の番が続きます．
+両者とも置けなくなったら終局で，駒が多い方の勝ちです．投了したり持ち時間が切れたりした側は負けです．
+
+【マス目の指定のしかた】
+列は左から英字（a，b，c，…），行は上から数字（1，2，3，…）で表します．
+d3のように列の英字と行番号で入力するか，3 4や3-4のように行番号と列番号で入力します．
+全角の文字で入力してもかまいません．
+
+【コマンド】"
+    }
+    fn unknown_command(&self) -> &'static str {
+        "入力が読めません．helpでコマンドの一覧を表示します．"
+    }
+    fn history_empty(&self) -> &'static str {
+        "まだ手が打たれていません．"
+    }
+    fn transcript_failed(&self, error: &dyn Display) -> String {
+        format!("--from-transcriptの棋譜を読めません: {}", error)
+    }
+    fn score(&self, black: char, black_count: usize, white: char, white_count: usize) -> String {
+        format!("{}が{}個，{}が{}個", black, black_count, white, white_count)
+    }
+    fn mobility(&self, black: &str, black_moves: usize, white: &str, white_moves: usize) -> String {
+        format!(
+            "置けるマス目: {}は{}か所，{}は{}か所",
+            black, black_moves, white, white_moves
+        )
+    }
+    fn saved(&self, path: &str) -> String {
+        format!("棋譜を{}に保存しました．", path)
+    }
+    fn save_failed(&self, path: &str, error: &dyn Display) -> String {
+        format!("棋譜を{}に保存できません: {}", path, error)
+    }
+    fn image_written(&self, path: &str) -> String {
+        format!("盤面の画像を{}に書き出しました．", path)
+    }
+    fn image_failed(&self, path: &str, error: &dyn Display) -> String {
+        format!("盤面の画像を{}に書き出せません: {}", path, error)
+    }
+    fn confirm_resign(&self) -> &'static str {
+        "本当に投了しますか？はいならy，いいえならそれ以外を入力してください．"
+    }
+    fn confirm_move(&self, flips: usize) -> String {
+        format!(
+            "@に置くと*の{}個の駒が裏返ります．この手を打ちますか？はいならy，いいえならそれ以外を入力してください．",
+            flips
+        )
+    }
+    fn confirm_undo(&self) -> &'static str {
+        "直前の自分の手を取り消しますか？はいならy，いいえならそれ以外を入力してください．"
+    }
+    fn cannot_undo(&self) -> &'static str {
+        "取り消せる手がありません．"
+    }
+    fn takeback_request(&self, requester: &str, opponent: &str) -> String {
+        format!(
+            "{0}が直前の自分の手の待ったを申し出ています．{1}は認めますか？認めるならy，認めないならそれ以外を入力してください．",
+            requester, opponent
+        )
+    }
+    fn takeback_declined(&self, opponent: &str) -> String {
+        format!("{}が待ったを認めませんでした．", opponent)
+    }
+    fn undo_limit(&self, used: usize) -> String {
+        format!("待ったはもう{}回使ったので，これ以上できません．", used)
+    }
+    fn ask_interrupt(&self, path: &str) -> String {
+        format!(
+            "対局を中断しますか？sで棋譜を{}に保存して終了，qで保存せずに終了，それ以外で対局を続けます．",
+            path
+        )
+    }
+    fn describe_last_move(&self, piece: char, square: Coord, flipped: &[Coord]) -> String {
+        format!(
+            "直前の手：{}が{}に置いて，{}を裏返しました．",
+            piece,
+            square,
+            join(flipped, "，")
+        )
+    }
+    fn describe_legal_moves(&self, squares: &[Coord]) -> String {
+        format!("置けるマス目：{}", join(squares, "，"))
+    }
+    fn describe_mode(&self, on: bool) -> &'static str {
+        if on {
+            "これから盤面を文章で表示します．"
+        } else {
+            "これから盤面を表示します．"
+        }
+    }
+    fn board_cropped(&self, first: &str, last: &str) -> String {
+        format!(
+            "盤面が端末に収まらないので，{}列から{}列までを表示します（view d3のように入力すると，そのマス目の周りを表示します）．",
+            first, last
+        )
+    }
+
+    fn ask_review(&self) -> &'static str {
+        "対局の振り返りを表示しますか？はいならy，いいえならそれ以外を入力してください．"
+    }
+    fn level_changed(&self, from: &str, to: &str) -> String {
+        format!("CPUの強さを「{}」から「{}」に変更します．", from, to)
+    }
+    fn ask_rematch(&self) -> &'static str {
+        "色を入れ替えてもう一度対局しますか？はいならy，いいえならそれ以外を入力してください．"
+    }
+    fn you(&self) -> &'static str {
+        "あなた"
+    }
+    fn player_number(&self, number: usize) -> String {
+        format!("対局者{}", number)
+    }
+    fn match_intro(&self, games: u32) -> String {
+        format!(
+            "{}番勝負です（{}勝した方の勝ち．1局ごとに色を入れ替えます）．",
+            games,
+            games / 2 + 1
+        )
+    }
+    fn series_score(&self, a: &str, a_wins: usize, b: &str, b_wins: usize) -> String {
+        format!("対戦成績：{}が{}勝，{}が{}勝", a, a_wins, b, b_wins)
+    }
+    fn match_winner(&self, winner: &str, wins: usize, losses: usize) -> String {
+        format!("{}が{}勝{}敗でこの勝負に勝ちました！", winner, wins, losses)
+    }
+    fn match_drawn(&self) -> &'static str {
+        "この勝負は引き分けです！"
+    }
+
+    #[cfg(feature = "tui")]
+    fn tui_board_title(&self) -> &'static str {
+        "盤面"
+    }
+    #[cfg(feature = "tui")]
+    fn tui_history_title(&self) -> &'static str {
+        "棋譜（新しい順）"
+    }
+    #[cfg(feature = "tui")]
+    fn tui_keys(&self) -> &'static str {
+        "矢印キー：マス目を選ぶ\nEnter：駒を置く\nh：おすすめの手\nu：待った\nq：投了"
+    }
+
+    fn mcts_name(&self, level: &str) -> String {
+        format!("{}（MCTS）", level)
+    }
+    fn tournament_intro(&self, size: usize) -> String {
+        format!("{}×{}の盤面で総当たり戦をします．", size, size)
+    }
+    fn crosstable_total(&self) -> &'static str {
+        "合計"
+    }
+    fn tournament_game(
+        &self,
+        black_name: &str,
+        black: char,
+        white_name: &str,
+        white: char,
+        black_count: usize,
+        white_count: usize,
+    ) -> String {
+        format!(
+            "{}（{}）対 {}（{}）: {}が{}個，{}が{}個",
+            black_name, black, white_name, white, black, black_count, white, white_count
+        )
+    }
+    fn bench_intro(&self, depth: u32) -> String {
+        format!("{}手先まで読んだときの調べた局面の数を比べます．", depth)
+    }
+    fn bench_line(&self, ply: usize, nodes: [u64; 2], seconds: [f64; 2]) -> String {
+        format!(
+            "{}手目: 工夫なし {}局面（{:.2}秒），工夫あり {}局面（{:.2}秒）",
+            ply, nodes[0], seconds[0], nodes[1], seconds[1]
+        )
+    }
+    fn bench_total(&self, before: u64, after: u64, percent: f64) -> String {
+        format!("合計: {}局面 → {}局面（{:.1}%）", before, after, percent)
+    }
+    fn tune_intro(&self, size: usize, iterations: usize) -> String {
+        format!(
+            "{}×{}の盤面で，自己対局で重みを{}回調整します．",
+            size, size, iterations
+        )
+    }
+    fn tune_trial(&self, iteration: usize, percent: f64, accepted: bool) -> String {
+        format!(
+            "{:3}回目: 候補の勝率{:.0}%{}",
+            iteration,
+            percent,
+            if accepted { "（採用）" } else { "" }
+        )
+    }
+    fn tune_done(&self, output: &str) -> String {
+        format!("調整した重みは{}に書き込まれています．", output)
+    }
+    fn calibrate_intro(&self, size: usize) -> String {
+        format!("{}×{}の盤面で自己対局をしています．", size, size)
+    }
+    fn calibrate_done(&self, samples: usize, scale: f64) -> String {
+        format!(
+            "{}局面から求めた尺度は{}です（--win-scale {}で使えます）．",
+            samples, scale, scale
+        )
+    }
+    fn replay_load_failed(&self, path: &str, error: &dyn Display) -> String {
+        format!("{}を棋譜として読み込めません: {}", path, error)
+    }
+    fn position_failed(&self, path: &str, error: &dyn Display) -> String {
+        format!("{}を局面として読み込めません: {}", path, error)
+    }
+    fn replay_position(&self, number: usize, total: usize) -> String {
+        format!("{}手目まで（全{}手）", number, total)
+    }
+    fn replay_prompt(&self) -> &'static str {
+        "n（またはEnter）で1手進める，pで1手戻す，数字でその手数まで移動，qで終了します．"
+    }
+    fn wthor_game(
+        &self,
+        number: usize,
+        year: u16,
+        tournament: &str,
+        black: &str,
+        white: &str,
+        discs: (u8, u8),
+    ) -> String {
+        format!(
+            "{}. {}年 {}：{}（黒）{} - {} {}（白）",
+            number, year, tournament, black, discs.0, discs.1, white
+        )
+    }
+    fn wthor_no_game(&self, number: usize, total: usize) -> String {
+        format!("{}局目はありません（全{}局）．", number, total)
+    }
+    fn archive_empty(&self) -> &'static str {
+        "記録した対局はありません．"
+    }
+    fn archive_line(
+        &self,
+        id: usize,
+        date: &str,
+        opponent: &str,
+        size: usize,
+        result: &str,
+    ) -> String {
+        format!(
+            "{:3}. {}（UTC） 相手: {} {}×{} {}",
+            id, date, opponent, size, size, result
+        )
+    }
+    fn archive_no_game(&self, id: usize, total: usize) -> String {
+        format!("{}番の対局はありません（記録は{}局）．", id, total)
+    }
+    fn archive_failed(&self, error: &dyn Display) -> String {
+        format!("対局を記録できませんでした: {}", error)
+    }
+    fn opponent_cpu(&self, level: Level) -> String {
+        format!("CPU（{}）", self.level_name(level))
+    }
+    fn opponent_watch(&self) -> &'static str {
+        "CPU同士"
+    }
+    fn book_written(&self, path: &str, games: usize) -> String {
+        format!("{}局の序盤を定石集として{}に書き込みました．", games, path)
+    }
+    fn csv_failed(&self, path: &str, error: &dyn Display) -> String {
+        format!("{}に結果を書き込めませんでした: {}", path, error)
+    }
+    fn quiet_not_allowed(&self) -> &'static str {
+        "--quietはscriptサブコマンドか--watchと一緒にしか使えません．"
+    }
+    fn script_failed(&self, number: usize, word: &str, error: &dyn Display) -> String {
+        format!("{}手目の{}を打てません: {}", number, word, error)
+    }
+
+    fn editor_help(&self) -> &'static str {
+        "対局を始める局面を編集します．
+b d3 / w d3   そのマス目に黒／白の駒を置く
+e d3          そのマス目を空にする
+x d3          そのマス目を塞ぐ
+turn b / w    黒／白の番にする
+clear         全てのマス目を空にする
+reset         最初の配置に戻す
+load file     ファイルから盤面を読み込む（.o#xの文字を1行ずつ並べたもの）
+help (?)      コマンドの一覧を表示する
+done          この局面から対局を始める"
+    }
+    fn editor_error(&self, error: &dyn Display) -> String {
+        format!("編集できません: {}", error)
+    }
+    fn editor_game_over(&self) -> &'static str {
+        "どちらも置けるマスがないので，この局面からは始められません．"
+    }
+
+    fn invalid_size_arg(&self) -> &'static str {
+        "4以上の偶数を指定してください"
+    }
+    fn pieces_one_char(&self) -> &'static str {
+        "黒，白，空きマスの文字を1文字ずつカンマ区切りで指定してください"
+    }
+    fn pieces_three(&self) -> &'static str {
+        "黒，白，空きマスの3つの文字を指定してください"
+    }
+    fn pieces_distinct(&self, blocked: char) -> String {
+        format!(
+            "文字は互いに異なり，塞いだマスの文字（{}）とも異なるものにしてください",
+            blocked
+        )
+    }
+    fn pieces_width(&self) -> &'static str {
+        "表示したときの幅が同じ文字を指定してください"
+    }
+    fn invalid_probability(&self) -> &'static str {
+        "0以上1以下の数を指定してください"
+    }
+    fn not_positive(&self) -> &'static str {
+        "正の数を指定してください"
+    }
+    fn time_too_long(&self) -> &'static str {
+        "時間が長すぎます"
+    }
+
+    fn reversi_error(&self, error: &ReversiError) -> String {
+        match error {
+            ReversiError::InvalidSize(n) => format!("盤面のサイズ{}は2以上の偶数ではありません", n),
+            ReversiError::RowLength { row, len, size } => format!(
+                "{}行目の長さが{}ですが，盤面のサイズは{}です",
+                row + 1,
+                len,
+                size
+            ),
+            ReversiError::UnknownChar { row, col, ch } => format!(
+                "{}行{}列目の文字'{}'は盤面に使えません",
+                row + 1,
+                col + 1,
+                ch
+            ),
+            ReversiError::EmptyCenter(c) => format!(
+                "中央の{}行{}列目に駒が置かれていません",
+                c.row + 1,
+                c.col + 1
+            ),
+            ReversiError::OutOfRange(c) => {
+                format!("{}行{}列目は盤面の外です", c.row + 1, c.col + 1)
+            }
+            ReversiError::BlockedSquare(c) => format!(
+                "{}行{}列目は塞がれているので駒を置けません",
+                c.row + 1,
+                c.col + 1
+            ),
+            ReversiError::IllegalMove(c) => {
+                format!("{}行{}列目には置けません", c.row + 1, c.col + 1)
+            }
+            ReversiError::IllegalPass => "置けるマスがあるのでパスできません".to_string(),
+            ReversiError::GameOver => "もう終局しています".to_string(),
+            ReversiError::UnknownMove(s) => format!("'{}'は手として読めません", s),
+            ReversiError::UnknownCoord(s) => format!("'{}'はマス目として読めません", s),
+            ReversiError::InvalidPosition(s) => format!("'{}'は局面として読めません", s),
+        }
+    }
+    fn replay_error(&self, error: &ReplayError) -> String {
+        format!(
+            "{}手目: {}",
+            error.index + 1,
+            self.reversi_error(&error.error)
+        )
+    }
+    fn game_file_error(&self, error: &GameFileError) -> String {
+        match error {
+            GameFileError::Io(e) => e.to_string(),
+            GameFileError::Json(e) => format!("JSONの書き方が正しくありません: {}", e),
+            GameFileError::Board(e) => {
+                format!("始めの局面が正しくありません: {}", self.reversi_error(e))
+            }
+            GameFileError::Moves(e) => self.replay_error(e),
+            GameFileError::Mismatch => "手順と保存された盤面が一致しません".to_string(),
+        }
+    }
+    fn sgf_error(&self, error: &SgfError) -> String {
+        match error {
+            SgfError::Syntax(pos) => format!("SGFの{}文字目の書き方が正しくありません", pos + 1),
+            SgfError::Property(key) => format!("SGFの{}の値が正しくありません", key),
+            SgfError::Board(e) => {
+                format!("始めの局面が正しくありません: {}", self.reversi_error(e))
+            }
+            SgfError::Moves(e) => self.replay_error(e),
+        }
+    }
+    fn wthor_error(&self, error: &WthorError) -> String {
+        match error {
+            WthorError::Io(e) => e.to_string(),
+            WthorError::Truncated => "ファイルが途中で終わっています".to_string(),
+            WthorError::Unsupported(n) => format!("盤面の大きさ{}の対局は読めません", n),
+            WthorError::Move { game, index } => {
+                format!("{}局目の{}手目が盤面の外です", game + 1, index + 1)
+            }
+        }
+    }
+    fn book_error(&self, error: &BookError) -> String {
+        match error {
+            BookError::Io(e) => format!("定石集を読み込めません: {}", e),
+            BookError::Line { line, error } => {
+                format!("定石集の{}行目の{}", line + 1, self.replay_error(error))
+            }
+        }
+    }
+    fn weights_error(&self, error: &WeightsError) -> String {
+        match error {
+            WeightsError::Io(e) => format!("重みのファイルを読み込めません: {}", e),
+            WeightsError::Parse(e) => format!("重みのファイルの書き方が正しくありません: {}", e),
+            WeightsError::OutOfRange { name, value } => {
+                format!("重み{}の値{}は範囲外です", name, value)
+            }
+            WeightsError::Asymmetric { row, col } => format!(
+                "マス目の重みの{}行{}列目と{}行{}列目が異なります",
+                row + 1,
+                col + 1,
+                col + 1,
+                row + 1
+            ),
+        }
+    }
+    fn invalid_value(&self, key: &str) -> String {
+        format!("{}の値が正しくありません", key)
+    }
+}
+
+impl Messages for English {
+    fn level_name(&self, level: Level) -> &'static str {
+        match level {
+            Level::Greedy => "Greedy",
+            Level::Easy => "Easy",
+            Level::Moderate => "Moderate",
+            Level::Normal => "Normal",
+            Level::Hard => "Hard",
+            Level::Expert => "Expert",
+        }
+    }
+
+    fn not_int(&self) -> &'static str {
+        "Please enter an integer."
+    }
+    fn invalid_input(&self) -> &'static str {
+        "Invalid input."
+    }
+    fn out_of_range(&self) -> &'static str {
+        "Input is out of range."
+    }
+
+    fn turn(&self, player: &str) -> String {
+        format!("{} to move.", player)
+    }
+    fn move_header(&self, number: usize, empties: usize) -> String {
+        let squares = if empties == 1 { "square" } else { "squares" };
+        format!("Move {} — {} {} remaining", number, empties, squares)
+    }
+    fn pass(&self, player: char, opponent: char) -> String {
+        format!(
+            "{0} has no legal move and passes. {1} moves again.",
+            player, opponent
+        )
+    }
+    fn cpu_thinking(&self) -> &'static str {
+        "\nCPU is thinking...\n"
+    }
+    fn cpu_resigns(&self, piece: char) -> String {
+        format!("{} sees no way to win and resigns.", piece)
+    }
+    fn cannot_place(&self) -> &'static str {
+        "You cannot place a disc there."
+    }
+    fn resigned(&self, loser: &str, winner: &str) -> String {
+        format!("{0} resigned, so {1} wins!", loser, winner)
+    }
+    fn timed_out(&self, loser: &str, winner: &str) -> String {
+        format!("{0} ran out of time, so {1} wins!", loser, winner)
+    }
+    fn move_timeout_random(&self, mv: &dyn Display) -> String {
+        format!("Time is up, so {} is played at random.", mv)
+    }
+    fn clock(&self, black: char, black_time: &str, white: char, white_time: &str) -> String {
+        format!(
+            "Time left {}: {}  {}: {}",
+            black, black_time, white, white_time
+        )
+    }
+    fn result(&self, c1: &str, s1: usize, c2: &str, s2: usize) -> String {
+        if s1 > s2 {
+            format!("{0}: {1}, {2}: {3}. {0} wins!", c1, s1, c2, s2)
+        } else if s1 < s2 {
+            format!("{0}: {1}, {2}: {3}. {2} wins!", c1, s1, c2, s2)
+        } else {
+            format!("{0}: {1}, {2}: {3}. It's a draw!", c1, s1, c2, s2)
+        }
+    }
+
+    fn score_exact(&self, difference: i32) -> String {
+        format!("solved to the end, {:+} discs", difference)
+    }
+    fn score_eval(&self, depth: u32, score: i32) -> String {
+        format!("depth {}, score {:+}", depth, score)
+    }
+    fn with_win_chance(&self, description: &str, percent: f64) -> String {
+        format!("{}, {:.0}% to win", description, percent)
+    }
+    fn hint(&self, mv: &dyn Display, description: &str) -> String {
+        format!("Suggested move: {} ({})", mv, description)
+    }
+    fn analysis_line(&self, rank: usize, mv: &dyn Display, description: &str) -> String {
+        format!("{:2}. {} ({})", rank, mv, description)
+    }
+    fn review_header(&self, threshold: i32) -> String {
+        format!(
+            "Review (moves that lost {} or more points are marked with !)",
+            threshold
+        )
+    }
+    fn review_line(
+        &self,
+        mark: &str,
+        number: usize,
+        played: &dyn Display,
+        played_description: &str,
+        best: &dyn Display,
+        best_description: &str,
+    ) -> String {
+        format!(
+            "{} move {:3}: {} ({}) CPU suggests: {} ({})",
+            mark, number, played, played_description, best, best_description
+        )
+    }
+    fn blunder_count(&self, blunders: usize) -> String {
+        format!("You made {} blunder(s).", blunders)
+    }
+    fn annotation(
+        &self,
+        played_description: &str,
+        best: &dyn Display,
+        best_description: &str,
+    ) -> String {
+        format!(
+            "Eval: {}; CPU suggests: {} ({})",
+            played_description, best, best_description
+        )
+    }
+    fn cpu_win_estimate(&self, percent: f64) -> String {
+        format!(
+            "The CPU estimates its chance of winning at {:.0}%.",
+            percent
+        )
+    }
+    fn search_info(&self, depth: u32, score: i32, nodes: u64, nps: u64, pv: &str) -> String {
+        format!(
+            "depth {:2} score {:+7} {:9} nodes {:8} nodes/s pv: {}",
+            depth, score, nodes, nps, pv
+        )
+    }
+    fn weights_fallback(&self, error: &dyn Display) -> String {
+        format!("{} (using the default weights)", error)
+    }
+    fn config_failed(&self, error: &str) -> String {
+        format!(
+            "Could not load the config file: {} (continuing without it)",
+            error
+        )
+    }
+    fn config_saved(&self, path: &dyn Display) -> String {
+        format!("Saved the settings to {}.", path)
+    }
+    fn config_save_failed(&self, path: &dyn Display, error: &dyn Display) -> String {
+        format!("Could not save the settings to {}: {}", path, error)
+    }
+
+    fn welcome(&self) -> &'static str {
+        "Let's play Reversi."
+    }
+    fn ask_recover(&self) -> &'static str {
+        "The previous game seems to have ended unexpectedly. Enter y to resume it, or anything else to discard it."
+    }
+    fn ask_size(&self) -> &'static str {
+        "Enter the board size (an even number of at least 4) and press Return."
+    }
+    fn ask_mode(&self) -> &'static str {
+        "Enter 1 to play against the CPU, 2 to watch the CPU play itself, or anything else to play both sides yourself."
+    }
+    fn ask_color(&self, black: char, white: char) -> String {
+        format!(
+            "Enter 1 to play as {0} or 2 to play as {1}. {0} moves first.",
+            black, white
+        )
+    }
+    fn ask_level(&self) -> &'static str {
+        "Choose the CPU strength."
+    }
+    fn ask_search(&self) -> &'static str {
+        "Choose how the CPU searches."
+    }
+    fn search_alpha_beta(&self) -> &'static str {
+        "Alpha-beta search"
+    }
+    fn search_mcts(&self) -> &'static str {
+        "Monte Carlo tree search (for large boards)"
+    }
+    fn ask_adaptive(&self) -> &'static str {
+        "Enter 1 to adjust the CPU strength after each game, or anything else to keep it."
+    }
+    fn ask_name(&self, piece: char) -> String {
+        format!(
+            "Enter the name of the player with {} (leave it empty for no name).",
+            piece
+        )
+    }
+    fn named(&self, name: &str, piece: char) -> String {
+        format!("{} ({})", name, piece)
+    }
+
+    fn prompt_move(&self) -> &'static str {
+        "Enter a square (a column letter and row number such as d3, or a row and column number such as 3 4) or a command. Enter help for a list of commands."
+    }
+    fn your_turn(&self, piece: char) -> String {
+        format!("It's your turn ({}).", piece)
+    }
+    fn command_help(&self) -> &'static str {
+        "d3 / 3 4      place a disc on that square
+help (?)      show this list of commands
+rules         show the rules and how to name squares
+hint (h)      show the CPU's suggested move
+moves (m)     mark the squares where you can place a disc
+analyze (a)   evaluate every legal move (analyze board or ab also marks them on the board)
+history       show the moves played so far
+transcript    show the moves on one line in the F5D6C3 format
+position      show the position on one line like 8/8/8/3o#3/3#o3/8/8/8 b 8
+score         show the current disc counts
+describe      switch between the board and a text description of it
+view d3       show the area around that square when the board does not fit
+save [file]   save the moves and settings to a file (game.txt by default, JSON or SGF if it ends in .json or .sgf; resume with --resume)
+image [file] [numbers]
+              write the board as an image (board.svg by default, PNG if it ends in .png; numbers overlays move numbers)
+undo (u)      take back your last move (if your opponent accepts, in a two-player game)
+quit (q)      resign"
+    }
+    fn rules(&self) -> &'static str {
+        "[Rules]
+Black (who moves first) and White take turns placing a disc. You may only place a disc
+where it traps one or more of your opponent's discs in a straight line (horizontally,
+vertically or diagonally) between it and another of your discs; every trapped disc is
+flipped to your color. If you have no such square you pass, and your opponent moves again.
+The game ends when neither side can move, and whoever has more discs wins.
+A player who resigns or runs out of time loses.
+
+[Naming squares]
+Columns are letters from the left (a, b, c, ...) and rows are numbers from the top (1, 2, 3, ...).
+Enter a column letter and a row number such as d3, or a row and a column number such as 3 4 or 3-4.
+Full-width characters are also accepted.
+
+[Commands]"
+    }
+    fn unknown_command(&self) -> &'static str {
+        "Could not read that. Enter help for a list of commands."
+    }
+    fn history_empty(&self) -> &'static str {
+        "No moves have been played yet."
+    }
+    fn transcript_failed(&self, error: &dyn Display) -> String {
+        format!("Could not read the --from-transcript moves: {}", error)
+    }
+    fn score(&self, black: char, black_count: usize, white: char, white_count: usize) -> String {
+        format!("{}: {}, {}: {}", black, black_count, white, white_count)
+    }
+    fn mobility(&self, black: &str, black_moves: usize, white: &str, white_moves: usize) -> String {
+        let moves = |n: usize| if n == 1 { "move" } else { "moves" };
+        format!(
+            "{}: {} {}, {}: {} {}",
+            black,
+            black_moves,
+            moves(black_moves),
+            white,
+            white_moves,
+            moves(white_moves)
+        )
+    }
+    fn saved(&self, path: &str) -> String {
+        format!("Saved the moves to {}.", path)
+    }
+    fn save_failed(&self, path: &str, error: &dyn Display) -> String {
+        format!("Could not save the moves to {}: {}", path, error)
+    }
+    fn image_written(&self, path: &str) -> String {
+        format!("Wrote the board image to {}.", path)
+    }
+    fn image_failed(&self, path: &str, error: &dyn Display) -> String {
+        format!("Could not write the board image to {}: {}", path, error)
+    }
+    fn confirm_resign(&self) -> &'static str {
+        "Do you really want to resign? Enter y for yes, or anything else for no."
+    }
+    fn confirm_move(&self, flips: usize) -> String {
+        format!(
+            "Placing a disc on @ flips the {} disc(s) marked *. Play this move? Enter y for yes, or anything else for no.",
+            flips
+        )
+    }
+    fn confirm_undo(&self) -> &'static str {
+        "Take back your last move? Enter y for yes, or anything else for no."
+    }
+    fn cannot_undo(&self) -> &'static str {
+        "There is no move to take back."
+    }
+    fn takeback_request(&self, requester: &str, opponent: &str) -> String {
+        format!(
+            "{0} asks to take back their last move. {1}, do you accept? Enter y for yes, or anything else for no.",
+            requester, opponent
+        )
+    }
+    fn takeback_declined(&self, opponent: &str) -> String {
+        format!("{} declined the takeback.", opponent)
+    }
+    fn undo_limit(&self, used: usize) -> String {
+        format!(
+            "You have already used {} undo(s) and cannot undo any more.",
+            used
+        )
+    }
+    fn ask_interrupt(&self, path: &str) -> String {
+        format!(
+            "Stop the game? Enter s to save it to {} and quit, q to quit without saving, or anything else to keep playing.",
+            path
+        )
+    }
+    fn describe_last_move(&self, piece: char, square: Coord, flipped: &[Coord]) -> String {
+        format!(
+            "Last move: {} played {} and flipped {}.",
+            piece,
+            square,
+            join(flipped, ", ")
+        )
+    }
+    fn describe_legal_moves(&self, squares: &[Coord]) -> String {
+        format!("Legal moves: {}", join(squares, ", "))
+    }
+    fn describe_mode(&self, on: bool) -> &'static str {
+        if on {
+            "The board will now be described in text."
+        } else {
+            "The board will now be drawn."
+        }
+    }
+    fn board_cropped(&self, first: &str, last: &str) -> String {
+        format!(
+            "The board does not fit in the terminal, so only columns {} to {} are shown (enter something like view d3 to show the area around that square).",
+            first, last
+        )
+    }
+
+    fn ask_review(&self) -> &'static str {
+        "Show a review of the game? Enter y for yes, or anything else for no."
+    }
+    fn level_changed(&self, from: &str, to: &str) -> String {
+        format!("Changing the CPU strength from \"{}\" to \"{}\".", from, to)
+    }
+    fn ask_rematch(&self) -> &'static str {
+        "Play again with colors swapped? Enter y for yes, or anything else for no."
+    }
+    fn you(&self) -> &'static str {
+        "You"
+    }
+    fn player_number(&self, number: usize) -> String {
+        format!("Player {}", number)
+    }
+    fn match_intro(&self, games: u32) -> String {
+        format!(
+            "Best of {}: the first to win {} games takes the match. Colors swap after each game.",
+            games,
+            games / 2 + 1
+        )
+    }
+    fn series_score(&self, a: &str, a_wins: usize, b: &str, b_wins: usize) -> String {
+        format!("Match score: {} {}, {} {}", a, a_wins, b, b_wins)
+    }
+    fn match_winner(&self, winner: &str, wins: usize, losses: usize) -> String {
+        format!("{} wins the match {}-{}!", winner, wins, losses)
+    }
+    fn match_drawn(&self) -> &'static str {
+        "The match is drawn!"
+    }
+
+    #[cfg(feature = "tui")]
+    fn tui_board_title(&self) -> &'static str {
+        "Board"
+    }
+    #[cfg(feature = "tui")]
+    fn tui_history_title(&self) -> &'static str {
+        "Moves (newest first)"
+    }
+    #[cfg(feature = "tui")]
+    fn tui_keys(&self) -> &'static str {
+        "Arrow keys: select a square\nEnter: place a disc\nh: hint\nu: undo\nq: resign"
+    }
+
+    fn mcts_name(&self, level: &str) -> String {
+        format!("{} (MCTS)", level)
+    }
+    fn tournament_intro(&self, size: usize) -> String {
+        format!("Running a round robin on a {}x{} board.", size, size)
+    }
+    fn crosstable_total(&self) -> &'static str {
+        "Total"
+    }
+    fn tournament_game(
+        &self,
+        black_name: &str,
+        black: char,
+        white_name: &str,
+        white: char,
+        black_count: usize,
+        white_count: usize,
+    ) -> String {
+        format!(
+            "{} ({}) vs {} ({}): {}: {}, {}: {}",
+            black_name, black, white_name, white, black, black_count, white, white_count
+        )
+    }
+    fn bench_intro(&self, depth: u32) -> String {
+        format!("Comparing the number of nodes searched at depth {}.", depth)
+    }
+    fn bench_line(&self, ply: usize, nodes: [u64; 2], seconds: [f64; 2]) -> String {
+        format!(
+            "ply {}: unordered {} nodes ({:.2}s), ordered {} nodes ({:.2}s)",
+            ply, nodes[0], seconds[0], nodes[1], seconds[1]
+        )
+    }
+    fn bench_total(&self, before: u64, after: u64, percent: f64) -> String {
+        format!(
+            "total: {} nodes -> {} nodes ({:.1}%)",
+            before, after, percent
+        )
+    }
+    fn tune_intro(&self, size: usize, iterations: usize) -> String {
+        format!(
+            "Tuning the weights by self-play on a {}x{} board for {} iterations.",
+            size, size, iterations
+        )
+    }
+    fn tune_trial(&self, iteration: usize, percent: f64, accepted: bool) -> String {
+        format!(
+            "iteration {:3}: candidate scored {:.0}%{}",
+            iteration,
+            percent,
+            if accepted { " (accepted)" } else { "" }
+        )
+    }
+    fn tune_done(&self, output: &str) -> String {
+        format!("The tuned weights have been written to {}.", output)
+    }
+    fn calibrate_intro(&self, size: usize) -> String {
+        format!("Playing self-play games on a {}x{} board.", size, size)
+    }
+    fn calibrate_done(&self, samples: usize, scale: f64) -> String {
+        format!(
+            "The scale fitted to {} positions is {} (use it with --win-scale {}).",
+            samples, scale, scale
+        )
+    }
+    fn replay_load_failed(&self, path: &str, error: &dyn Display) -> String {
+        format!("Could not load {} as a game record: {}", path, error)
+    }
+    fn position_failed(&self, path: &str, error: &dyn Display) -> String {
+        format!("Could not load {} as a position: {}", path, error)
+    }
+    fn replay_position(&self, number: usize, total: usize) -> String {
+        format!("After move {} of {}", number, total)
+    }
+    fn replay_prompt(&self) -> &'static str {
+        "n (or Enter) steps forward, p steps back, a number jumps to that move, q quits."
+    }
+    fn wthor_game(
+        &self,
+        number: usize,
+        year: u16,
+        tournament: &str,
+        black: &str,
+        white: &str,
+        discs: (u8, u8),
+    ) -> String {
+        format!(
+            "{}. {} {}: {} (black) {} - {} {} (white)",
+            number, year, tournament, black, discs.0, discs.1, white
+        )
+    }
+    fn wthor_no_game(&self, number: usize, total: usize) -> String {
+        format!(
+            "There is no game {} (the file has {} games).",
+            number, total
+        )
+    }
+    fn archive_empty(&self) -> &'static str {
+        "No games have been recorded yet."
+    }
+    fn archive_line(
+        &self,
+        id: usize,
+        date: &str,
+        opponent: &str,
+        size: usize,
+        result: &str,
+    ) -> String {
+        format!(
+            "{:3}. {} (UTC) vs {} {}x{} {}",
+            id, date, opponent, size, size, result
+        )
+    }
+    fn archive_no_game(&self, id: usize, total: usize) -> String {
+        format!("There is no game {} ({} games recorded).", id, total)
+    }
+    fn archive_failed(&self, error: &dyn Display) -> String {
+        format!("Could not record the game: {}", error)
+    }
+    fn opponent_cpu(&self, level: Level) -> String {
+        format!("CPU ({})", self.level_name(level))
+    }
+    fn opponent_watch(&self) -> &'static str {
+        "CPU vs CPU"
+    }
+    fn book_written(&self, path: &str, games: usize) -> String {
+        format!(
+            "Wrote the openings of {} games to {} as an opening book.",
+            games, path
+        )
+    }
+    fn csv_failed(&self, path: &str, error: &dyn Display) -> String {
+        format!("Could not write the result to {}: {}", path, error)
+    }
+    fn quiet_not_allowed(&self) -> &'static str {
+        "--quiet can only be used with the script subcommand or --watch."
+    }
+    fn script_failed(&self, number: usize, word: &str, error: &dyn Display) -> String {
+        format!("Cannot play move {} ({}): {}", number, word, error)
+    }
+
+    fn editor_help(&self) -> &'static str {
+        "Edit the position to start the game from.
+b d3 / w d3   put a black / white disc on that square
+e d3          empty that square
+x d3          block that square
+turn b / w    make it black's / white's turn
+clear         empty every square
+reset         go back to the initial position
+load file     load a board from a file (rows of the characters .o#x)
+help (?)      show this list of commands
+done          start the game from this position"
+    }
+    fn editor_error(&self, error: &dyn Display) -> String {
+        format!("Cannot edit: {}", error)
+    }
+    fn editor_game_over(&self) -> &'static str {
+        "Neither side can move, so the game cannot start from this position."
+    }
+
+    fn invalid_size_arg(&self) -> &'static str {
+        "specify an even number of at least 4"
+    }
+    fn pieces_one_char(&self) -> &'static str {
+        "specify one character each for black, white and empty squares, separated by commas"
+    }
+    fn pieces_three(&self) -> &'static str {
+        "specify three characters for black, white and empty squares"
+    }
+    fn pieces_distinct(&self, blocked: char) -> String {
+        format!(
+            "the characters must differ from each other and from the blocked square ({})",
+            blocked
+        )
+    }
+    fn pieces_width(&self) -> &'static str {
+        "specify characters of the same display width"
+    }
+    fn invalid_probability(&self) -> &'static str {
+        "specify a number between 0 and 1"
+    }
+    fn not_positive(&self) -> &'static str {
+        "specify a positive number"
+    }
+    fn time_too_long(&self) -> &'static str {
+        "the time is too long"
+    }
+
+    fn reversi_error(&self, error: &ReversiError) -> String {
+        match error {
+            ReversiError::InvalidSize(n) => {
+                format!("board size {} is not an even number of at least 2", n)
+            }
+            ReversiError::RowLength { row, len, size } => format!(
+                "row {} has length {}, but the board size is {}",
+                row + 1,
+                len,
+                size
+            ),
+            ReversiError::UnknownChar { row, col, ch } => format!(
+                "the character '{}' at row {}, column {} cannot be used on a board",
+                ch,
+                row + 1,
+                col + 1
+            ),
+            ReversiError::EmptyCenter(c) => format!(
+                "the center square at row {}, column {} is empty",
+                c.row + 1,
+                c.col + 1
+            ),
+            ReversiError::OutOfRange(c) => format!(
+                "row {}, column {} is outside the board",
+                c.row + 1,
+                c.col + 1
+            ),
+            ReversiError::BlockedSquare(c) => format!(
+                "row {}, column {} is blocked, so no disc can be placed there",
+                c.row + 1,
+                c.col + 1
+            ),
+            ReversiError::IllegalMove(c) => format!(
+                "cannot place a disc at row {}, column {}",
+                c.row + 1,
+                c.col + 1
+            ),
+            ReversiError::IllegalPass => "cannot pass while a move is available".to_string(),
+            ReversiError::GameOver => "the game is already over".to_string(),
+            ReversiError::UnknownMove(s) => format!("'{}' is not a move", s),
+            ReversiError::UnknownCoord(s) => format!("'{}' is not a square", s),
+            ReversiError::InvalidPosition(s) => format!("'{}' is not a position", s),
+        }
+    }
+    fn replay_error(&self, error: &ReplayError) -> String {
+        format!(
+            "move {}: {}",
+            error.index + 1,
+            self.reversi_error(&error.error)
+        )
+    }
+    fn game_file_error(&self, error: &GameFileError) -> String {
+        match error {
+            GameFileError::Io(e) => e.to_string(),
+            GameFileError::Json(e) => format!("invalid JSON: {}", e),
+            GameFileError::Board(e) => {
+                format!("invalid starting position: {}", self.reversi_error(e))
+            }
+            GameFileError::Moves(e) => self.replay_error(e),
+            GameFileError::Mismatch => "the moves do not match the saved board".to_string(),
+        }
+    }
+    fn sgf_error(&self, error: &SgfError) -> String {
+        match error {
+            SgfError::Syntax(pos) => format!("invalid SGF at character {}", pos + 1),
+            SgfError::Property(key) => format!("invalid value for the SGF property {}", key),
+            SgfError::Board(e) => format!("invalid starting position: {}", self.reversi_error(e)),
+            SgfError::Moves(e) => self.replay_error(e),
+        }
+    }
+    fn wthor_error(&self, error: &WthorError) -> String {
+        match error {
+            WthorError::Io(e) => e.to_string(),
+            WthorError::Truncated => "the file ends unexpectedly".to_string(),
+            WthorError::Unsupported(n) => format!("cannot read games on a board of size {}", n),
+            WthorError::Move { game, index } => format!(
+                "move {} of game {} is outside the board",
+                index + 1,
+                game + 1
+            ),
+        }
+    }
+    fn book_error(&self, error: &BookError) -> String {
+        match error {
+            BookError::Io(e) => format!("Cannot read the opening book: {}", e),
+            BookError::Line { line, error } => format!(
+                "Opening book line {}, {}",
+                line + 1,
+                self.replay_error(error)
+            ),
+        }
+    }
+    fn weights_error(&self, error: &WeightsError) -> String {
+        match error {
+            WeightsError::Io(e) => format!("Cannot read the weights file: {}", e),
+            WeightsError::Parse(e) => format!("Invalid weights file: {}", e),
+            WeightsError::OutOfRange { name, value } => {
+                format!("The value {} of the weight {} is out of range", value, name)
+            }
+            WeightsError::Asymmetric { row, col } => format!(
+                "The square weights at row {}, column {} and row {}, column {} differ",
+                row + 1,
+                col + 1,
+                col + 1,
+                row + 1
+            ),
+        }
+    }
+    fn invalid_value(&self, key: &str) -> String {
+        format!("invalid value for {}", key)
+    }
+}
@@ -0,0 +1,308 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use simple_reversi::boardstate::{BoardState, Player};
+use simple_reversi::engine::Level;
+use simple_reversi::error::{GameFileError, ReplayError, SgfError};
+use simple_reversi::game::Game;
+use simple_reversi::sgf::{self, SgfInfo};
+
+use crate::messages::msg;
+
+/// 対局の形式（```Cpu```ならhumanが自分の色）
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    Cpu { human: Player },
+    Watch,
+    Pvp,
+}
+
+/// 途中から再開するために，棋譜と一緒に保存する設定
+///
+/// namesは黒，白の順の名前，levelとmctsはCPUの強さと先読みの方法，
+/// clockは```--clock```での黒，白の残り時間，undosはその対局で待ったした回数．
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub names: [Option<String>; 2],
+    pub mode: Mode,
+    pub level: Level,
+    pub mcts: bool,
+    pub clock: Option<[Duration; 2]>,
+    pub undos: usize,
+}
+
+/// 保存した対局を読み込めなかったことを表すエラー
+#[derive(Debug)]
+pub enum ResumeError {
+    /// ファイルを読めなかった
+    Io(io::Error),
+    /// ```[名前 "値"]```の行の値が正しくなかった
+    Tag(String),
+    /// 棋譜の手順を適用できなかった
+    Moves(ReplayError),
+    /// JSONのファイル（```Game::save_json```で書いたもの）を読めなかった
+    Json(GameFileError),
+    /// SGFのファイルを読めなかった
+    Sgf(SgfError),
+}
+
+impl fmt::Display for ResumeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResumeError::Io(e) => write!(f, "{}", e),
+            ResumeError::Tag(tag) => write!(f, "{}", msg().invalid_value(tag)),
+            ResumeError::Moves(e) => write!(f, "{}", msg().replay_error(e)),
+            ResumeError::Json(e) => write!(f, "{}", msg().game_file_error(e)),
+            ResumeError::Sgf(e) => write!(f, "{}", msg().sgf_error(e)),
+        }
+    }
+}
+
+impl Error for ResumeError {}
+
+/// ファイル名が```.json```で終わるかどうか（終わればJSONで保存する）
+pub fn is_json(path: &str) -> bool {
+    path.to_ascii_lowercase().ends_with(".json")
+}
+
+/// ファイル名が```.sgf```で終わるかどうか（終わればSGFで保存する）
+pub fn is_sgf(path: &str) -> bool {
+    path.to_ascii_lowercase().ends_with(".sgf")
+}
+
+impl SavedGame {
+    /// 書かれていない設定の既定値（名前なしで2人で対局する，強さは```Easy```）
+    fn new() -> SavedGame {
+        SavedGame {
+            names: [None, None],
+            mode: Mode::Pvp,
+            level: Level::Easy,
+            mcts: false,
+            clock: None,
+            undos: 0,
+        }
+    }
+
+    /// pathのファイルに保存する
+    ///
+    /// ```.json```で終わればJSON，```.sgf```で終わればSGF（名前以外の設定は書かない），
+    /// それ以外は```to_text```の形式で書く．
+    pub fn save(&self, game: &Game, path: &str) -> Result<(), Box<dyn Error>> {
+        if is_json(path) {
+            game.save_json(path, self)
+                .map_err(|e| msg().game_file_error(&e))?;
+        } else if is_sgf(path) {
+            let [black, white] = self.names.clone();
+            std::fs::write(path, sgf::to_sgf(game, &SgfInfo { black, white }))?;
+        } else {
+            std::fs::write(path, self.to_text(game))?;
+        }
+        Ok(())
+    }
+
+    /// ```save```で保存したファイルを読み込む
+    ///
+    /// ```to_text```の形式で始めの局面が書かれていなければ，default_startから始めたものとする．
+    /// SGFのファイルは，書かれている名前で2人で対局していたものとする．
+    pub fn load(path: &str, default_start: BoardState) -> Result<(SavedGame, Game), ResumeError> {
+        if is_json(path) {
+            return Game::load_json(path)
+                .map(|(game, saved)| (saved, game))
+                .map_err(ResumeError::Json);
+        }
+        let text = std::fs::read_to_string(path).map_err(ResumeError::Io)?;
+        if is_sgf(path) {
+            let (game, info) = sgf::from_sgf(&text).map_err(ResumeError::Sgf)?;
+            let saved = SavedGame {
+                names: [info.black, info.white],
+                ..SavedGame::new()
+            };
+            return Ok((saved, game));
+        }
+        SavedGame::parse(&text, default_start)
+    }
+
+    /// 設定を```[名前 "値"]```の行にして前につけた，gameの棋譜
+    ///
+    /// 棋譜の部分は```Game::transcript```と同じなので，```replay```でもそのまま読める．
+    pub fn to_text(&self, game: &Game) -> String {
+        let mut tags: Vec<(&str, String)> = Vec::new();
+        for (tag, name) in ["Black", "White"].iter().zip(&self.names) {
+            if let Some(name) = name {
+                tags.push((tag, name.clone()));
+            }
+        }
+        tags.push(("Start", game.start().to_position_string()));
+        let mode = match self.mode {
+            Mode::Cpu { human } => format!("cpu {}", human.piece()),
+            Mode::Watch => "watch".to_string(),
+            Mode::Pvp => "pvp".to_string(),
+        };
+        tags.push(("Mode", mode));
+        let level = Level::ALL.iter().position(|&l| l == self.level).unwrap() + 1;
+        tags.push(("Level", level.to_string()));
+        if self.mcts {
+            tags.push(("Search", "mcts".to_string()));
+        }
+        if let Some([black, white]) = self.clock {
+            let secs = format!("{:.1} {:.1}", black.as_secs_f64(), white.as_secs_f64());
+            tags.push(("Clock", secs));
+        }
+        if self.undos > 0 {
+            tags.push(("Undos", self.undos.to_string()));
+        }
+        let mut text: String = tags
+            .iter()
+            .map(|(tag, value)| format!("[{} {}]\n", tag, quote_tag(value)))
+            .collect();
+        text.push_str(&game.transcript());
+        text
+    }
+
+    /// ```to_text```で書いたものを読んで，設定と，手順を最後まで適用した対局を返す
+    ///
+    /// 書かれていない設定は，2人で対局する，強さは```Easy```，始めの局面はdefault_startとする．
+    pub fn parse(text: &str, default_start: BoardState) -> Result<(SavedGame, Game), ResumeError> {
+        let mut saved = SavedGame::new();
+        let mut start = default_start;
+        for line in text.lines().filter(|l| l.starts_with('[')) {
+            let (tag, value) = line
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .split_once(' ')
+                .ok_or_else(|| ResumeError::Tag(line.to_string()))?;
+            let invalid = || ResumeError::Tag(tag.to_string());
+            let value = unquote_tag(value.trim()).ok_or_else(invalid)?;
+            let value = value.as_str();
+            match tag {
+                "Black" => saved.names[0] = Some(value.to_string()),
+                "White" => saved.names[1] = Some(value.to_string()),
+                "Start" => {
+                    start = BoardState::from_position_string(value).map_err(|_| invalid())?
+                }
+                "Mode" => {
+                    saved.mode = match value.split_once(' ') {
+                        Some(("cpu", p)) => Mode::Cpu {
+                            human: p
+                                .chars()
+                                .next()
+                                .and_then(Player::from_piece)
+                                .ok_or_else(invalid)?,
+                        },
+                        _ if value == "watch" => Mode::Watch,
+                        _ if value == "pvp" => Mode::Pvp,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "Level" => {
+                    saved.level = value
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|n| Level::ALL.get(n.wrapping_sub(1)))
+                        .copied()
+                        .ok_or_else(invalid)?
+                }
+                "Search" => saved.mcts = value == "mcts",
+                "Clock" => {
+                    // 負の数や```inf```，```NaN```などの時間にできない値は受け付けない
+                    let remaining: Vec<Duration> = value
+                        .split_whitespace()
+                        .map(|s| Duration::try_from_secs_f64(s.parse().ok()?).ok())
+                        .collect::<Option<_>>()
+                        .filter(|v: &Vec<Duration>| v.len() == 2)
+                        .ok_or_else(invalid)?;
+                    saved.clock = Some([remaining[0], remaining[1]]);
+                }
+                "Undos" => saved.undos = value.parse().map_err(|_| invalid())?,
+                _ => {}
+            }
+        }
+        let game = Game::from_transcript(start, text).map_err(ResumeError::Moves)?;
+        Ok((saved, game))
+    }
+}
+
+/// ```[名前 "値"]```の値の部分（```"```と```\```の前に```\```をつけてダブルクォートで囲む）
+fn quote_tag(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// ```quote_tag```で書いた値を元に戻す（ダブルクォートで囲まれていなければNone）
+fn unquote_tag(quoted: &str) -> Option<String> {
+    let inner = quoted.strip_prefix('"')?.strip_suffix('"')?;
+    let mut value = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        value.push(if c == '\\' { chars.next()? } else { c });
+    }
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use simple_reversi::boardstate::{BoardBuilder, Coord};
+
+    #[test]
+    fn names_with_quotes_and_backslashes_round_trip() {
+        let start = BoardState::new(4, false);
+        let game = Game::from_compact_transcript(start.clone(), "F5D6").unwrap();
+        let saved = SavedGame {
+            names: [
+                Some(r#"say "hi" \ bye"#.to_string()),
+                Some(r#"ends with a quote""#.to_string()),
+            ],
+            ..SavedGame::new()
+        };
+        let (loaded, _) = SavedGame::parse(&saved.to_text(&game), start).unwrap();
+        assert_eq!(loaded.names, saved.names);
+    }
+
+    #[test]
+    fn settings_start_and_moves_round_trip() {
+        // 中央が空いていて，使えないマスのある編集した局面から始める
+        let start = BoardBuilder::new(6)
+            .without_initial_discs()
+            .disc(Coord::new(2, 2), Player::White)
+            .disc(Coord::new(2, 3), Player::Black)
+            .disc(Coord::new(3, 3), Player::White)
+            .blocked(Coord::new(0, 0))
+            .build()
+            .unwrap();
+        let game = Game::from_compact_transcript(start.clone(), "B3").unwrap();
+        let saved = SavedGame {
+            names: [Some("Alice".to_string()), None],
+            mode: Mode::Cpu {
+                human: Player::White,
+            },
+            level: Level::ALL[2],
+            mcts: true,
+            clock: Some([Duration::from_millis(61_500), Duration::from_secs(90)]),
+            undos: 3,
+        };
+        let text = saved.to_text(&game);
+        let (loaded, resumed) = SavedGame::parse(&text, BoardState::new(4, false)).unwrap();
+        assert_eq!(loaded.names, saved.names);
+        assert_eq!(loaded.mode, saved.mode);
+        assert_eq!(loaded.level, saved.level);
+        assert_eq!(loaded.mcts, saved.mcts);
+        assert_eq!(loaded.clock, saved.clock);
+        assert_eq!(loaded.undos, saved.undos);
+        assert_eq!(resumed.start(), &start);
+        assert_eq!(resumed.board(), game.board());
+        assert_eq!(resumed.transcript(), game.transcript());
+    }
+}
@@ -0,0 +1,449 @@
+//! オセロのSGF形式（```GM[2]```）の棋譜の読み書き
+//!
+//! 手は```;B[f5]```のように列の英字と1始まりの行番号で書き、パスは```;W[]```と書く。
+//! 読み込むときは、囲碁と同じく列と行を英字で書いた```;B[fe]```や、パスを表す```[tt]```も受け付ける。
+//! 分岐がある場合は、最初の変化だけを本譜として読む。
+
+use std::collections::HashMap;
+
+use crate::boardstate::{BoardBuilder, BoardState, Cell, Coord, Player};
+use crate::error::SgfError;
+use crate::game::{Game, Move, Outcome};
+
+/// 棋譜と一緒に読み書きする対局の情報
+///
+/// blackとwhiteは対局者の名前（```PB```、```PW```）。
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SgfInfo {
+    pub black: Option<String>,
+    pub white: Option<String>,
+}
+
+/// 1手につける注釈
+///
+/// symbolは```!```（好手）、```?```（疑問手）、```??```（悪手）のいずれか（なければ空文字列）で、
+/// それぞれ```TE[1]```、```BM[1]```、```BM[2]```として書く。commentはコメント（```C```）。
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Annotation {
+    pub symbol: &'static str,
+    pub comment: String,
+}
+
+/// gameをSGFの文字列にする
+///
+/// 盤面の大きさ（```SZ```）と、結果（```RE```）も書く。
+/// 始めの局面が最初の配置でなければ、駒の配置（```AB```、```AW```）と手番（```PL```）も書く
+/// （塞がれたマスはSGFで表せないので書かない）。
+pub fn to_sgf(game: &Game, info: &SgfInfo) -> String {
+    to_annotated_sgf(game, info, &HashMap::new())
+}
+
+/// ```to_sgf```と同じだが、annotationsにある手数（1始まり）の手に注釈をつける
+pub fn to_annotated_sgf(
+    game: &Game,
+    info: &SgfInfo,
+    annotations: &HashMap<usize, Annotation>,
+) -> String {
+    let start = game.start();
+    let n = start.get_size();
+    let mut text = format!("(;GM[2]FF[4]SZ[{}]", n);
+    for (key, name) in [("PB", &info.black), ("PW", &info.white)].iter() {
+        if let Some(name) = name {
+            text.push_str(&format!("{}[{}]", key, escape(name)));
+        }
+    }
+    if let Some(outcome) = game.outcome() {
+        text.push_str(&format!("RE[{}]", result(&outcome)));
+    }
+    let initial = BoardState::new(n / 2, false);
+    if start.grid() != initial.grid() || start.turn() != initial.turn() {
+        for (key, player) in [("AB", Player::Black), ("AW", Player::White)].iter() {
+            let mut discs = String::new();
+            for (row, cells) in start.grid().into_iter().enumerate() {
+                for (col, cell) in cells.into_iter().enumerate() {
+                    if cell == Cell::Occupied(*player) {
+                        discs.push_str(&format!("[{}]", Coord::new(row, col)));
+                    }
+                }
+            }
+            if !discs.is_empty() {
+                text.push_str(key);
+                text.push_str(&discs);
+            }
+        }
+        let turn = match start.turn() {
+            Player::Black => 'B',
+            Player::White => 'W',
+        };
+        text.push_str(&format!("PL[{}]", turn));
+    }
+    text.push('\n');
+    for r in game.history() {
+        let color = match r.player {
+            Player::Black => 'B',
+            Player::White => 'W',
+        };
+        let at = match r.mv {
+            Move::Place(c) => c.to_string(),
+            Move::Pass => String::new(),
+        };
+        text.push_str(&format!(";{}[{}]", color, at));
+        if let Some(a) = annotations.get(&r.number) {
+            match a.symbol {
+                "!" => text.push_str("TE[1]"),
+                "?" => text.push_str("BM[1]"),
+                "??" => text.push_str("BM[2]"),
+                _ => {}
+            }
+            if !a.comment.is_empty() {
+                text.push_str(&format!("C[{}]\n", escape(&a.comment)));
+            }
+        }
+    }
+    text.push_str(")\n");
+    text
+}
+
+/// SGFの文字列を読んで、手順を最後まで適用した対局と対局の情報を返す
+///
+/// ```RE```が投了（```B+R```など）か時間切れ（```B+T```など）なら、負けた側の投了か時間切れで終局させる。
+pub fn from_sgf(text: &str) -> Result<(Game, SgfInfo), SgfError> {
+    let mut parser = Parser {
+        chars: text.chars().collect(),
+        pos: 0,
+    };
+    let nodes = parser.tree()?;
+    let root = nodes.first().ok_or(SgfError::Syntax(0))?;
+
+    if let Some(gm) = property(root, "GM") {
+        if gm != "2" {
+            return Err(SgfError::Property("GM".to_string()));
+        }
+    }
+    let size = match property(root, "SZ") {
+        Some(sz) => sz
+            .parse::<usize>()
+            .ok()
+            .filter(|n| *n >= 4 && n % 2 == 0)
+            .ok_or_else(|| SgfError::Property("SZ".to_string()))?,
+        None => 8,
+    };
+    let info = SgfInfo {
+        black: property(root, "PB").map(str::to_string),
+        white: property(root, "PW").map(str::to_string),
+    };
+
+    let start = if ["AB", "AW", "AE", "PL"]
+        .iter()
+        .any(|key| root.iter().any(|(k, _)| k == key))
+    {
+        setup(root, size)?
+    } else {
+        BoardState::new(size / 2, false)
+    };
+
+    let mut moves = Vec::new();
+    for node in &nodes {
+        for (key, values) in node {
+            if key != "B" && key != "W" {
+                continue;
+            }
+            let value = values.first().map(String::as_str).unwrap_or("");
+            let mv = parse_move(value, size).ok_or_else(|| SgfError::Property(key.clone()))?;
+            moves.push(mv);
+        }
+    }
+    let mut game = Game::new(start);
+    game.apply_moves(&moves).map_err(SgfError::Moves)?;
+
+    // 投了や時間切れで終わった対局は、負けた側の投了・時間切れとして終局させる
+    if let Some(re) = property(root, "RE") {
+        let winner = match re.chars().next() {
+            Some('B') => Some(Player::Black),
+            Some('W') => Some(Player::White),
+            _ => None,
+        };
+        if let (Some(winner), false) = (winner, game.is_over()) {
+            let reason = re.split('+').nth(1).unwrap_or("");
+            if reason.starts_with('R') {
+                game.resign(winner.opponent()).ok();
+            } else if reason.starts_with('T') {
+                game.time_out(winner.opponent()).ok();
+            }
+        }
+    }
+    Ok((game, info))
+}
+
+/// ```RE```の値（```B+16```、```W+R```、引き分けは```0```）
+fn result(outcome: &Outcome) -> String {
+    let color = |p: Player| match p {
+        Player::Black => 'B',
+        Player::White => 'W',
+    };
+    match *outcome {
+        Outcome::Completed { white, black } => match outcome.winner() {
+            Some(p) => format!("{}+{}", color(p), black.max(white) - black.min(white)),
+            None => "0".to_string(),
+        },
+        Outcome::Resignation { resigned } => format!("{}+R", color(resigned.opponent())),
+        Outcome::Timeout { flagged } => format!("{}+T", color(flagged.opponent())),
+    }
+}
+
+/// ```]```と```\```の前に```\```をつける
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace(']', "\\]")
+}
+
+/// SGFの1つの節点（プロパティの名前と値の一覧の組）
+type Node = Vec<(String, Vec<String>)>;
+
+/// 節点のkeyのプロパティの最初の値
+fn property<'a>(node: &'a Node, key: &str) -> Option<&'a str> {
+    node.iter()
+        .find(|(k, _)| k == key)
+        .and_then(|(_, values)| values.first())
+        .map(String::as_str)
+}
+
+/// 手の値（```f5```、```fe```、パスは空か```tt```）を読む（盤面の外ならNone）
+fn parse_move(value: &str, size: usize) -> Option<Move> {
+    let value = value.trim();
+    if value.is_empty() || (value == "tt" && size <= 19) {
+        return Some(Move::Pass);
+    }
+    coord(value, size).map(Move::Place)
+}
+
+/// マス目の値（```f5```か```fe```）を読む（盤面の外ならNone）
+fn coord(value: &str, size: usize) -> Option<Coord> {
+    let bytes = value.as_bytes();
+    let c = if bytes.len() == 2 && bytes.iter().all(u8::is_ascii_lowercase) {
+        Coord::new((bytes[1] - b'a') as usize, (bytes[0] - b'a') as usize)
+    } else {
+        value.parse::<Coord>().ok()?
+    };
+    Some(c).filter(|c| c.row < size && c.col < size)
+}
+
+/// 最初の節点の```AB```、```AW```、```AE```、```PL```から始めの局面を作る
+///
+/// ```AB```か```AW```があれば空の盤面に置き、なければ最初の配置から```AE```のマスを空ける。
+fn setup(root: &Node, size: usize) -> Result<BoardState, SgfError> {
+    let values = |key: &str| -> Result<Vec<Coord>, SgfError> {
+        root.iter()
+            .filter(|(k, _)| k == key)
+            .flat_map(|(_, values)| values)
+            .map(|v| coord(v, size).ok_or_else(|| SgfError::Property(key.to_string())))
+            .collect()
+    };
+    let black = values("AB")?;
+    let white = values("AW")?;
+    let empty = values("AE")?;
+    let turn = match property(root, "PL") {
+        Some("W") => Player::White,
+        Some("B") | None => Player::Black,
+        Some(_) => return Err(SgfError::Property("PL".to_string())),
+    };
+    let mut builder = BoardBuilder::new(size).turn(turn).without_initial_discs();
+    if black.is_empty() && white.is_empty() {
+        let initial = BoardState::new(size / 2, false);
+        for (row, cells) in initial.grid().into_iter().enumerate() {
+            for (col, cell) in cells.into_iter().enumerate() {
+                let c = Coord::new(row, col);
+                if let (Cell::Occupied(p), false) = (cell, empty.contains(&c)) {
+                    builder = builder.disc(c, p);
+                }
+            }
+        }
+    }
+    for &c in &black {
+        builder = builder.disc(c, Player::Black);
+    }
+    for &c in &white {
+        builder = builder.disc(c, Player::White);
+    }
+    builder.build().map_err(SgfError::Board)
+}
+
+/// SGFの文字列を読む（posは次に読む文字の位置）
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    /// 空白を読み飛ばして次の文字を見る
+    fn peek(&mut self) -> Option<char> {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+        self.chars.get(self.pos).copied()
+    }
+
+    /// 次の文字がcであることを確かめて読み進める
+    fn expect(&mut self, c: char) -> Result<(), SgfError> {
+        if self.peek() != Some(c) {
+            return Err(SgfError::Syntax(self.pos));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    /// ```(```から```)```までの木を読み、本譜（各分岐の最初の変化）の節点を順に返す
+    fn tree(&mut self) -> Result<Vec<Node>, SgfError> {
+        self.expect('(')?;
+        let mut nodes = Vec::new();
+        while self.peek() == Some(';') {
+            self.pos += 1;
+            nodes.push(self.node()?);
+        }
+        let mut first = true;
+        while self.peek() == Some('(') {
+            let variation = self.tree()?;
+            if first {
+                nodes.extend(variation);
+                first = false;
+            }
+        }
+        self.expect(')')?;
+        Ok(nodes)
+    }
+
+    /// ```;```の後の1つの節点を読む
+    fn node(&mut self) -> Result<Node, SgfError> {
+        let mut node = Node::new();
+        while self.peek().is_some_and(|c| c.is_ascii_uppercase()) {
+            let mut key = String::new();
+            while let Some(c) = self.chars.get(self.pos).filter(|c| c.is_ascii_uppercase()) {
+                key.push(*c);
+                self.pos += 1;
+            }
+            let mut values = Vec::new();
+            while self.peek() == Some('[') {
+                self.pos += 1;
+                values.push(self.value()?);
+            }
+            if values.is_empty() {
+                return Err(SgfError::Syntax(self.pos));
+            }
+            node.push((key, values));
+        }
+        Ok(node)
+    }
+
+    /// ```[```の後から```]```までの値を読む（```\```の次の文字はそのまま）
+    fn value(&mut self) -> Result<String, SgfError> {
+        let mut value = String::new();
+        loop {
+            let c = *self.chars.get(self.pos).ok_or(SgfError::Syntax(self.pos))?;
+            self.pos += 1;
+            match c {
+                ']' => return Ok(value),
+                '\\' => {
+                    let next = *self.chars.get(self.pos).ok_or(SgfError::Syntax(self.pos))?;
+                    self.pos += 1;
+                    value.push(next);
+                }
+                _ => value.push(c),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::strategy::RandomStrategy;
+
+    /// 乱数の種を順に変えながら、途中でパスが起きる8×8の対局を探す
+    fn game_with_pass() -> Game {
+        (0..)
+            .map(|seed| {
+                let mut black = RandomStrategy::with_seed(Some(seed));
+                let mut white = RandomStrategy::with_seed(Some(seed + 1000));
+                let mut game = Game::new(BoardState::new(4, false));
+                game.play_out(&mut black, &mut white).unwrap();
+                game
+            })
+            .find(|g| g.history().iter().any(|r| r.mv == Move::Pass))
+            .unwrap()
+    }
+
+    fn round_trip(game: &Game, info: &SgfInfo) -> (Game, SgfInfo) {
+        from_sgf(&to_sgf(game, info)).unwrap()
+    }
+
+    #[test]
+    fn round_trip_with_pass() {
+        let game = game_with_pass();
+        let info = SgfInfo {
+            black: Some("Alice".to_string()),
+            white: Some("Bob [2]".to_string()),
+        };
+        let (loaded, loaded_info) = round_trip(&game, &info);
+        assert_eq!(loaded.history(), game.history());
+        assert_eq!(loaded.outcome(), game.outcome());
+        assert_eq!(loaded_info, info);
+    }
+
+    #[test]
+    fn round_trip_resigned() {
+        let mut game = Game::from_compact_transcript(BoardState::new(4, false), "F5D6C3").unwrap();
+        game.resign(Player::White).unwrap();
+        let text = to_sgf(&game, &SgfInfo::default());
+        assert!(text.contains("RE[B+R]"));
+        let (loaded, _) = from_sgf(&text).unwrap();
+        assert_eq!(loaded.history(), game.history());
+        assert_eq!(
+            loaded.outcome(),
+            Some(Outcome::Resignation {
+                resigned: Player::White
+            })
+        );
+    }
+
+    #[test]
+    fn round_trip_setup_position() {
+        let start = BoardBuilder::new(8)
+            .turn(Player::White)
+            .disc(Coord::new(0, 0), Player::Black)
+            .disc(Coord::new(2, 3), Player::Black)
+            .build()
+            .unwrap();
+        let mut game = Game::new(start.clone());
+        let mv = game.board().legal_moves().next().unwrap();
+        game.apply_move(Move::Place(mv)).unwrap();
+        let text = to_sgf(&game, &SgfInfo::default());
+        assert!(text.contains("AB[") && text.contains("AW[") && text.contains("PL[W]"));
+        let (loaded, _) = from_sgf(&text).unwrap();
+        assert_eq!(loaded.start(), &start);
+        assert_eq!(loaded.history(), game.history());
+        assert_eq!(loaded.board(), game.board());
+    }
+
+    #[test]
+    fn go_style_coordinates_and_pass() {
+        let (go, _) = from_sgf("(;GM[2]SZ[8];B[fe];W[df];B[cc])").unwrap();
+        let (plain, _) = from_sgf("(;GM[2]SZ[8];B[f5];W[d6];B[c3])").unwrap();
+        assert_eq!(go.history(), plain.history());
+
+        // 黒が置けずにパスしてから始まる局面で、パスを[tt]で書いたもの
+        let (game, _) = from_sgf("(;GM[2]SZ[4]AB[b1]AW[a1]PL[B];B[tt];W[c1])").unwrap();
+        let moves: Vec<Move> = game.history().iter().map(|r| r.mv).collect();
+        assert_eq!(moves, vec![Move::Pass, Move::Place(Coord::new(0, 2))]);
+    }
+
+    #[test]
+    fn round_trip_forced_pass_at_start() {
+        let start = BoardState::from_position_string("o#2/4/4/4 b 4").unwrap();
+        let mut game = Game::new(start.clone());
+        assert_eq!(game.history()[0].mv, Move::Pass);
+        game.apply_move(Move::Place(Coord::new(0, 2))).unwrap();
+        let (loaded, _) = round_trip(&game, &SgfInfo::default());
+        assert_eq!(loaded.start(), &start);
+        assert_eq!(loaded.history(), game.history());
+        assert_eq!(loaded.outcome(), game.outcome());
+    }
+}
@@ -0,0 +1,166 @@
+use std::fmt::{self, Write};
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::boardstate::{BoardState, Player};
+use crate::engine::strategy::Strategy;
+use crate::game::Game;
+
+/// CPU同士の総当たり戦
+///
+/// 参加するCPUの全ての組み合わせについて、startの局面からgames局ずつ
+/// （先手と後手を交互に入れ替えながら）対局させる。
+pub struct Tournament {
+    start: BoardState,
+    games: usize,
+    entrants: Vec<(String, Box<dyn Strategy>)>,
+}
+
+/// ある参加者の、ある相手に対する成績
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Record {
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+}
+
+/// 総当たり戦の結果（対戦表）
+///
+/// namesは参加者の名前、records\[i\]\[j\]はi番目の参加者のj番目の参加者に対する成績。
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Crosstable {
+    pub names: Vec<String>,
+    pub records: Vec<Vec<Record>>,
+}
+
+impl Tournament {
+    /// startの局面から、1つの組み合わせにつきgames局ずつ対局させる総当たり戦を作成する
+    pub fn new(start: BoardState, games: usize) -> Tournament {
+        Tournament {
+            start,
+            games,
+            entrants: Vec::new(),
+        }
+    }
+
+    /// 参加するCPUを追加する
+    pub fn add(&mut self, name: &str, strategy: Box<dyn Strategy>) {
+        self.entrants.push((name.to_string(), strategy));
+    }
+
+    /// 全ての対局を行って対戦表を作る
+    ///
+    /// 1局終わるごとに、on_gameに（先手の番号, 後手の番号, 終局した対局）を渡す。
+    pub fn run<F: FnMut(usize, usize, &Game)>(&mut self, mut on_game: F) -> Crosstable {
+        let n = self.entrants.len();
+        let mut records = vec![vec![Record::default(); n]; n];
+        for i in 0..n {
+            for j in i + 1..n {
+                let (left, right) = self.entrants.split_at_mut(j);
+                let a = &mut left[i].1;
+                let b = &mut right[0].1;
+                for k in 0..self.games {
+                    // 偶数局目はiが先手（黒）、奇数局目はjが先手
+                    let (black, white) = if k.is_multiple_of(2) { (i, j) } else { (j, i) };
+                    let mut game = Game::new(self.start.clone());
+                    let result = if black == i {
+                        game.play_out(a.as_mut(), b.as_mut())
+                    } else {
+                        game.play_out(b.as_mut(), a.as_mut())
+                    };
+                    result.expect("CPUが置けないマスを選んだ");
+                    on_game(black, white, &game);
+
+                    let winner = game.outcome().and_then(|o| o.winner());
+                    match winner {
+                        Some(Player::Black) => {
+                            records[black][white].wins += 1;
+                            records[white][black].losses += 1;
+                        }
+                        Some(Player::White) => {
+                            records[white][black].wins += 1;
+                            records[black][white].losses += 1;
+                        }
+                        None => {
+                            records[black][white].draws += 1;
+                            records[white][black].draws += 1;
+                        }
+                    }
+                }
+            }
+        }
+        Crosstable {
+            names: self.entrants.iter().map(|(name, _)| name.clone()).collect(),
+            records,
+        }
+    }
+}
+
+impl Crosstable {
+    /// i番目の参加者の全ての相手に対する成績の合計
+    pub fn total(&self, i: usize) -> Record {
+        self.records[i]
+            .iter()
+            .fold(Record::default(), |acc, r| Record {
+                wins: acc.wins + r.wins,
+                draws: acc.draws + r.draws,
+                losses: acc.losses + r.losses,
+            })
+    }
+
+    /// 参加者の一覧と、番号で並べた対戦表（行の参加者から見た成績）の文字列
+    ///
+    /// totalは合計の欄の見出し（表示したときの幅に合わせて揃える）。
+    pub fn to_text(&self, total: &str) -> String {
+        let mut text = String::new();
+        self.write_text(&mut text, total).unwrap();
+        text
+    }
+
+    fn write_text<W: Write>(&self, f: &mut W, total: &str) -> fmt::Result {
+        let n = self.names.len();
+        for (i, name) in self.names.iter().enumerate() {
+            writeln!(f, "{:2}: {}", i + 1, name)?;
+        }
+
+        // 成績の欄の幅（合計の欄が一番長くなる）
+        let width = (0..n)
+            .map(|i| self.total(i).to_string().len())
+            .max()
+            .unwrap_or(0)
+            .max(4)
+            .max(total.width());
+
+        write!(f, "  ")?;
+        for j in 1..=n {
+            write!(f, " {:>w$}", j, w = width)?;
+        }
+        writeln!(f, " {}{}", " ".repeat(width - total.width()), total)?;
+        for i in 0..n {
+            write!(f, "{:2}", i + 1)?;
+            for j in 0..n {
+                if i == j {
+                    write!(f, " {:>w$}", "-", w = width)?;
+                } else {
+                    write!(f, " {:>w$}", self.records[i][j].to_string(), w = width)?;
+                }
+            }
+            writeln!(f, " {:>w$}", self.total(i).to_string(), w = width)?;
+        }
+        Ok(())
+    }
+}
+
+/// 「勝ち-引き分け-負け」の形式で表示する
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}-{}", self.wins, self.draws, self.losses)
+    }
+}
+
+/// 合計の欄の見出しを「合計」として```to_text```と同じように表示する
+impl fmt::Display for Crosstable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_text(f, "合計")
+    }
+}
@@ -0,0 +1,272 @@
+use std::io;
+use std::thread::sleep;
+use std::time::Duration;
+
+use ratatui::crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseButton,
+    MouseEventKind,
+};
+use ratatui::crossterm::execute;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use simple_reversi::boardstate::{Cell, Coord, Player};
+use simple_reversi::display::ColorTheme;
+use simple_reversi::engine::strategy::Strategy;
+use simple_reversi::engine::Engine;
+use simple_reversi::game::{Game, Move};
+
+use crate::cli::cli;
+use crate::messages::msg;
+use crate::{
+    board_style, describe_score, mobility_text, notify_turn, piece, result_text, take_back, Names,
+};
+
+/// 全画面表示の状態
+///
+/// cursorは選んでいるマス目，messageは盤面の横に表示する一言，
+/// originは左上のマス目が描かれている端末上の位置（列，行）．
+struct Screen {
+    cursor: Coord,
+    message: String,
+    origin: (u16, u16),
+}
+
+impl Screen {
+    /// 端末上の位置（列，行）にあるマス目（盤面の外ならNone）
+    fn square_at(&self, size: usize, x: u16, y: u16) -> Option<Coord> {
+        let (ox, oy) = self.origin;
+        // 各マス目の左の空白もそのマス目として扱う
+        let col = usize::from(x.checked_add(1)?.checked_sub(ox)?) / 2;
+        let row = usize::from(y.checked_sub(oy)?);
+        Some(Coord::new(row, col)).filter(|_| row < size && col < size)
+    }
+}
+
+/// 全画面表示で対局する（```--tui```）
+///
+/// cpu_playersの側はcpuが打ち，それ以外の側は矢印キーでマス目を選んでEnterで置くか，
+/// マス目をクリックして置く．
+/// 終局するか投了すると，結果を表示してキーが押されるのを待ってから元の画面に戻る．
+pub fn play(
+    game: &mut Game,
+    cpu: &mut dyn Strategy,
+    cpu_players: &[Player],
+    names: &Names,
+) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    execute!(io::stdout(), EnableMouseCapture)?;
+    let result = run(&mut terminal, game, cpu, cpu_players, names);
+    execute!(io::stdout(), DisableMouseCapture)?;
+    ratatui::restore();
+    result
+}
+
+/// 対局の本体（画面の後始末は```play```で行う）
+fn run(
+    terminal: &mut DefaultTerminal,
+    game: &mut Game,
+    cpu: &mut dyn Strategy,
+    cpu_players: &[Player],
+    names: &Names,
+) -> io::Result<()> {
+    let size = game.board().get_size();
+    let mut screen = Screen {
+        cursor: Coord::new(size / 2 - 1, size / 2 - 1),
+        message: String::new(),
+        origin: (0, 0),
+    };
+    loop {
+        if game.is_over() {
+            screen.message = result_text(game, names);
+            terminal.draw(|frame| draw(frame, game, names, &mut screen, false))?;
+            wait_key()?;
+            return Ok(());
+        }
+
+        // CPUの番
+        let player = game.board().turn();
+        if cpu_players.contains(&player) {
+            screen.message = msg().cpu_thinking().trim().to_string();
+            terminal.draw(|frame| draw(frame, game, names, &mut screen, false))?;
+            if cpu_players.len() == 2 {
+                sleep(Duration::from_millis(300));
+            }
+            screen.message.clear();
+            match cpu.choose_move_or_resign(game.board()) {
+                Some(mv) => game.apply_move(mv).unwrap(),
+                None => game.resign(player).unwrap(),
+            }
+            let next = game.board().turn();
+            if !game.is_over() && !cpu_players.contains(&next) {
+                notify_turn(next);
+            }
+            continue;
+        }
+
+        // 自分の番
+        terminal.draw(|frame| draw(frame, game, names, &mut screen, true))?;
+        let key = match event::read()? {
+            Event::Key(k) if k.kind == KeyEventKind::Press => k.code,
+            // マウスを乗せたマス目を選び，クリックしたマス目に置く
+            Event::Mouse(m) => match (m.kind, screen.square_at(size, m.column, m.row)) {
+                (MouseEventKind::Moved, Some(c)) => {
+                    screen.cursor = c;
+                    continue;
+                }
+                (MouseEventKind::Down(MouseButton::Left), Some(c)) => {
+                    screen.cursor = c;
+                    KeyCode::Enter
+                }
+                _ => continue,
+            },
+            _ => continue,
+        };
+        screen.message.clear();
+        let c = &mut screen.cursor;
+        match key {
+            KeyCode::Up => c.row = c.row.saturating_sub(1),
+            KeyCode::Down => c.row = (c.row + 1).min(size - 1),
+            KeyCode::Left => c.col = c.col.saturating_sub(1),
+            KeyCode::Right => c.col = (c.col + 1).min(size - 1),
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                if let Err(_e) = game.apply_move(Move::Place(*c)) {
+                    screen.message = msg().cannot_place().to_string();
+                }
+            }
+            KeyCode::Char('h') => {
+                screen.message =
+                    match Engine::new(1).search_within(game.board(), Duration::from_millis(500)) {
+                        Some(r) => msg().hint(&Move::Place(r.best_move), &describe_score(&r)),
+                        None => String::new(),
+                    };
+            }
+            KeyCode::Char('u') => {
+                let human = Some(player).filter(|_| !cpu_players.is_empty());
+                if !take_back(game, human) {
+                    screen.message = msg().cannot_undo().to_string();
+                }
+            }
+            KeyCode::Char('q') | KeyCode::Esc => game.resign(player).unwrap(),
+            _ => {}
+        }
+    }
+}
+
+/// キーが押されるまで待つ
+fn wait_key() -> io::Result<()> {
+    loop {
+        if let Event::Key(k) = event::read()? {
+            if k.kind == KeyEventKind::Press {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// 盤面と，駒の個数・棋譜・操作方法の欄を描く
+///
+/// humanがtrueなら，選んでいるマス目と置けるマス目を目立たせる．
+fn draw(frame: &mut Frame, game: &Game, names: &Names, screen: &mut Screen, human: bool) {
+    let bs = game.board();
+    let n = bs.get_size();
+    let style = board_style();
+    let last = game.last_placement().map(|(c, _)| c);
+    // 盤面，直前の手の背景と空きマスの文字の色（```--theme```の配色．色を使わない場合は背景がNone）
+    let (board_bg, last_bg, empty_fg) = match style.theme {
+        _ if !style.color => (None, Color::Reset, Color::Reset),
+        ColorTheme::Classic => (Some(Color::Green), Color::LightGreen, Color::Green),
+        ColorTheme::HighContrast => (Some(Color::Blue), Color::LightBlue, Color::Gray),
+        ColorTheme::Monochrome => (None, Color::Reset, Color::Reset),
+    };
+
+    // 盤面
+    let mut lines = Vec::new();
+    let mut header = String::from("  ");
+    for col in 0..n {
+        header.push_str(&format!("{:>2}", Coord::column_name(col)));
+    }
+    lines.push(Line::from(header));
+    for (row, cells) in bs.grid().into_iter().enumerate() {
+        let mut spans = vec![Span::raw(format!("{:>2}", row + 1))];
+        for (col, cell) in cells.into_iter().enumerate() {
+            let coord = Coord::new(row, col);
+            let (ch, fg) = match cell {
+                Cell::Occupied(Player::Black) => (style.black, Color::Black),
+                Cell::Occupied(Player::White) => (style.white, Color::White),
+                Cell::Blocked => (style.blocked, Color::DarkGray),
+                Cell::Empty if human && bs.is_legal(coord) => ('+', Color::Yellow),
+                Cell::Empty => (style.empty, empty_fg),
+            };
+            let mut st = match board_bg {
+                Some(_) if Some(coord) == last => Style::default().fg(fg).bg(last_bg),
+                Some(bg) => Style::default().fg(fg).bg(bg),
+                // 色を使わない場合は，直前の手を太字と下線で表す
+                None if Some(coord) == last => {
+                    Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+                }
+                None => Style::default(),
+            };
+            if human && coord == screen.cursor {
+                st = st.add_modifier(Modifier::REVERSED);
+            }
+            let gap = board_bg.map_or(Style::default(), |bg| Style::default().bg(bg));
+            spans.push(Span::styled(" ", gap));
+            spans.push(Span::styled(ch.to_string(), st));
+        }
+        lines.push(Line::from(spans));
+    }
+    let board_width = (2 * n + 4) as u16;
+    let board_height = (n + 3) as u16;
+    let [board_area, side] =
+        Layout::horizontal([Constraint::Length(board_width), Constraint::Min(24)])
+            .areas(frame.area());
+    let [board_area, _] =
+        Layout::vertical([Constraint::Length(board_height), Constraint::Min(0)]).areas(board_area);
+    // 枠，行番号と空白，見出しの行の分だけずれる
+    screen.origin = (board_area.x + 4, board_area.y + 2);
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title(msg().tui_board_title())),
+        board_area,
+    );
+
+    // 駒の個数と手番，一言
+    let score = bs.score();
+    let mut info = vec![
+        Line::from(msg().move_header(game.move_number(), game.empties())),
+        Line::from(msg().score(
+            piece(Player::Black),
+            score.black,
+            piece(Player::White),
+            score.white,
+        )),
+        Line::from(msg().turn(&names.label(bs.turn()))),
+    ];
+    if cli().show_mobility {
+        info.push(Line::from(mobility_text(bs, names)));
+    }
+    info.extend([
+        Line::from(""),
+        Line::from(screen.message.clone()),
+        Line::from(""),
+    ]);
+    // 棋譜（新しい手が上）
+    info.push(Line::from(msg().tui_history_title()));
+    info.extend(
+        game.history()
+            .iter()
+            .rev()
+            .map(|r| Line::from(format!("{:3}. {} {}", r.number, piece(r.player), r.mv))),
+    );
+    let keys: Vec<Line> = msg().tui_keys().lines().map(Line::from).collect();
+    let [info_area, keys_area] = Layout::vertical([
+        Constraint::Min(8),
+        Constraint::Length(keys.len() as u16 + 2),
+    ])
+    .areas(side);
+    frame.render_widget(Paragraph::new(info).block(Block::bordered()), info_area);
+    frame.render_widget(Paragraph::new(keys).block(Block::bordered()), keys_area);
+}
@@ -0,0 +1,213 @@
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::boardstate::{BoardState, Coord, Player};
+use crate::engine::eval::{EvalWeights, PositionalEvaluator, PositionalWeights, MAX_WEIGHT};
+use crate::engine::strategy::make_rng;
+use crate::engine::Engine;
+use crate::game::{Game, Move};
+
+/// 対局の最初にランダムに打つ手の数（毎回同じ対局にならないようにする）
+const RANDOM_OPENING_PLIES: usize = 4;
+
+/// 自己対局で評価関数（```PositionalEvaluator```）の重みを調整する仕組み（山登り法）
+///
+/// 1回の試行では、今の重みの全ての値をランダムに±stepだけずらした候補を作り、
+/// startの局面から候補と今の重みのエンジン（depth手先まで読む）をgames局対局させて、
+/// 勝ち越した（引き分けは0.5勝とする）ら候補を新しい重みにする。
+/// 対局は最初の数手をランダムに打ってから始め、同じ序盤で先手と後手を入れ替えて2局ずつ行う。
+pub struct Tuner {
+    start: BoardState,
+    games: usize,
+    depth: u32,
+    step: i32,
+    rng: SmallRng,
+}
+
+/// 1回の試行の結果
+///
+/// iterationは何回目の試行か（1始まり）、candidateは試した重み、
+/// scoreは候補の勝率（0から1）、acceptedは候補を新しい重みにしたかどうか。
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Trial {
+    pub iteration: usize,
+    pub candidate: PositionalWeights,
+    pub score: f64,
+    pub accepted: bool,
+}
+
+impl Tuner {
+    /// startの局面から対局させて調整する（既定は1回の試行につき10局、2手先まで読み、ずらす幅は5）
+    pub fn new(start: BoardState) -> Tuner {
+        Tuner {
+            start,
+            games: 10,
+            depth: 2,
+            step: 5,
+            rng: make_rng(None),
+        }
+    }
+
+    /// 1回の試行での対局数を指定する（奇数なら1つ増やす）
+    pub fn games(self, games: usize) -> Tuner {
+        assert!(games != 0);
+        Tuner {
+            games: games + games % 2,
+            ..self
+        }
+    }
+
+    /// 対局させるエンジンの読む深さを指定する
+    pub fn depth(self, depth: u32) -> Tuner {
+        assert!(depth != 0);
+        Tuner { depth, ..self }
+    }
+
+    /// 重みをずらす幅を指定する
+    pub fn step(self, step: i32) -> Tuner {
+        assert!(step > 0);
+        Tuner { step, ..self }
+    }
+
+    /// 乱数の種を指定する（同じ種なら同じ結果になる）
+    pub fn seed(self, seed: u64) -> Tuner {
+        Tuner {
+            rng: make_rng(Some(seed)),
+            ..self
+        }
+    }
+
+    /// weightsから始めてiterations回試行し、調整した重みを返す
+    ///
+    /// 1回試行するごとに、on_trialに結果と、その時点の重みを渡す。
+    pub fn run<F: FnMut(&Trial, &EvalWeights)>(
+        &mut self,
+        weights: EvalWeights,
+        iterations: usize,
+        mut on_trial: F,
+    ) -> EvalWeights {
+        let mut current = weights;
+        for iteration in 1..=iterations {
+            let candidate = self.perturb(&current.positional);
+            let score = self.match_score(&candidate, &current.positional);
+            let accepted = score > 0.5;
+            if accepted {
+                current.positional = candidate;
+            }
+            on_trial(
+                &Trial {
+                    iteration,
+                    candidate,
+                    score,
+                    accepted,
+                },
+                &current,
+            );
+        }
+        current
+    }
+
+    /// 全ての値をランダムに±stepだけずらした重み（マス目の重みは対称なまま）
+    fn perturb(&mut self, weights: &PositionalWeights) -> PositionalWeights {
+        let step = self.step;
+        let rng = &mut self.rng;
+        let mut delta = || if rng.gen_bool(0.5) { step } else { -step };
+        let mut next = *weights;
+        for i in 0..4 {
+            for j in 0..=i {
+                let w = (next.squares[i][j] + delta()).clamp(-MAX_WEIGHT, MAX_WEIGHT);
+                next.squares[i][j] = w;
+                next.squares[j][i] = w;
+            }
+        }
+        next.mobility = (next.mobility + delta()).clamp(-MAX_WEIGHT, MAX_WEIGHT);
+        next
+    }
+
+    /// candidateのエンジンをbaseのエンジンと対局させたときの勝率
+    fn match_score(&mut self, candidate: &PositionalWeights, base: &PositionalWeights) -> f64 {
+        let mut a = Engine::with_evaluator(self.depth, PositionalEvaluator::new(*candidate));
+        let mut b = Engine::with_evaluator(self.depth, PositionalEvaluator::new(*base));
+        let mut points = 0.0;
+        for _ in 0..self.games / 2 {
+            let opening = self.random_opening();
+            for &candidate_color in [Player::Black, Player::White].iter() {
+                let mut game = self.replay(&opening);
+                if candidate_color == Player::Black {
+                    game.play_out(&mut a, &mut b)
+                } else {
+                    game.play_out(&mut b, &mut a)
+                }
+                .expect("CPUが置けないマスを選んだ");
+                points += match game.outcome().and_then(|o| o.winner()) {
+                    Some(w) if w == candidate_color => 1.0,
+                    Some(_) => 0.0,
+                    None => 0.5,
+                };
+            }
+        }
+        points / self.games as f64
+    }
+
+    /// weightsのエンジン同士をgames局対局させて、勝つ確率の見積もりを合わせるための組を集める
+    ///
+    /// 終局まで読み切れない局面ごとに、（探索の評価値, 実際の結果）を手番の側から見て記録する。
+    /// 実際の結果は勝ちなら1、負けなら0、引き分けなら0.5（```WinProbability::calibrate```に渡せる）。
+    pub fn win_samples(&mut self, weights: &EvalWeights) -> Vec<(i32, f64)> {
+        let engine =
+            Engine::with_evaluator(self.depth, PositionalEvaluator::new(weights.positional));
+        let mut samples = Vec::new();
+        for _ in 0..self.games {
+            let opening = self.random_opening();
+            let mut game = self.replay(&opening);
+            let mut scores: Vec<(Player, i32)> = Vec::new();
+            while !game.is_over() {
+                let bs = game.board();
+                let result = engine.search(bs).expect("置けるマスがない");
+                if !result.exact {
+                    scores.push((bs.turn(), result.score));
+                }
+                game.apply_move(Move::Place(result.best_move))
+                    .expect("CPUが置けないマスを選んだ");
+            }
+            let winner = game.outcome().and_then(|o| o.winner());
+            samples.extend(scores.into_iter().map(|(player, score)| {
+                let outcome = match winner {
+                    Some(w) if w == player => 1.0,
+                    Some(_) => 0.0,
+                    None => 0.5,
+                };
+                (score, outcome)
+            }));
+        }
+        samples
+    }
+
+    /// startの局面からランダムに数手打つ手順
+    fn random_opening(&mut self) -> Vec<Coord> {
+        let mut bs = self.start.clone();
+        let mut opening = Vec::new();
+        for _ in 0..RANDOM_OPENING_PLIES {
+            let moves: Vec<Coord> = bs.legal_moves().collect();
+            let c = match moves.choose(&mut self.rng) {
+                Some(&c) => c,
+                None => break,
+            };
+            opening.push(c);
+            if !bs.put(c.row, c.col) {
+                break;
+            }
+        }
+        opening
+    }
+
+    /// startの局面からopeningの手順を打った対局
+    fn replay(&self, opening: &[Coord]) -> Game {
+        let mut game = Game::new(self.start.clone());
+        for &c in opening {
+            game.apply_move(Move::Place(c)).unwrap();
+        }
+        game
+    }
+}
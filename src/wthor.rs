@@ -0,0 +1,164 @@
+//! WTHOR形式（フランスオセロ連盟が公開している対局のデータベース）の読み込み
+//!
+//! 対局は```.wtb```のファイルに、対局者の名前は```.jou```、大会の名前は```.trn```のファイルに入っている。
+//! どのファイルも16バイトのヘッダーの後に、固定長のレコードが並ぶ（数値はリトルエンディアン）。
+//! 8×8の盤面の対局のみ読める。
+
+use std::fs;
+use std::path::Path;
+
+use crate::boardstate::{BoardState, Coord};
+use crate::error::{ReplayError, WthorError};
+use crate::game::{Game, Move};
+
+/// ヘッダーの長さ
+const HEADER_LEN: usize = 16;
+
+/// ```.wtb```の1局分のレコードの長さ
+const GAME_LEN: usize = 68;
+
+/// ```.jou```の1人分のレコードの長さ
+const PLAYER_LEN: usize = 20;
+
+/// ```.trn```の1大会分のレコードの長さ
+const TOURNAMENT_LEN: usize = 26;
+
+/// ```.wtb```の1局
+///
+/// tournament、black、whiteは大会と黒・白の対局者の番号（```.trn```と```.jou```の何番目の名前か）、
+/// black_discsは終局時の黒の駒の個数、theoretical_discsは終盤を読み切った場合の黒の駒の個数、
+/// movesは打った手の順（パスは記録されない）。
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WthorGame {
+    pub tournament: u16,
+    pub black: u16,
+    pub white: u16,
+    pub black_discs: u8,
+    pub theoretical_discs: u8,
+    pub moves: Vec<Coord>,
+}
+
+/// ```.wtb```のファイルの内容
+///
+/// yearは対局が行われた年、gamesは対局の一覧。
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Wthor {
+    pub year: u16,
+    pub games: Vec<WthorGame>,
+}
+
+impl Wthor {
+    /// ```.wtb```のファイルの中身を読む
+    pub fn parse(bytes: &[u8]) -> Result<Wthor, WthorError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(WthorError::Truncated);
+        }
+        let count = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as usize;
+        let year = u16::from_le_bytes([bytes[10], bytes[11]]);
+        // 盤面の大きさは0か8なら8×8
+        let size = bytes[12];
+        if size != 0 && size != 8 {
+            return Err(WthorError::Unsupported(size));
+        }
+        let records = &bytes[HEADER_LEN..];
+        if records.len() < count * GAME_LEN {
+            return Err(WthorError::Truncated);
+        }
+        let games = records
+            .chunks_exact(GAME_LEN)
+            .take(count)
+            .enumerate()
+            .map(|(index, r)| game_record(index, r))
+            .collect::<Result<_, _>>()?;
+        Ok(Wthor { year, games })
+    }
+
+    /// ```.wtb```のファイルを読み込む
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Wthor, WthorError> {
+        Wthor::parse(&fs::read(path)?)
+    }
+}
+
+impl WthorGame {
+    /// 最初の配置から手順を適用した対局（パスは自動で行われる）
+    pub fn to_game(&self) -> Result<Game, ReplayError> {
+        let moves: Vec<Move> = self.moves.iter().map(|&c| Move::Place(c)).collect();
+        let mut game = Game::new(BoardState::new(4, false));
+        game.apply_moves(&moves)?;
+        Ok(game)
+    }
+
+    /// 最初のdepth手までを定石集（```Book::parse```）の1行の形式にしたもの
+    pub fn book_line(&self, depth: usize) -> String {
+        self.moves
+            .iter()
+            .take(depth)
+            .map(|&c| Move::Place(c).to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// index番目（0始まり）の対局のレコードを読む
+///
+/// 手は「10×行＋列」（どちらも1始まり）の1バイトで、0なら終わり。
+fn game_record(index: usize, r: &[u8]) -> Result<WthorGame, WthorError> {
+    let mut moves = Vec::new();
+    for (i, &b) in r[8..].iter().enumerate() {
+        if b == 0 {
+            break;
+        }
+        let (row, col) = (b / 10, b % 10);
+        if !(1..=8).contains(&row) || !(1..=8).contains(&col) {
+            return Err(WthorError::Move {
+                game: index,
+                index: i,
+            });
+        }
+        moves.push(Coord::new(row as usize - 1, col as usize - 1));
+    }
+    Ok(WthorGame {
+        tournament: u16::from_le_bytes([r[0], r[1]]),
+        black: u16::from_le_bytes([r[2], r[3]]),
+        white: u16::from_le_bytes([r[4], r[5]]),
+        black_discs: r[6],
+        theoretical_discs: r[7],
+        moves,
+    })
+}
+
+/// ```.jou```や```.trn```のファイルの中身から、lenバイトずつのレコードの名前を読む
+///
+/// 名前は0で終わるLatin-1の文字列。
+fn parse_names(bytes: &[u8], len: usize) -> Result<Vec<String>, WthorError> {
+    if bytes.len() < HEADER_LEN {
+        return Err(WthorError::Truncated);
+    }
+    let count = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+    let records = &bytes[HEADER_LEN..];
+    if records.len() < count * len {
+        return Err(WthorError::Truncated);
+    }
+    Ok(records
+        .chunks_exact(len)
+        .take(count)
+        .map(|r| {
+            r.iter()
+                .take_while(|&&b| b != 0)
+                .map(|&b| b as char)
+                .collect::<String>()
+                .trim()
+                .to_string()
+        })
+        .collect())
+}
+
+/// ```.jou```のファイルから対局者の名前の一覧を読み込む（```WthorGame```のblack、whiteの番号順）
+pub fn load_players<P: AsRef<Path>>(path: P) -> Result<Vec<String>, WthorError> {
+    parse_names(&fs::read(path)?, PLAYER_LEN)
+}
+
+/// ```.trn```のファイルから大会の名前の一覧を読み込む（```WthorGame```のtournamentの番号順）
+pub fn load_tournaments<P: AsRef<Path>>(path: P) -> Result<Vec<String>, WthorError> {
+    parse_names(&fs::read(path)?, TOURNAMENT_LEN)
+}